@@ -181,4 +181,8 @@ pub enum AuctionHouseError {
     // 6044
     #[msg("This sale requires exactly one signer: either the seller or the authority.")]
     SaleRequiresExactlyOneSigner,
+
+    // 6045
+    #[msg("Only bids can be refunded, listings must be canceled by the seller.")]
+    CannotRefundListing,
 }
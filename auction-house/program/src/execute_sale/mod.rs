@@ -709,6 +709,47 @@ pub fn auctioneer_execute_sale<'info>(
     program_as_signer_bump: u8,
     buyer_price: u64,
     token_size: u64,
+) -> Result<()> {
+    auctioneer_execute_full_sale(
+        ctx,
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        buyer_price,
+        token_size,
+        false,
+    )
+}
+
+/// Execute a sale settled by a keeper after an auction has ended. When neither the buyer nor the
+/// seller signs, the auctioneer authority signs and pays for the accounts the sale creates.
+pub fn auctioneer_keeper_execute_sale<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerExecuteSale<'info>>,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+) -> Result<()> {
+    auctioneer_execute_full_sale(
+        ctx,
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        buyer_price,
+        token_size,
+        true,
+    )
+}
+
+fn auctioneer_execute_full_sale<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerExecuteSale<'info>>,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+    keeper_settled: bool,
 ) -> Result<()> {
     let auction_house = &ctx.accounts.auction_house;
     let auctioneer_authority = &ctx.accounts.auctioneer_authority;
@@ -756,6 +797,7 @@ pub fn auctioneer_execute_sale<'info>(
         token_size,
         None,
         None,
+        keeper_settled,
     )
 }
 
@@ -974,6 +1016,56 @@ pub fn auctioneer_execute_partial_sale<'info>(
     token_size: u64,
     partial_order_size: Option<u64>,
     partial_order_price: Option<u64>,
+) -> Result<()> {
+    auctioneer_execute_any_partial_sale(
+        ctx,
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        buyer_price,
+        token_size,
+        partial_order_size,
+        partial_order_price,
+        false,
+    )
+}
+
+/// Execute a partial sale settled by a keeper after an auction has ended. When neither the buyer
+/// nor the seller signs, the auctioneer authority signs and pays for the accounts the sale creates.
+pub fn auctioneer_keeper_execute_partial_sale<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerExecutePartialSale<'info>>,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+    partial_order_size: Option<u64>,
+    partial_order_price: Option<u64>,
+) -> Result<()> {
+    auctioneer_execute_any_partial_sale(
+        ctx,
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        buyer_price,
+        token_size,
+        partial_order_size,
+        partial_order_price,
+        true,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn auctioneer_execute_any_partial_sale<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerExecutePartialSale<'info>>,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+    partial_order_size: Option<u64>,
+    partial_order_price: Option<u64>,
+    keeper_settled: bool,
 ) -> Result<()> {
     let auction_house = &ctx.accounts.auction_house;
     let auctioneer_authority = &ctx.accounts.auctioneer_authority;
@@ -1023,6 +1115,7 @@ pub fn auctioneer_execute_partial_sale<'info>(
         token_size,
         partial_order_size,
         partial_order_price,
+        keeper_settled,
     )
 }
 
@@ -1038,6 +1131,7 @@ fn auctioneer_execute_sale_logic<'c, 'info>(
     token_size: u64,
     partial_order_size: Option<u64>,
     partial_order_price: Option<u64>,
+    keeper_settled: bool,
 ) -> Result<()> {
     let buyer = &accounts.buyer;
     let seller = &accounts.seller;
@@ -1165,12 +1259,20 @@ fn auctioneer_execute_sale_logic<'c, 'info>(
         &[auction_house.fee_payer_bump],
     ];
 
-    let wallet_to_use = if buyer.is_signer { buyer } else { seller };
+    // A keeper settlement signed by neither the buyer nor the seller is paid for by the auctioneer
+    // authority, as long as the Auction House doesn't require sign off.
+    let wallet_to_use = if buyer.is_signer {
+        buyer.to_account_info()
+    } else if seller.is_signer || !keeper_settled {
+        seller.to_account_info()
+    } else {
+        accounts.auctioneer_authority.to_account_info()
+    };
 
     let (fee_payer, fee_payer_seeds) = get_fee_payer(
         authority,
         auction_house,
-        wallet_to_use.to_account_info(),
+        wallet_to_use,
         auction_house_fee_account.to_account_info(),
         &seeds,
     )?;
@@ -1196,8 +1298,7 @@ fn auctioneer_execute_sale_logic<'c, 'info>(
     // rent-exemption checked accounts.  The fee payer makes up the shortfall up to the amount of
     // rent for an empty account.
     if is_native {
-        let rent_shortfall =
-            verify_withdrawal(escrow_payment_account.to_account_info(), buyer_price)?;
+        let rent_shortfall = verify_withdrawal(escrow_payment_account.to_account_info(), price)?;
         if rent_shortfall > 0 {
             invoke_signed(
                 &system_instruction::transfer(
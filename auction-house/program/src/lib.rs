@@ -14,6 +14,7 @@ pub mod errors;
 pub mod execute_sale;
 pub mod pda;
 pub mod receipt;
+pub mod refund;
 pub mod sell;
 pub mod state;
 pub mod utils;
@@ -23,7 +24,7 @@ pub use state::*;
 
 use crate::{
     auctioneer::*, bid::*, cancel::*, constants::*, deposit::*, errors::AuctionHouseError,
-    execute_sale::*, receipt::*, sell::*, utils::*, withdraw::*,
+    execute_sale::*, receipt::*, refund::*, sell::*, utils::*, withdraw::*,
 };
 
 use anchor_lang::{
@@ -492,6 +493,24 @@ pub mod auction_house {
         )
     }
 
+    pub fn auctioneer_keeper_execute_sale<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerExecuteSale<'info>>,
+        escrow_payment_bump: u8,
+        _free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+    ) -> Result<()> {
+        execute_sale::auctioneer_keeper_execute_sale(
+            ctx,
+            escrow_payment_bump,
+            _free_trade_state_bump,
+            program_as_signer_bump,
+            buyer_price,
+            token_size,
+        )
+    }
+
     pub fn auctioneer_execute_partial_sale<'info>(
         ctx: Context<'_, '_, '_, 'info, AuctioneerExecutePartialSale<'info>>,
         escrow_payment_bump: u8,
@@ -514,6 +533,28 @@ pub mod auction_house {
         )
     }
 
+    pub fn auctioneer_keeper_execute_partial_sale<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerExecutePartialSale<'info>>,
+        escrow_payment_bump: u8,
+        _free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+        partial_order_size: Option<u64>,
+        partial_order_price: Option<u64>,
+    ) -> Result<()> {
+        execute_sale::auctioneer_keeper_execute_partial_sale(
+            ctx,
+            escrow_payment_bump,
+            _free_trade_state_bump,
+            program_as_signer_bump,
+            buyer_price,
+            token_size,
+            partial_order_size,
+            partial_order_price,
+        )
+    }
+
     pub fn sell<'info>(
         ctx: Context<'_, '_, '_, 'info, Sell<'info>>,
         trade_state_bump: u8,
@@ -566,6 +607,18 @@ pub mod auction_house {
         withdraw::auctioneer_withdraw(ctx, escrow_payment_bump, amount)
     }
 
+    /// Refund an outbid or otherwise displaced bid on behalf of the bidder, but with an auctioneer.
+    /// At most `amount`, capped at the bid's `buyer_price`, is returned from the bidder's escrow.
+    pub fn auctioneer_refund_bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerRefundBid<'info>>,
+        escrow_payment_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+        amount: u64,
+    ) -> Result<()> {
+        refund::auctioneer_refund_bid(ctx, escrow_payment_bump, buyer_price, token_size, amount)
+    }
+
     /// Close the escrow account of the user.
     pub fn close_escrow_account<'info>(
         ctx: Context<'_, '_, '_, 'info, CloseEscrowAccount<'info>>,
@@ -0,0 +1,251 @@
+use anchor_lang::{prelude::*, AnchorDeserialize};
+use solana_program::program_memory::sol_memset;
+
+use crate::{constants::*, errors::*, utils::*, AuctionHouse, AuthorityScope, *};
+
+/// Accounts for the [`auctioneer_refund_bid` handler](auction_house/fn.auctioneer_refund_bid.html).
+#[derive(Accounts, Clone)]
+#[instruction(escrow_payment_bump: u8, buyer_price: u64, token_size: u64)]
+pub struct AuctioneerRefundBid<'info> {
+    /// CHECK: Validated as the owner of the trade state in auctioneer_refund_bid.
+    /// Wallet of the bidder being refunded.
+    #[account(mut)]
+    pub wallet: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in auctioneer_refund_bid.
+    /// SPL token account or native SOL account to refund the bid to. If the account is a native SOL account, this is the same as the wallet address.
+    #[account(mut)]
+    pub receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Bidder escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// SPL token account the bid was placed on.
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Verified with has_one constraint on auction house account.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in ah_auctioneer_pda seeds.
+    /// The auctioneer authority - typically a PDA of the Auctioneer program running this action.
+    pub auctioneer_authority: Signer<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump=auction_house.bump,
+        has_one=authority,
+        has_one=treasury_mint
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Validated in auctioneer_refund_bid.
+    /// Buyer trade state PDA account representing the bid to be refunded.
+    #[account(mut)]
+    pub trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// The auctioneer PDA owned by Auction House storing scopes.
+    #[account(
+        seeds = [
+            AUCTIONEER.as_bytes(),
+            auction_house.key().as_ref(),
+            auctioneer_authority.key().as_ref()
+        ],
+        bump = ah_auctioneer_pda.bump
+    )]
+    pub ah_auctioneer_pda: Account<'info, Auctioneer>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Close a bid's trade state on behalf of the bidder, returning its rent to the bidder's wallet, and
+/// return `amount` from their escrow. The escrow is shared by all of the bidder's bids, so the
+/// auctioneer passes the amount this bid deposited, and no more than the bid's `buyer_price` is ever
+/// returned. Funds can only ever go back to the bidder.
+pub fn auctioneer_refund_bid<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerRefundBid<'info>>,
+    escrow_payment_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+    amount: u64,
+) -> Result<()> {
+    let wallet = &ctx.accounts.wallet;
+    let receipt_account = &ctx.accounts.receipt_account;
+    let escrow_payment_account = &ctx.accounts.escrow_payment_account;
+    let token_account = &ctx.accounts.token_account;
+    let treasury_mint = &ctx.accounts.treasury_mint;
+    let auction_house = &ctx.accounts.auction_house;
+    let auctioneer_authority = &ctx.accounts.auctioneer_authority;
+    let ah_auctioneer_pda = &ctx.accounts.ah_auctioneer_pda;
+    let trade_state = &ctx.accounts.trade_state;
+    let token_program = &ctx.accounts.token_program;
+    let system_program = &ctx.accounts.system_program;
+
+    if !auction_house.has_auctioneer {
+        return Err(AuctionHouseError::NoAuctioneerProgramSet.into());
+    }
+
+    assert_valid_auctioneer_and_scope(
+        auction_house,
+        &auctioneer_authority.key(),
+        ah_auctioneer_pda,
+        AuthorityScope::Cancel,
+    )?;
+    let amount = amount.min(buyer_price);
+    if amount > 0 {
+        assert_valid_auctioneer_and_scope(
+            auction_house,
+            &auctioneer_authority.key(),
+            ah_auctioneer_pda,
+            AuthorityScope::Withdraw,
+        )?;
+    }
+
+    if escrow_payment_bump
+        != *ctx
+            .bumps
+            .get("escrow_payment_account")
+            .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?
+    {
+        return Err(AuctionHouseError::BumpSeedNotInHashMap.into());
+    }
+
+    // Listings are canceled by their seller, only bids can be refunded.
+    if token_account.owner == wallet.key() {
+        return Err(AuctionHouseError::CannotRefundListing.into());
+    }
+
+    if trade_state.data_is_empty() {
+        return Err(AuctionHouseError::BuyerTradeStateNotValid.into());
+    }
+    let ts_bump = trade_state.try_borrow_data()?[0];
+    assert_valid_trade_state(
+        &wallet.key(),
+        auction_house,
+        buyer_price,
+        token_size,
+        &trade_state.to_account_info(),
+        &token_account.mint,
+        &token_account.key(),
+        ts_bump,
+    )?;
+
+    let curr_lamp = trade_state.lamports();
+    **trade_state.lamports.borrow_mut() = 0;
+
+    **wallet.lamports.borrow_mut() = wallet
+        .lamports()
+        .checked_add(curr_lamp)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    #[allow(clippy::explicit_auto_deref)]
+    sol_memset(*trade_state.try_borrow_mut_data()?, 0, TRADE_STATE_SIZE);
+
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let auction_house_key = auction_house.key();
+    let wallet_key = wallet.key();
+
+    if treasury_mint.key() == spl_token::native_mint::id() {
+        assert_keys_equal(receipt_account.key(), wallet.key())?;
+
+        // The bidder may already have withdrawn part of the escrow, refund whatever is left of the bid.
+        let rent_minimum = (Rent::get()?).minimum_balance(escrow_payment_account.data_len());
+        let amount = amount.min(
+            escrow_payment_account
+                .lamports()
+                .saturating_sub(rent_minimum),
+        );
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let escrow_signer_seeds = [
+            PREFIX.as_bytes(),
+            auction_house_key.as_ref(),
+            wallet_key.as_ref(),
+            &[escrow_payment_bump],
+        ];
+
+        invoke_signed(
+            &system_instruction::transfer(
+                &escrow_payment_account.key(),
+                &receipt_account.key(),
+                amount,
+            ),
+            &[
+                escrow_payment_account.to_account_info(),
+                receipt_account.to_account_info(),
+                system_program.to_account_info(),
+            ],
+            &[&escrow_signer_seeds],
+        )?;
+    } else {
+        let rec_acct = assert_is_ata(
+            &receipt_account.to_account_info(),
+            &wallet.key(),
+            &treasury_mint.key(),
+        )?;
+
+        // make sure you cant get rugged
+        if rec_acct.delegate.is_some() {
+            return Err(AuctionHouseError::BuyerATACannotHaveDelegate.into());
+        }
+
+        let escrow_account: spl_token::state::Account =
+            assert_initialized(&escrow_payment_account.to_account_info())?;
+        let amount = amount.min(escrow_account.amount);
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let ah_seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            &[auction_house.bump],
+        ];
+
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                &escrow_payment_account.key(),
+                &receipt_account.key(),
+                &auction_house.key(),
+                &[],
+                amount,
+            )?,
+            &[
+                escrow_payment_account.to_account_info(),
+                receipt_account.to_account_info(),
+                token_program.to_account_info(),
+                auction_house.to_account_info(),
+            ],
+            &[&ah_seeds],
+        )?;
+    }
+
+    Ok(())
+}
@@ -1,17 +1,22 @@
 //! Create a private bids.
 
-use anchor_lang::{prelude::*, AnchorDeserialize};
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_lang::{prelude::*, AnchorDeserialize, InstructionData};
+use anchor_spl::token::{spl_token, Mint, Token, TokenAccount};
 
 use mpl_auction_house::{
     self,
-    constants::{AUCTIONEER, FEE_PAYER, PREFIX},
-    cpi::accounts::AuctioneerBuy as AHBuy,
+    constants::{AUCTIONEER, FEE_PAYER, PREFIX, SIGNER},
+    cpi::accounts::{
+        AuctioneerBuy as AHBuy, AuctioneerExecuteSale as AHExecuteSale,
+        AuctioneerRefundBid as AHRefundBid,
+    },
     program::AuctionHouse as AuctionHouseProgram,
     AuctionHouse,
 };
 
-use crate::{constants::*, sell::config::*, utils::*};
+use solana_program::program::invoke_signed;
+
+use crate::{constants::*, errors::*, events::*, sell::config::*, utils::*};
 
 /// Accounts for the [`private_bid_with_auctioneer` handler](fn.private_bid_with_auctioneer.html).
 #[derive(Accounts)]
@@ -92,7 +97,7 @@ pub struct AuctioneerBuy<'info> {
 
     /// CHECK: Not dangerous. Account seeds checked in constraint.
     /// Buyer trade state PDA.
-    #[account(mut, seeds = [PREFIX.as_bytes(), wallet.key().as_ref(), auction_house.key().as_ref(), token_account.key().as_ref(), treasury_mint.key().as_ref(), token_account.mint.as_ref(), buyer_price.to_le_bytes().as_ref(), token_size.to_le_bytes().as_ref()], seeds::program=auction_house_program, bump = trade_state_bump)]
+    #[account(mut, seeds = [PREFIX.as_bytes(), wallet.key().as_ref(), auction_house.key().as_ref(), token_account.key().as_ref(), treasury_mint.key().as_ref(), token_account.mint.as_ref(), buyer_price.to_le_bytes().as_ref(), listing_config.bid_size(token_size).to_le_bytes().as_ref()], seeds::program=auction_house_program, bump = trade_state_bump)]
     buyer_trade_state: UncheckedAccount<'info>,
 
     /// CHECK: Is used as a seed for ah_auctioneer_pda.
@@ -124,14 +129,124 @@ pub fn auctioneer_buy<'info>(
     auctioneer_authority_bump: u8,
     buyer_price: u64,
     token_size: u64,
+    allowlist_proof: Option<Vec<[u8; 32]>>,
+    keep_escrow_on_outbid: Option<bool>,
 ) -> Result<()> {
-    assert_auction_active(&ctx.accounts.listing_config)?;
-    assert_higher_bid(&ctx.accounts.listing_config, buyer_price)?;
-    assert_exceeds_reserve_price(&ctx.accounts.listing_config, buyer_price)?;
-    process_time_extension(&mut ctx.accounts.listing_config)?;
-    ctx.accounts.listing_config.highest_bid.amount = buyer_price;
-    ctx.accounts.listing_config.highest_bid.buyer_trade_state =
-        ctx.accounts.buyer_trade_state.key();
+    // Gated auctions only take bids from wallets on the allowlist or holding the collection.
+    let wallet = ctx.accounts.wallet.key();
+    let remaining_accounts = assert_bid_gates(
+        &ctx.accounts.listing_config,
+        &wallet,
+        allowlist_proof,
+        ctx.remaining_accounts,
+    )?;
+
+    let previous_bidder = ctx.accounts.listing_config.highest_bidder;
+    let previous_bid = ctx.accounts.listing_config.highest_bid.amount;
+    let bid = BidBookEntry {
+        bidder: wallet,
+        amount: buyer_price,
+        buyer_trade_state: ctx.accounts.buyer_trade_state.key(),
+        keeps_escrow: keep_escrow_on_outbid.unwrap_or(false),
+        deposit: 0,
+    };
+    let outbid = match ctx.accounts.listing_config.auction_type {
+        AuctionType::English => {
+            assert_auction_active(&ctx.accounts.listing_config)?;
+            if is_bought_now(&ctx.accounts.listing_config) {
+                return err!(AuctioneerError::AuctionEnded);
+            }
+            let buy_now = is_buy_now_bid(&ctx.accounts.listing_config, buyer_price);
+            if buy_now {
+                // Paying the buy now price wins outright, so only require it to beat the current bid.
+                if buyer_price <= ctx.accounts.listing_config.highest_bid.amount {
+                    return err!(AuctioneerError::BidTooLow);
+                }
+            } else {
+                assert_higher_bid(&ctx.accounts.listing_config, buyer_price)?;
+            }
+            assert_exceeds_reserve_price(&ctx.accounts.listing_config, buyer_price)?;
+            if buy_now {
+                ctx.accounts.listing_config.end_time = Clock::get()?.unix_timestamp;
+            } else {
+                process_time_extension(&mut ctx.accounts.listing_config, buyer_price)?;
+            }
+            Some(replace_highest_bid(&mut ctx.accounts.listing_config, bid))
+        }
+        AuctionType::Dutch(_) => {
+            assert_auction_active(&ctx.accounts.listing_config)?;
+            assert_dutch_auction_price(&ctx.accounts.listing_config, buyer_price)?;
+            // The first bid at or above the current price wins, so end the auction now and settle
+            // it once the bid is placed.
+            ctx.accounts.listing_config.end_time = Clock::get()?.unix_timestamp;
+            Some(replace_highest_bid(&mut ctx.accounts.listing_config, bid))
+        }
+        AuctionType::SealedBid(_) => {
+            // Sealed bids are committed and revealed separately; the highest bidder places the
+            // only public bid at the clearing price once the reveal period is over.
+            assert_reveal_period_over(&ctx.accounts.listing_config)?;
+            assert_sealed_bid_winner(
+                &ctx.accounts.listing_config,
+                ctx.accounts.wallet.key(),
+                buyer_price,
+            )?;
+            Some(replace_highest_bid(&mut ctx.accounts.listing_config, bid))
+        }
+        AuctionType::Edition(_) => {
+            if Clock::get()?.unix_timestamp > ctx.accounts.listing_config.end_time {
+                Some(process_edition_claim(
+                    &mut ctx.accounts.listing_config,
+                    ctx.accounts.wallet.key(),
+                    buyer_price,
+                    ctx.accounts.buyer_trade_state.key(),
+                )?)
+            } else {
+                assert_auction_active(&ctx.accounts.listing_config)?;
+                assert_exceeds_reserve_price(&ctx.accounts.listing_config, buyer_price)?;
+                process_edition_bid(&mut ctx.accounts.listing_config, bid)?
+            }
+        }
+    };
+    let bid_size = ctx.accounts.listing_config.bid_size(token_size);
+
+    if ctx.accounts.listing_config.records_bid_history {
+        record_bid_history(
+            &mut ctx.accounts.listing_config,
+            BidHistoryEntry {
+                bidder: wallet,
+                amount: buyer_price,
+                timestamp: Clock::get()?.unix_timestamp,
+            },
+        );
+    }
+    emit!(BidPlacedEvent {
+        listing_config: ctx.accounts.listing_config.key(),
+        bidder: wallet,
+        amount: buyer_price,
+        buyer_trade_state: ctx.accounts.buyer_trade_state.key(),
+        previous_bidder,
+        previous_bid,
+    });
+
+    // The outbid bid can no longer win, release it before the new bid is funded so a bidder
+    // raising their own bid reuses or refunds their escrow first.
+    // Bids carried over from V0 listings don't record their wallet, their bidder cancels them.
+    if let Some(outbid) = outbid {
+        if outbid.buyer_trade_state != Pubkey::default() && outbid.bidder != Pubkey::default() {
+            refund_bid(
+                ctx.accounts,
+                remaining_accounts,
+                &outbid,
+                auctioneer_authority_bump,
+                bid_size,
+            )?;
+        }
+    }
+
+    // The escrow is shared by all of the bidder's bids, so record what this bid adds to it as the
+    // most it can be refunded when outbid.
+    let is_native = ctx.accounts.treasury_mint.key() == spl_token::native_mint::id();
+    let escrow_balance = get_escrow_balance(&ctx.accounts.escrow_payment_account, is_native)?;
 
     let cpi_program = ctx.accounts.auction_house_program.to_account_info();
     let cpi_accounts = AHBuy {
@@ -170,6 +285,227 @@ pub fn auctioneer_buy<'info>(
         trade_state_bump,
         escrow_payment_bump,
         buyer_price,
+        bid_size,
+    )?;
+
+    let deposit = get_escrow_balance(&ctx.accounts.escrow_payment_account, is_native)?
+        .saturating_sub(escrow_balance);
+    let buyer_trade_state = ctx.accounts.buyer_trade_state.key();
+    record_bid_deposit(&mut ctx.accounts.listing_config, buyer_trade_state, deposit);
+
+    if !matches!(
+        ctx.accounts.listing_config.auction_type,
+        AuctionType::Dutch(_)
+    ) {
+        return Ok(());
+    }
+
+    execute_dutch_sale(
+        ctx.accounts,
+        remaining_accounts,
+        escrow_payment_bump,
+        auctioneer_authority_bump,
+        buyer_price,
+        token_size,
+    )?;
+
+    emit!(SaleSettledEvent {
+        listing_config: ctx.accounts.listing_config.key(),
+        seller: ctx.accounts.seller.key(),
+        buyer: wallet,
+        price: buyer_price,
+        token_size,
+        keeper: Pubkey::default(),
+    });
+
+    // Close the Listing Config account. The buyer settled the sale, so the keeper reward goes back
+    // to the seller.
+    let listing_config = &ctx.accounts.listing_config.to_account_info();
+    let seller = &ctx.accounts.seller.to_account_info();
+
+    let listing_config_lamports = listing_config.lamports();
+    **seller.lamports.borrow_mut() = seller
+        .lamports()
+        .checked_add(listing_config_lamports)
+        .ok_or(AuctioneerError::NumericalOverflow)?;
+    **listing_config.lamports.borrow_mut() = 0;
+
+    let mut source_data = listing_config.data.borrow_mut();
+    source_data.fill(0);
+
+    Ok(())
+}
+
+/// Settle the Dutch auction won by a bid through Auction House. The token mint, seller trade state,
+/// free seller trade state, seller payment receipt account, buyer receipt token account, Auction
+/// House treasury, program as signer and associated token program are expected as the remaining
+/// accounts after any gate accounts, followed by the creators of the token. The seller and token
+/// account must be writable.
+#[inline(never)]
+fn execute_dutch_sale<'info>(
+    accounts: &AuctioneerBuy<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    escrow_payment_bump: u8,
+    auctioneer_authority_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+) -> Result<()> {
+    if remaining_accounts.len() < 8 {
+        return err!(AuctioneerError::MissingSaleAccounts);
+    }
+    let (sale_accounts, creators) = remaining_accounts.split_at(8);
+    let token_mint = &sale_accounts[0];
+    let seller_trade_state = &sale_accounts[1];
+    let free_trade_state = &sale_accounts[2];
+    let seller_payment_receipt_account = &sale_accounts[3];
+    let buyer_receipt_token_account = &sale_accounts[4];
+    let auction_house_treasury = &sale_accounts[5];
+    let program_as_signer = &sale_accounts[6];
+    let ata_program = &sale_accounts[7];
+
+    let (_, program_as_signer_bump) = Pubkey::find_program_address(
+        &[PREFIX.as_bytes(), SIGNER.as_bytes()],
+        &mpl_auction_house::id(),
+    );
+
+    let cpi_program = accounts.auction_house_program.to_account_info();
+    let cpi_accounts = AHExecuteSale {
+        buyer: accounts.wallet.to_account_info(),
+        seller: accounts.seller.to_account_info(),
+        token_account: accounts.token_account.to_account_info(),
+        token_mint: token_mint.to_account_info(),
+        metadata: accounts.metadata.to_account_info(),
+        treasury_mint: accounts.treasury_mint.to_account_info(),
+        escrow_payment_account: accounts.escrow_payment_account.to_account_info(),
+        seller_payment_receipt_account: seller_payment_receipt_account.to_account_info(),
+        buyer_receipt_token_account: buyer_receipt_token_account.to_account_info(),
+        auction_house: accounts.auction_house.to_account_info(),
+        auction_house_fee_account: accounts.auction_house_fee_account.to_account_info(),
+        auction_house_treasury: auction_house_treasury.to_account_info(),
+        buyer_trade_state: accounts.buyer_trade_state.to_account_info(),
+        seller_trade_state: seller_trade_state.to_account_info(),
+        free_trade_state: free_trade_state.to_account_info(),
+        authority: accounts.authority.to_account_info(),
+        auctioneer_authority: accounts.auctioneer_authority.to_account_info(),
+        ah_auctioneer_pda: accounts.ah_auctioneer_pda.to_account_info(),
+        token_program: accounts.token_program.to_account_info(),
+        system_program: accounts.system_program.to_account_info(),
+        ata_program: ata_program.to_account_info(),
+        program_as_signer: program_as_signer.to_account_info(),
+        rent: accounts.rent.to_account_info(),
+    };
+
+    let execute_sale_data = mpl_auction_house::instruction::AuctioneerExecuteSale {
+        escrow_payment_bump,
+        // Unused by Auction House, which derives the free trade state's bump itself.
+        _free_trade_state_bump: 0,
+        program_as_signer_bump,
+        buyer_price,
         token_size,
+    };
+
+    let mut cpi_account_metas: Vec<AccountMeta> = cpi_accounts
+        .to_account_metas(None)
+        .into_iter()
+        .zip(cpi_accounts.to_account_infos())
+        .map(|mut pair| {
+            pair.0.is_signer = pair.1.is_signer;
+            if pair.0.pubkey == accounts.auctioneer_authority.key() {
+                pair.0.is_signer = true;
+            }
+            pair.0
+        })
+        .collect();
+    cpi_account_metas.append(&mut creators.to_vec().to_account_metas(None));
+
+    let mut cpi_account_infos: Vec<AccountInfo> = cpi_accounts.to_account_infos();
+    cpi_account_infos.append(&mut creators.to_vec());
+
+    let ix = solana_program::instruction::Instruction {
+        program_id: cpi_program.key(),
+        accounts: cpi_account_metas,
+        data: execute_sale_data.data(),
+    };
+
+    let ah_key = accounts.auction_house.key();
+    let auctioneer_seeds = [
+        AUCTIONEER.as_bytes(),
+        ah_key.as_ref(),
+        &[auctioneer_authority_bump],
+    ];
+
+    invoke_signed(&ix, &cpi_account_infos, &[&auctioneer_seeds])?;
+
+    Ok(())
+}
+
+/// Refund an outbid bid through Auction House. The bid's wallet, receipt account, escrow payment
+/// account and trade state may be passed as the first four remaining accounts after any gate
+/// accounts. Without them, or when the receipt account can't take the refund, the outbid funds
+/// stay in the bidder's escrow to be withdrawn and the bid is left for its bidder to cancel, so
+/// the outbid bidder can't block new bids.
+fn refund_bid<'info>(
+    accounts: &AuctioneerBuy<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    outbid: &BidBookEntry,
+    auctioneer_authority_bump: u8,
+    bid_size: u64,
+) -> Result<()> {
+    let (wallet, receipt_account, escrow_payment_account, trade_state) = match remaining_accounts {
+        [wallet, receipt_account, escrow_payment_account, trade_state, ..] => {
+            (wallet, receipt_account, escrow_payment_account, trade_state)
+        }
+        _ => return Ok(()),
+    };
+    if wallet.key() != outbid.bidder || trade_state.key() != outbid.buyer_trade_state {
+        return err!(AuctioneerError::InvalidPreviousBidAccounts);
+    }
+    // Bids canceled or already refunded have nothing left to release.
+    let treasury_mint = accounts.treasury_mint.key();
+    if trade_state.data_is_empty()
+        || !can_receive_refund(receipt_account, &wallet.key(), &treasury_mint)
+    {
+        return Ok(());
+    }
+
+    let ah_key = accounts.auction_house.key();
+    let (_, escrow_payment_bump) = Pubkey::find_program_address(
+        &[PREFIX.as_bytes(), ah_key.as_ref(), wallet.key().as_ref()],
+        &mpl_auction_house::id(),
+    );
+
+    let cpi_program = accounts.auction_house_program.to_account_info();
+    let cpi_accounts = AHRefundBid {
+        wallet: wallet.to_account_info(),
+        receipt_account: receipt_account.to_account_info(),
+        escrow_payment_account: escrow_payment_account.to_account_info(),
+        token_account: accounts.token_account.to_account_info(),
+        treasury_mint: accounts.treasury_mint.to_account_info(),
+        authority: accounts.authority.to_account_info(),
+        auctioneer_authority: accounts.auctioneer_authority.to_account_info(),
+        auction_house: accounts.auction_house.to_account_info(),
+        trade_state: trade_state.to_account_info(),
+        ah_auctioneer_pda: accounts.ah_auctioneer_pda.to_account_info(),
+        token_program: accounts.token_program.to_account_info(),
+        system_program: accounts.system_program.to_account_info(),
+    };
+
+    let auctioneer_seeds = [
+        AUCTIONEER.as_bytes(),
+        ah_key.as_ref(),
+        &[auctioneer_authority_bump],
+    ];
+
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    mpl_auction_house::cpi::auctioneer_refund_bid(
+        cpi_ctx.with_signer(&[&auctioneer_seeds]),
+        escrow_payment_bump,
+        outbid.amount,
+        bid_size,
+        if outbid.keeps_escrow {
+            0
+        } else {
+            outbid.deposit
+        },
     )
 }
@@ -10,7 +10,7 @@ use mpl_auction_house::{
 };
 use solana_program::program::invoke_signed;
 
-use crate::{constants::*, errors::*, sell::config::*};
+use crate::{constants::*, errors::*, events::*, sell::config::*, utils::*};
 
 /// Accounts for the [`cancel` handler](auction_house/fn.cancel.html).
 #[derive(Accounts, Clone)]
@@ -98,12 +98,56 @@ pub fn auctioneer_cancel<'info>(
     buyer_price: u64,
     token_size: u64,
 ) -> Result<()> {
-    if !ctx.accounts.listing_config.allow_high_bid_cancel
-        && (ctx.accounts.trade_state.key()
-            == ctx.accounts.listing_config.highest_bid.buyer_trade_state)
-    {
+    let trade_state_key = ctx.accounts.trade_state.key();
+    let is_winning_bid = trade_state_key
+        == ctx.accounts.listing_config.highest_bid.buyer_trade_state
+        || ctx
+            .accounts
+            .listing_config
+            .bid_book
+            .iter()
+            .any(|entry| entry.buyer_trade_state == trade_state_key);
+    if !ctx.accounts.listing_config.allow_high_bid_cancel && is_winning_bid {
         return err!(AuctioneerError::CannotCancelHighestBid);
     }
+    // The seller's cancellation rule applies once a bid meets the reserve price, unless that bid
+    // can no longer be settled.
+    let is_listing = ctx.accounts.token_account.owner == ctx.accounts.wallet.key();
+    let mut cancel_penalty = 0;
+    if is_listing
+        && is_reserve_met(&ctx.accounts.listing_config)
+        && !is_highest_bid_unsettleable(
+            &ctx.accounts.listing_config,
+            &ctx.accounts.auction_house.key(),
+            &ctx.accounts.auction_house.treasury_mint,
+            ctx.remaining_accounts.get(1..).unwrap_or_default(),
+            Clock::get()?.unix_timestamp,
+        )?
+    {
+        match ctx.accounts.listing_config.seller_cancel_rule {
+            SellerCancelRule::Anytime => {}
+            SellerCancelRule::BeforeReserveMet => {
+                return err!(AuctioneerError::CannotCancelListingWithBids)
+            }
+            SellerCancelRule::WithPenalty(penalty) => cancel_penalty = penalty,
+        }
+    }
+
+    // A canceled bid gives up its place in an edition auction's bid book, or as the highest bid.
+    ctx.accounts
+        .listing_config
+        .bid_book
+        .retain(|entry| entry.buyer_trade_state != trade_state_key);
+    if !is_listing && trade_state_key == ctx.accounts.listing_config.highest_bid.buyer_trade_state {
+        clear_highest_bid(&mut ctx.accounts.listing_config);
+    }
+
+    // Bids on edition auctions are for a single unit.
+    let trade_state_size = if is_listing {
+        token_size
+    } else {
+        ctx.accounts.listing_config.bid_size(token_size)
+    };
 
     let cpi_program = ctx.accounts.auction_house_program.to_account_info();
     let cpi_accounts = AHCancel {
@@ -121,7 +165,7 @@ pub fn auctioneer_cancel<'info>(
 
     let cancel_data = mpl_auction_house::instruction::AuctioneerCancel {
         buyer_price,
-        token_size,
+        token_size: trade_state_size,
     };
 
     let ix = solana_program::instruction::Instruction {
@@ -154,10 +198,36 @@ pub fn auctioneer_cancel<'info>(
 
     invoke_signed(&ix, &cpi_accounts.to_account_infos(), &[&auctioneer_seeds])?;
 
+    emit!(CanceledEvent {
+        listing_config: ctx.accounts.listing_config.key(),
+        wallet: ctx.accounts.wallet.key(),
+        trade_state: trade_state_key,
+        is_listing,
+    });
+
+    // Pay the penalty held by the Listing Config to the highest bidder, passed as a remaining account.
+    if cancel_penalty > 0 {
+        let listing_config = &ctx.accounts.listing_config.to_account_info();
+        let highest_bidder = ctx
+            .remaining_accounts
+            .first()
+            .filter(|account| {
+                account.key() == ctx.accounts.listing_config.highest_bidder && account.is_writable
+            })
+            .ok_or(AuctioneerError::InvalidHighestBidderAccount)?;
+
+        **listing_config.lamports.borrow_mut() = listing_config
+            .lamports()
+            .checked_sub(cancel_penalty)
+            .ok_or(AuctioneerError::NumericalOverflow)?;
+        **highest_bidder.lamports.borrow_mut() = highest_bidder
+            .lamports()
+            .checked_add(cancel_penalty)
+            .ok_or(AuctioneerError::NumericalOverflow)?;
+    }
+
     // Close the Listing Config account if the seller is canceling their listing.
-    if ctx.accounts.token_account.owner == ctx.accounts.wallet.key()
-        && ctx.accounts.wallet.is_signer
-    {
+    if is_listing && ctx.accounts.wallet.is_signer {
         let listing_config = &ctx.accounts.listing_config.to_account_info();
         let seller = &ctx.accounts.seller.to_account_info();
 
@@ -165,7 +235,7 @@ pub fn auctioneer_cancel<'info>(
         **seller.lamports.borrow_mut() = seller
             .lamports()
             .checked_add(listing_config_lamports)
-            .unwrap();
+            .ok_or(AuctioneerError::NumericalOverflow)?;
         **listing_config.lamports.borrow_mut() = 0;
 
         let mut source_data = listing_config.data.borrow_mut();
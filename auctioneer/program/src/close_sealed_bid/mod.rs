@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::{commit_bid::sealed_bid::*, constants::*, sell::config::*, utils::*};
+
+/// Accounts for the [`close_sealed_bid` handler](fn.auctioneer_close_sealed_bid.html).
+#[derive(Accounts)]
+pub struct AuctioneerCloseSealedBid<'info> {
+    /// CHECK: Checked against the sealed bid, and closed once the listing is sold or canceled.
+    /// The Listing Config of the sealed-bid auction.
+    #[account(address = sealed_bid.listing_config)]
+    pub listing_config: UncheckedAccount<'info>,
+
+    /// The sealed bid being closed, its rent is returned to the bidder.
+    #[account(
+        mut,
+        close=wallet,
+        seeds=[
+            SEALED_BID.as_bytes(),
+            sealed_bid.listing_config.as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump=sealed_bid.bump,
+        constraint = sealed_bid.bidder == wallet.key(),
+    )]
+    pub sealed_bid: Account<'info, SealedBid>,
+
+    /// The deposits locked in the bidder's escrow by their open sealed bids.
+    #[account(
+        mut,
+        seeds=[
+            ESCROW_LOCK.as_bytes(),
+            sealed_bid.auction_house.as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump=escrow_lock.bump,
+    )]
+    pub escrow_lock: Account<'info, EscrowLock>,
+
+    /// The bidder closing their sealed bid.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+}
+
+/// Close a sealed bid that can no longer win, releasing its deposit so it can be withdrawn.
+/// Every sealed bid is released once its listing is sold or canceled. Before that the two highest
+/// bidders stay locked while they can claim the item, and the winner until the sale is executed.
+pub fn auctioneer_close_sealed_bid<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerCloseSealedBid<'info>>,
+) -> Result<()> {
+    let listing_config_info = ctx.accounts.listing_config.to_account_info();
+    if listing_config_info.lamports() > 0 && listing_config_info.owner == &crate::id() {
+        let listing_config = Account::<ListingConfig>::try_from(&listing_config_info)?;
+        assert_sealed_bid_released(&listing_config, ctx.accounts.wallet.key())?;
+    }
+
+    let escrow_lock = &mut ctx.accounts.escrow_lock;
+    escrow_lock.locked = escrow_lock
+        .locked
+        .saturating_sub(ctx.accounts.sealed_bid.deposit);
+
+    Ok(())
+}
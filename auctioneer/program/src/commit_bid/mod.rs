@@ -0,0 +1,212 @@
+pub mod sealed_bid;
+
+use anchor_lang::{prelude::*, AnchorDeserialize};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use mpl_auction_house::{
+    self,
+    constants::{AUCTIONEER, FEE_PAYER, PREFIX},
+    cpi::accounts::AuctioneerDeposit as AHDeposit,
+    program::AuctionHouse as AuctionHouseProgram,
+    AuctionHouse,
+};
+
+use crate::{
+    commit_bid::sealed_bid::*, constants::*, errors::*, events::*, sell::config::*, utils::*,
+};
+
+/// Accounts for the [`commit_bid` handler](fn.auctioneer_commit_bid.html).
+#[derive(Accounts)]
+#[instruction(escrow_payment_bump: u8, auctioneer_authority_bump: u8, token_size: u64)]
+pub struct AuctioneerCommitBid<'info> {
+    /// Auction House Program
+    pub auction_house_program: Program<'info, AuctionHouseProgram>,
+
+    // Accounts used for Auctioneer
+    /// The Listing Config used for listing settings
+    #[account(
+        seeds=[
+            LISTING_CONFIG.as_bytes(),
+            seller.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &token_size.to_le_bytes()
+        ],
+        bump=listing_config.bump,
+    )]
+    pub listing_config: Box<Account<'info, ListingConfig>>,
+
+    /// The sealed bid storing the bidder's commitment.
+    #[account(
+        init,
+        payer=wallet,
+        space=SEALED_BID_SIZE,
+        seeds=[
+            SEALED_BID.as_bytes(),
+            listing_config.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump,
+    )]
+    pub sealed_bid: Account<'info, SealedBid>,
+
+    /// The deposits locked in the bidder's escrow by their open sealed bids.
+    #[account(
+        init_if_needed,
+        payer=wallet,
+        space=ESCROW_LOCK_SIZE,
+        seeds=[
+            ESCROW_LOCK.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump,
+    )]
+    pub escrow_lock: Account<'info, EscrowLock>,
+
+    /// The seller of the NFT
+    /// CHECK: Checked via listing config seeds
+    pub seller: UncheckedAccount<'info>,
+
+    /// SPL token account of the listing.
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    // Accounts passed into Auction House CPI call
+    /// User wallet account.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Verified through CPI
+    /// User SOL or SPL account to transfer funds from.
+    #[account(mut)]
+    pub payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Verified through CPI
+    /// SPL token account transfer authority.
+    pub transfer_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(mut, seeds=[PREFIX.as_bytes(), auction_house.key().as_ref(), wallet.key().as_ref()], seeds::program=auction_house_program, bump=escrow_payment_bump)]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Verified through CPI
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(seeds=[PREFIX.as_bytes(), auction_house.creator.as_ref(), auction_house.treasury_mint.as_ref()], seeds::program=auction_house_program, bump=auction_house.bump, has_one=authority, has_one=treasury_mint, has_one=auction_house_fee_account)]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(mut, seeds=[PREFIX.as_bytes(), auction_house.key().as_ref(), FEE_PAYER.as_bytes()], seeds::program=auction_house_program, bump=auction_house.fee_payer_bump)]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Verified through CPI
+    /// The auctioneer program PDA running this auction.
+    pub auctioneer_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// The auctioneer PDA owned by Auction House storing scopes.
+    #[account(
+        seeds = [
+            AUCTIONEER.as_bytes(),
+            auction_house.key().as_ref(),
+            auctioneer_authority.key().as_ref()
+        ],
+        seeds::program=auction_house_program,
+        bump = ah_auctioneer_pda.bump,
+    )]
+    pub ah_auctioneer_pda: Account<'info, mpl_auction_house::Auctioneer>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Commit a hidden bid on a sealed-bid auction and deposit `deposit` into the bidder's escrow to back it.
+pub fn auctioneer_commit_bid<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerCommitBid<'info>>,
+    escrow_payment_bump: u8,
+    auctioneer_authority_bump: u8,
+    _token_size: u64,
+    commitment: [u8; 32],
+    deposit: u64,
+    allowlist_proof: Option<Vec<[u8; 32]>>,
+) -> Result<()> {
+    get_sealed_bid_config(&ctx.accounts.listing_config)?;
+    assert_bid_gates(
+        &ctx.accounts.listing_config,
+        &ctx.accounts.wallet.key(),
+        allowlist_proof,
+        ctx.remaining_accounts,
+    )?;
+    assert_auction_active(&ctx.accounts.listing_config)?;
+    assert_exceeds_reserve_price(&ctx.accounts.listing_config, deposit)?;
+
+    let sealed_bid = &mut ctx.accounts.sealed_bid;
+    sealed_bid.listing_config = ctx.accounts.listing_config.key();
+    sealed_bid.auction_house = ctx.accounts.auction_house.key();
+    sealed_bid.bidder = ctx.accounts.wallet.key();
+    sealed_bid.commitment = commitment;
+    sealed_bid.deposit = deposit;
+    sealed_bid.bump = *ctx
+        .bumps
+        .get("sealed_bid")
+        .ok_or(AuctioneerError::BumpSeedNotInHashMap)?;
+
+    // The deposit stays in escrow until the sealed bid is closed.
+    let escrow_lock = &mut ctx.accounts.escrow_lock;
+    escrow_lock.locked = escrow_lock
+        .locked
+        .checked_add(deposit)
+        .ok_or(AuctioneerError::NumericalOverflow)?;
+    escrow_lock.bump = *ctx
+        .bumps
+        .get("escrow_lock")
+        .ok_or(AuctioneerError::BumpSeedNotInHashMap)?;
+    emit!(SealedBidCommittedEvent {
+        listing_config: sealed_bid.listing_config,
+        bidder: sealed_bid.bidder,
+        deposit,
+    });
+
+    let cpi_program = ctx.accounts.auction_house_program.to_account_info();
+    let cpi_accounts = AHDeposit {
+        wallet: ctx.accounts.wallet.to_account_info(),
+        payment_account: ctx.accounts.payment_account.to_account_info(),
+        transfer_authority: ctx.accounts.transfer_authority.to_account_info(),
+        escrow_payment_account: ctx.accounts.escrow_payment_account.to_account_info(),
+        treasury_mint: ctx.accounts.treasury_mint.to_account_info(),
+        auction_house: ctx.accounts.auction_house.to_account_info(),
+        auction_house_fee_account: ctx.accounts.auction_house_fee_account.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+        auctioneer_authority: ctx.accounts.auctioneer_authority.to_account_info(),
+        ah_auctioneer_pda: ctx.accounts.ah_auctioneer_pda.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        rent: ctx.accounts.rent.to_account_info(),
+    };
+
+    let auction_house = &ctx.accounts.auction_house;
+    let ah_key = auction_house.key();
+
+    let auctioneer_seeds = [
+        AUCTIONEER.as_bytes(),
+        ah_key.as_ref(),
+        &[auctioneer_authority_bump],
+    ];
+
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    mpl_auction_house::cpi::auctioneer_deposit(
+        cpi_ctx.with_signer(&[&auctioneer_seeds]),
+        escrow_payment_bump,
+        deposit,
+    )
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+pub const SEALED_BID_SIZE: usize = 8 + 32 + 32 + 32 + 32 + 8 + 1 + 1;
+pub const ESCROW_LOCK_SIZE: usize = 8 + 8 + 1;
+
+/// A hidden bid on a sealed-bid auction listing, revealed after the listing's `end_time`.
+#[account]
+pub struct SealedBid {
+    pub listing_config: Pubkey,
+    pub auction_house: Pubkey,
+    pub bidder: Pubkey,
+    /// `keccak(price || salt)` of the hidden bid.
+    pub commitment: [u8; 32],
+    /// Amount deposited into the bidder's escrow when committing, the upper bound on the bid.
+    /// It stays locked in the escrow until the sealed bid is closed.
+    pub deposit: u64,
+    pub revealed: bool,
+    pub bump: u8,
+}
+
+/// The deposits of a wallet's open sealed bids on an Auction House, which can't be withdrawn from
+/// its escrow until each sealed bid is closed.
+#[account]
+pub struct EscrowLock {
+    pub locked: u64,
+    pub bump: u8,
+}
@@ -1,2 +1,8 @@
 pub const LISTING_CONFIG: &str = "listing_config";
+pub const SEALED_BID: &str = "sealed_bid";
+pub const ESCROW_LOCK: &str = "escrow_lock";
 pub const AUCTIONEER_BUYER_PRICE: u64 = u64::MAX;
+pub const BASIS_POINTS: u64 = 10_000;
+pub const DUTCH_DECAY_PRECISION: u128 = 1_000_000_000_000;
+/// Seconds after `end_time` the winning bid has to be settled before the seller may cancel anyway.
+pub const SETTLEMENT_GRACE_PERIOD: i64 = 7 * 24 * 60 * 60;
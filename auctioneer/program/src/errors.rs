@@ -41,4 +41,112 @@ pub enum AuctioneerError {
     // 6009
     #[msg("The highest bidder is not allowed to cancel")]
     CannotCancelHighestBid,
+
+    // 6010
+    #[msg("The Dutch auction configuration is invalid")]
+    InvalidDutchAuctionConfig,
+
+    // 6011
+    #[msg("The bid must be at least the current Dutch auction price")]
+    BelowDutchAuctionPrice,
+
+    // 6012
+    #[msg("The listing is not a Dutch auction")]
+    NotDutchAuction,
+
+    // 6013
+    #[msg("The listing is not a sealed-bid auction")]
+    NotSealedBidAuction,
+
+    // 6014
+    #[msg("The sealed-bid auction configuration is invalid")]
+    InvalidSealedBidConfig,
+
+    // 6015
+    #[msg("The revealed price and salt do not match the sealed bid commitment")]
+    InvalidSealedBidReveal,
+
+    // 6016
+    #[msg("The revealed bid is larger than the committed deposit")]
+    SealedBidExceedsDeposit,
+
+    // 6017
+    #[msg("The sealed bid reveal period has ended")]
+    RevealPeriodEnded,
+
+    // 6018
+    #[msg("The sealed bid reveal period has not ended yet")]
+    RevealPeriodActive,
+
+    // 6019
+    #[msg("The bid must match the sealed-bid auction clearing price")]
+    SealedBidPriceMismatch,
+
+    // 6020
+    #[msg("The buy now price must be set on an English auction and be at least the reserve price")]
+    InvalidBuyNowPrice,
+
+    // 6021
+    #[msg("The accounts of the outbid highest bid are missing or invalid")]
+    InvalidPreviousBidAccounts,
+
+    // 6022
+    #[msg("The number of edition winners must equal the token size and fit in the bid book")]
+    InvalidEditionConfig,
+
+    // 6023
+    #[msg("The listing is not an edition auction")]
+    NotEditionAuction,
+
+    // 6024
+    #[msg("The bid is not one of the winning bids of the edition auction")]
+    NotEditionWinner,
+
+    // 6025
+    #[msg("The bid price does not match the price the edition winner pays")]
+    EditionPriceMismatch,
+
+    // 6026
+    #[msg("Numerical overflow error")]
+    NumericalOverflow,
+
+    // 6027
+    #[msg("The listing cannot be canceled once a bid meets the reserve price")]
+    CannotCancelListingWithBids,
+
+    // 6028
+    #[msg("The highest bidder wallet account is missing or invalid")]
+    InvalidHighestBidderAccount,
+
+    // 6029
+    #[msg("The wallet is not on the auction's allowlist")]
+    NotAllowlisted,
+
+    // 6030
+    #[msg("The wallet does not hold a verified NFT of the auction's gating collection")]
+    NotCollectionMember,
+
+    // 6031
+    #[msg("The auction start time must be before its end time")]
+    InvalidAuctionTimes,
+
+    // 6032
+    #[msg("The listing config cannot be updated once the auction has started and has bids")]
+    ListingConfigLocked,
+
+    // 6033
+    #[msg("The accounts needed to settle the sale are missing")]
+    MissingSaleAccounts,
+
+    // 6034
+    #[msg("The withdrawal would take deposits still backing open sealed bids")]
+    EscrowLocked,
+
+    // 6035
+    #[msg("The sealed bid can still win the auction or has not been settled")]
+    SealedBidStillOpen,
+
+    // 6036
+    #[msg("The trade state or escrow account of the highest bid is invalid")]
+    InvalidHighestBidAccounts,
 }
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use solana_program::clock::UnixTimestamp;
+
+use crate::sell::config::AuctionType;
+
+/// Emitted when a listing is created.
+#[event]
+pub struct ListingCreatedEvent {
+    pub listing_config: Pubkey,
+    pub seller: Pubkey,
+    pub token_mint: Pubkey,
+    pub token_size: u64,
+    pub auction_type: AuctionType,
+    pub start_time: UnixTimestamp,
+    pub end_time: UnixTimestamp,
+    pub reserve_price: u64,
+}
+
+/// Emitted when a bid is placed.
+#[event]
+pub struct BidPlacedEvent {
+    pub listing_config: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub buyer_trade_state: Pubkey,
+    /// The highest bidder before this bid, the default pubkey if there was none.
+    pub previous_bidder: Pubkey,
+    pub previous_bid: u64,
+}
+
+/// Emitted when a late bid extends the auction.
+#[event]
+pub struct AuctionExtendedEvent {
+    pub listing_config: Pubkey,
+    pub end_time: UnixTimestamp,
+    pub extension_count: u16,
+}
+
+/// Emitted when a listing or bid is canceled.
+#[event]
+pub struct CanceledEvent {
+    pub listing_config: Pubkey,
+    pub wallet: Pubkey,
+    pub trade_state: Pubkey,
+    /// Whether the seller's listing was canceled rather than a bid.
+    pub is_listing: bool,
+}
+
+/// Emitted when a sale is settled.
+#[event]
+pub struct SaleSettledEvent {
+    pub listing_config: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64,
+    pub token_size: u64,
+    pub keeper: Pubkey,
+}
+
+/// Emitted when a sealed bid commitment is placed.
+#[event]
+pub struct SealedBidCommittedEvent {
+    pub listing_config: Pubkey,
+    pub bidder: Pubkey,
+    pub deposit: u64,
+}
+
+/// Emitted when a sealed bid is revealed.
+#[event]
+pub struct SealedBidRevealedEvent {
+    pub listing_config: Pubkey,
+    pub bidder: Pubkey,
+    pub price: u64,
+}
+
+/// Emitted when the seller updates the listing config.
+#[event]
+pub struct ListingConfigUpdatedEvent {
+    pub listing_config: Pubkey,
+    pub start_time: UnixTimestamp,
+    pub end_time: UnixTimestamp,
+    pub reserve_price: u64,
+    pub min_bid_increment: u64,
+}
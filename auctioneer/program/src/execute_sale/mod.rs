@@ -1,5 +1,12 @@
-use anchor_lang::{prelude::*, AnchorDeserialize, InstructionData};
-use anchor_spl::{associated_token::AssociatedToken, token::Token};
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+    AnchorDeserialize, InstructionData,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{spl_token, Token},
+};
 
 use mpl_auction_house::{
     self,
@@ -9,9 +16,9 @@ use mpl_auction_house::{
     AuctionHouse,
 };
 
-use crate::{constants::*, sell::config::*, utils::*};
+use crate::{constants::*, errors::*, events::*, sell::config::*, utils::*};
 
-use solana_program::program::invoke_signed;
+use solana_program::{program::invoke_signed, program_pack::Pack};
 
 #[derive(Accounts)]
 #[instruction(escrow_payment_bump: u8, free_trade_state_bump: u8, program_as_signer_bump: u8, auctioneer_authority_bump: u8, buyer_price: u64, token_size: u64)]
@@ -143,6 +150,19 @@ pub struct AuctioneerExecuteSale<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+/// Accounts for the [`keeper_execute_sale` handler](fn.auctioneer_keeper_execute_sale.html).
+#[derive(Accounts)]
+pub struct AuctioneerKeeperExecuteSale<'info> {
+    /// The sale being settled. The auctioneer authority must be writable, it pays the rent and fees
+    /// of the sale out of the keeper's funds.
+    pub execute_sale: AuctioneerExecuteSale<'info>,
+
+    /// Anyone settling the ended auction, paying its rent and fees and paid the keeper reward of
+    /// the listing.
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+}
+
 pub fn auctioneer_execute_sale<'info>(
     ctx: Context<'_, '_, '_, 'info, AuctioneerExecuteSale<'info>>,
     escrow_payment_bump: u8,
@@ -152,48 +172,152 @@ pub fn auctioneer_execute_sale<'info>(
     buyer_price: u64,
     token_size: u64,
 ) -> Result<()> {
-    assert_auction_over(&ctx.accounts.listing_config)?;
-    assert_highest_bidder(
-        &ctx.accounts.listing_config,
-        ctx.accounts.buyer_trade_state.key(),
-    )?;
-
-    let cpi_program = ctx.accounts.auction_house_program.to_account_info();
-    let cpi_accounts = AHExecuteSale {
-        buyer: ctx.accounts.buyer.to_account_info(),
-        seller: ctx.accounts.seller.to_account_info(),
-        token_account: ctx.accounts.token_account.to_account_info(),
-        token_mint: ctx.accounts.token_mint.to_account_info(),
-        metadata: ctx.accounts.metadata.to_account_info(),
-        treasury_mint: ctx.accounts.treasury_mint.to_account_info(),
-        escrow_payment_account: ctx.accounts.escrow_payment_account.to_account_info(),
-        seller_payment_receipt_account: ctx
-            .accounts
-            .seller_payment_receipt_account
-            .to_account_info(),
-        buyer_receipt_token_account: ctx.accounts.buyer_receipt_token_account.to_account_info(),
-        auction_house: ctx.accounts.auction_house.to_account_info(),
-        auction_house_fee_account: ctx.accounts.auction_house_fee_account.to_account_info(),
-        auction_house_treasury: ctx.accounts.auction_house_treasury.to_account_info(),
-        buyer_trade_state: ctx.accounts.buyer_trade_state.to_account_info(),
-        seller_trade_state: ctx.accounts.seller_trade_state.to_account_info(),
-        free_trade_state: ctx.accounts.free_trade_state.to_account_info(),
-        authority: ctx.accounts.authority.to_account_info(),
-        auctioneer_authority: ctx.accounts.auctioneer_authority.to_account_info(),
-        ah_auctioneer_pda: ctx.accounts.ah_auctioneer_pda.to_account_info(),
-        token_program: ctx.accounts.token_program.to_account_info(),
-        system_program: ctx.accounts.system_program.to_account_info(),
-        ata_program: ctx.accounts.ata_program.to_account_info(),
-        program_as_signer: ctx.accounts.program_as_signer.to_account_info(),
-        rent: ctx.accounts.rent.to_account_info(),
-    };
+    execute_sale_logic(
+        ctx.accounts,
+        ctx.remaining_accounts,
+        None,
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        auctioneer_authority_bump,
+        buyer_price,
+        token_size,
+    )
+}
 
-    let execute_sale_data = mpl_auction_house::instruction::AuctioneerExecuteSale {
+/// Settle an ended auction on behalf of the buyer and seller. The keeper funds the rent and fees of
+/// the sale and is paid the keeper reward of the listing.
+pub fn auctioneer_keeper_execute_sale<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerKeeperExecuteSale<'info>>,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    auctioneer_authority_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+) -> Result<()> {
+    execute_sale_logic(
+        &mut ctx.accounts.execute_sale,
+        ctx.remaining_accounts,
+        Some(&ctx.accounts.keeper),
         escrow_payment_bump,
-        _free_trade_state_bump: free_trade_state_bump,
+        free_trade_state_bump,
         program_as_signer_bump,
+        auctioneer_authority_bump,
         buyer_price,
         token_size,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn execute_sale_logic<'info>(
+    accounts: &mut AuctioneerExecuteSale<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    keeper: Option<&Signer<'info>>,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    auctioneer_authority_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+) -> Result<()> {
+    // Edition winners each buy a single unit through a partial sale.
+    let is_edition = matches!(
+        accounts.listing_config.auction_type,
+        AuctionType::Edition(_)
+    );
+    if is_edition {
+        assert_auction_over(&accounts.listing_config)?;
+        process_edition_settlement(
+            &mut accounts.listing_config,
+            accounts.buyer.key(),
+            accounts.buyer_trade_state.key(),
+            buyer_price,
+        )?;
+    } else {
+        // A buy now bid ends the auction early.
+        if !is_bought_now(&accounts.listing_config) {
+            assert_auction_over(&accounts.listing_config)?;
+        }
+        assert_highest_bidder(&accounts.listing_config, accounts.buyer_trade_state.key())?;
+    }
+
+    let cpi_program = accounts.auction_house_program.to_account_info();
+    let cpi_accounts = AHExecuteSale {
+        buyer: accounts.buyer.to_account_info(),
+        seller: accounts.seller.to_account_info(),
+        token_account: accounts.token_account.to_account_info(),
+        token_mint: accounts.token_mint.to_account_info(),
+        metadata: accounts.metadata.to_account_info(),
+        treasury_mint: accounts.treasury_mint.to_account_info(),
+        escrow_payment_account: accounts.escrow_payment_account.to_account_info(),
+        seller_payment_receipt_account: accounts.seller_payment_receipt_account.to_account_info(),
+        buyer_receipt_token_account: accounts.buyer_receipt_token_account.to_account_info(),
+        auction_house: accounts.auction_house.to_account_info(),
+        auction_house_fee_account: accounts.auction_house_fee_account.to_account_info(),
+        auction_house_treasury: accounts.auction_house_treasury.to_account_info(),
+        buyer_trade_state: accounts.buyer_trade_state.to_account_info(),
+        seller_trade_state: accounts.seller_trade_state.to_account_info(),
+        free_trade_state: accounts.free_trade_state.to_account_info(),
+        authority: accounts.authority.to_account_info(),
+        auctioneer_authority: accounts.auctioneer_authority.to_account_info(),
+        ah_auctioneer_pda: accounts.ah_auctioneer_pda.to_account_info(),
+        token_program: accounts.token_program.to_account_info(),
+        system_program: accounts.system_program.to_account_info(),
+        ata_program: accounts.ata_program.to_account_info(),
+        program_as_signer: accounts.program_as_signer.to_account_info(),
+        rent: accounts.rent.to_account_info(),
+    };
+
+    // Partial sales take the same accounts. Auction House checks the unit price of the partial order
+    // against the price of the whole listing, which is the winning bid checked above for each unit.
+    // Keeper settlements go through their own instructions, the only ones Auction House lets the
+    // auctioneer authority pay for.
+    let execute_sale_data = if is_edition {
+        let listing_price = buyer_price
+            .checked_mul(token_size)
+            .ok_or(AuctioneerError::NumericalOverflow)?;
+        if keeper.is_some() {
+            mpl_auction_house::instruction::AuctioneerKeeperExecutePartialSale {
+                escrow_payment_bump,
+                _free_trade_state_bump: free_trade_state_bump,
+                program_as_signer_bump,
+                buyer_price: listing_price,
+                token_size,
+                partial_order_size: Some(1),
+                partial_order_price: Some(buyer_price),
+            }
+            .data()
+        } else {
+            mpl_auction_house::instruction::AuctioneerExecutePartialSale {
+                escrow_payment_bump,
+                _free_trade_state_bump: free_trade_state_bump,
+                program_as_signer_bump,
+                buyer_price: listing_price,
+                token_size,
+                partial_order_size: Some(1),
+                partial_order_price: Some(buyer_price),
+            }
+            .data()
+        }
+    } else if keeper.is_some() {
+        mpl_auction_house::instruction::AuctioneerKeeperExecuteSale {
+            escrow_payment_bump,
+            _free_trade_state_bump: free_trade_state_bump,
+            program_as_signer_bump,
+            buyer_price,
+            token_size,
+        }
+        .data()
+    } else {
+        mpl_auction_house::instruction::AuctioneerExecuteSale {
+            escrow_payment_bump,
+            _free_trade_state_bump: free_trade_state_bump,
+            program_as_signer_bump,
+            buyer_price,
+            token_size,
+        }
+        .data()
     };
 
     let mut cpi_account_metas: Vec<AccountMeta> = cpi_accounts
@@ -202,27 +326,28 @@ pub fn auctioneer_execute_sale<'info>(
         .zip(cpi_accounts.to_account_infos())
         .map(|mut pair| {
             pair.0.is_signer = pair.1.is_signer;
-            if pair.0.pubkey == ctx.accounts.auctioneer_authority.key() {
+            if pair.0.pubkey == accounts.auctioneer_authority.key() {
                 pair.0.is_signer = true;
+                pair.0.is_writable = pair.1.is_writable;
             }
             pair.0
         })
         .collect();
 
-    cpi_account_metas.append(&mut ctx.remaining_accounts.to_vec().to_account_metas(None));
+    cpi_account_metas.append(&mut remaining_accounts.to_vec().to_account_metas(None));
 
     let mut cpi_account_infos: Vec<AccountInfo> = cpi_accounts.to_account_infos();
-    cpi_account_infos.append(&mut ctx.remaining_accounts.to_vec());
+    cpi_account_infos.append(&mut remaining_accounts.to_vec());
 
     let ix = solana_program::instruction::Instruction {
         program_id: cpi_program.key(),
         accounts: cpi_account_metas,
-        data: execute_sale_data.data(),
+        data: execute_sale_data,
     };
 
-    let auction_house = &ctx.accounts.auction_house;
+    let auction_house = &accounts.auction_house;
     let ah_key = auction_house.key();
-    let auctioneer_authority = &ctx.accounts.auctioneer_authority;
+    let auctioneer_authority = &accounts.auctioneer_authority;
     let _aa_key = auctioneer_authority.key();
 
     let auctioneer_seeds = [
@@ -231,17 +356,96 @@ pub fn auctioneer_execute_sale<'info>(
         &[auctioneer_authority_bump],
     ];
 
+    // Auction House pays the rent and fees of a sale signed by neither the buyer nor the seller from
+    // the auctioneer authority, which the keeper funds for the duration of the sale.
+    let auctioneer_authority_lamports = auctioneer_authority.lamports();
+    if let Some(keeper) = keeper {
+        let fee_budget = keeper_fee_budget(accounts, remaining_accounts)?;
+        transfer(
+            CpiContext::new(
+                accounts.system_program.to_account_info(),
+                Transfer {
+                    from: keeper.to_account_info(),
+                    to: auctioneer_authority.to_account_info(),
+                },
+            ),
+            fee_budget,
+        )?;
+    }
+
     invoke_signed(&ix, &cpi_account_infos, &[&auctioneer_seeds])?;
 
+    emit!(SaleSettledEvent {
+        listing_config: accounts.listing_config.key(),
+        seller: accounts.seller.key(),
+        buyer: accounts.buyer.key(),
+        price: buyer_price,
+        token_size: if is_edition { 1 } else { token_size },
+        keeper: keeper.map(|keeper| keeper.key()).unwrap_or_default(),
+    });
+
+    if let Some(keeper) = keeper {
+        // Return what is left of the keeper's funds.
+        let unused_fee_budget = auctioneer_authority
+            .lamports()
+            .saturating_sub(auctioneer_authority_lamports);
+        if unused_fee_budget > 0 {
+            transfer(
+                CpiContext::new_with_signer(
+                    accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: auctioneer_authority.to_account_info(),
+                        to: keeper.to_account_info(),
+                    },
+                    &[&auctioneer_seeds],
+                ),
+                unused_fee_budget,
+            )?;
+        }
+
+        // Pay the keeper reward funded by the seller at listing time. Sales settled by the buyer
+        // or seller leave it to the seller.
+        let keeper_reward = accounts.listing_config.keeper_reward;
+        if keeper_reward > 0 {
+            let listing_config = &accounts.listing_config.to_account_info();
+            let keeper = &keeper.to_account_info();
+
+            **listing_config.lamports.borrow_mut() = listing_config
+                .lamports()
+                .checked_sub(keeper_reward)
+                .ok_or(AuctioneerError::NumericalOverflow)?;
+            **keeper.lamports.borrow_mut() = keeper
+                .lamports()
+                .checked_add(keeper_reward)
+                .ok_or(AuctioneerError::NumericalOverflow)?;
+        }
+    }
+
+    // Keep the Listing Config of an edition auction until its last unit is sold, recording each
+    // settled unit in its bid history. The history of other listings closes with them.
+    if is_edition && accounts.seller_trade_state.lamports() > 0 {
+        if accounts.listing_config.records_bid_history {
+            record_bid_history(
+                &mut accounts.listing_config,
+                BidHistoryEntry {
+                    bidder: accounts.buyer.key(),
+                    amount: buyer_price,
+                    timestamp: Clock::get()?.unix_timestamp,
+                },
+            );
+        }
+        return Ok(());
+    }
+
     // Close the Listing Config account.
-    let listing_config = &ctx.accounts.listing_config.to_account_info();
-    let seller = &ctx.accounts.seller.to_account_info();
+    let listing_config = &accounts.listing_config.to_account_info();
+    let seller = &accounts.seller.to_account_info();
 
     let listing_config_lamports = listing_config.lamports();
     **seller.lamports.borrow_mut() = seller
         .lamports()
         .checked_add(listing_config_lamports)
-        .unwrap();
+        .ok_or(AuctioneerError::NumericalOverflow)?;
     **listing_config.lamports.borrow_mut() = 0;
 
     let mut source_data = listing_config.data.borrow_mut();
@@ -249,3 +453,38 @@ pub fn auctioneer_execute_sale<'info>(
 
     Ok(())
 }
+
+/// The lamports a keeper funds a sale with: the rent of every token account Auction House creates
+/// for it, and for native sales the rent shortfall Auction House may top up the escrow with.
+fn keeper_fee_budget(
+    accounts: &AuctioneerExecuteSale,
+    remaining_accounts: &[AccountInfo],
+) -> Result<u64> {
+    let rent = Rent::get()?;
+    let is_native = accounts.treasury_mint.key() == spl_token::native_mint::id();
+
+    let mut new_token_accounts = 0u64;
+    if accounts.buyer_receipt_token_account.data_is_empty() {
+        new_token_accounts += 1;
+    }
+    let mut escrow_rent_shortfall = 0;
+    if is_native {
+        escrow_rent_shortfall = rent.minimum_balance(0);
+    } else {
+        if accounts.seller_payment_receipt_account.data_is_empty() {
+            new_token_accounts += 1;
+        }
+        // Creators are paid into their token accounts, passed after each creator's wallet.
+        new_token_accounts += remaining_accounts
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .filter(|account| account.data_is_empty())
+            .count() as u64;
+    }
+
+    rent.minimum_balance(spl_token::state::Account::LEN)
+        .checked_mul(new_token_accounts)
+        .and_then(|token_accounts_rent| token_accounts_rent.checked_add(escrow_rent_shortfall))
+        .ok_or_else(|| AuctioneerError::NumericalOverflow.into())
+}
@@ -2,16 +2,27 @@
 pub mod authorize;
 pub mod bid;
 pub mod cancel;
+pub mod close_sealed_bid;
+pub mod commit_bid;
 pub mod constants;
 pub mod deposit;
 pub mod errors;
+pub mod events;
 pub mod execute_sale;
+pub mod merkle_proof;
+pub mod migrate_listing_config;
 pub mod pda;
+pub mod reveal_bid;
 pub mod sell;
+pub mod update_listing_config;
 pub mod utils;
 pub mod withdraw;
 
-use crate::{authorize::*, bid::*, cancel::*, deposit::*, execute_sale::*, sell::*, withdraw::*};
+use crate::{
+    authorize::*, bid::*, cancel::*, close_sealed_bid::*, commit_bid::*, deposit::*,
+    execute_sale::*, migrate_listing_config::*, reveal_bid::*, sell::config::*, sell::*,
+    update_listing_config::*, withdraw::*,
+};
 
 use anchor_lang::prelude::*;
 
@@ -51,6 +62,9 @@ pub mod auctioneer {
     }
 
     /// Cancel a bid or ask by revoking the token delegate, transferring all lamports from the trade state account to the fee payer, and setting the trade state account data to zero so it can be garbage collected.
+    /// Canceling a listing that owes a cancellation penalty takes the highest bidder's wallet as a remaining account.
+    /// Its trade state and escrow payment account can follow, letting the seller cancel a listing whose highest bid
+    /// was canceled or is no longer covered by its escrow.
     pub fn cancel<'info>(
         ctx: Context<'_, '_, '_, 'info, AuctioneerCancel<'info>>,
         auctioneer_authority_bump: u8,
@@ -82,7 +96,37 @@ pub mod auctioneer {
         )
     }
 
+    /// Execute sale on behalf of the buyer and seller of an ended auction. Anyone may settle it as the `keeper`, funding the rent and fees of the sale and receiving the listing's keeper reward.
+    #[inline(never)]
+    pub fn keeper_execute_sale<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerKeeperExecuteSale<'info>>,
+        escrow_payment_bump: u8,
+        free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        auctioneer_authority_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+    ) -> Result<()> {
+        auctioneer_keeper_execute_sale(
+            ctx,
+            escrow_payment_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            auctioneer_authority_bump,
+            buyer_price,
+            token_size,
+        )
+    }
+
     /// Create a sell bid by creating a `seller_trade_state` account and approving the program as the token delegate.
+    /// `auction_type` defaults to an English auction; Dutch auctions ignore the English bidding settings.
+    /// English auctions can set a `buy_now_price` at which a bid wins the auction immediately.
+    /// `anti_sniping` bounds the time extensions granted to late bids.
+    /// Edition auctions sell `token_size` units, one to each of the highest bids, settled by one `execute_sale` per winner.
+    /// `keeper_reward` lamports are paid by the seller for each sale to whoever settles it with `keeper_execute_sale`.
+    /// `seller_cancel_rule` restricts canceling the listing once a bid meets the reserve price.
+    /// `collection_gate` and `allowlist_root` restrict bidding to holders of the collection and wallets on the allowlist.
+    /// `record_bid_history` keeps the most recent bids, sealed-bid reveals and edition settlements in the Listing Config.
     pub fn sell<'info>(
         ctx: Context<'_, '_, '_, 'info, AuctioneerSell<'info>>,
         trade_state_bump: u8,
@@ -97,6 +141,14 @@ pub mod auctioneer {
         time_ext_period: Option<u32>,
         time_ext_delta: Option<u32>,
         allow_high_bid_cancel: Option<bool>,
+        auction_type: Option<AuctionType>,
+        buy_now_price: Option<u64>,
+        anti_sniping: Option<AntiSnipingConfig>,
+        keeper_reward: Option<u64>,
+        seller_cancel_rule: Option<SellerCancelRule>,
+        collection_gate: Option<Pubkey>,
+        allowlist_root: Option<[u8; 32]>,
+        record_bid_history: Option<bool>,
     ) -> Result<()> {
         auctioneer_sell(
             ctx,
@@ -112,10 +164,49 @@ pub mod auctioneer {
             time_ext_period,
             time_ext_delta,
             allow_high_bid_cancel,
+            auction_type,
+            buy_now_price,
+            anti_sniping,
+            keeper_reward,
+            seller_cancel_rule,
+            collection_gate,
+            allowlist_root,
+            record_bid_history,
+        )
+    }
+
+    /// Change the start and end time, reserve price or minimum bid increment of a listing before it starts or receives a bid.
+    pub fn update_listing_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerUpdateListingConfig<'info>>,
+        token_size: u64,
+        start_time: Option<UnixTimestamp>,
+        end_time: Option<UnixTimestamp>,
+        reserve_price: Option<u64>,
+        min_bid_increment: Option<u64>,
+    ) -> Result<()> {
+        auctioneer_update_listing_config(
+            ctx,
+            token_size,
+            start_time,
+            end_time,
+            reserve_price,
+            min_bid_increment,
         )
     }
 
+    /// Grow a Listing Config created by an earlier version of the program to the current layout.
+    pub fn migrate_listing_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerMigrateListingConfig<'info>>,
+    ) -> Result<()> {
+        auctioneer_migrate_listing_config(ctx)
+    }
+
     /// Create a private buy bid by creating a `buyer_trade_state` account and an `escrow_payment` account and funding the escrow with the necessary SOL or SPL token amount.
+    /// The outbid highest bid is closed and refunded when its wallet, receipt account, escrow and trade state are passed as remaining accounts.
+    /// Without them, or if its receipt account can't take the refund, the outbid funds stay in its escrow and its bidder cancels the bid.
+    /// Gated auctions take an `allowlist_proof` of the wallet, and a token account and metadata of the gating collection as the first remaining accounts.
+    /// `keep_escrow_on_outbid` leaves this bid's funds in escrow instead of returning them to the wallet once it is outbid, they are returned by default.
+    /// The winning bid of a Dutch auction settles the sale, taking the accounts `execute_sale` needs beyond those of `buy` as remaining accounts.
     pub fn buy<'info>(
         ctx: Context<'_, '_, '_, 'info, AuctioneerBuy<'info>>,
         trade_state_bump: u8,
@@ -123,6 +214,8 @@ pub mod auctioneer {
         auctioneer_authority_bump: u8,
         buyer_price: u64,
         token_size: u64,
+        allowlist_proof: Option<Vec<[u8; 32]>>,
+        keep_escrow_on_outbid: Option<bool>,
     ) -> Result<()> {
         auctioneer_buy(
             ctx,
@@ -131,6 +224,51 @@ pub mod auctioneer {
             auctioneer_authority_bump,
             buyer_price,
             token_size,
+            allowlist_proof,
+            keep_escrow_on_outbid,
+        )
+    }
+
+    /// Commit a hidden `keccak(price || salt)` bid on a sealed-bid auction, depositing `deposit` into escrow to back it.
+    /// The deposit can't be withdrawn until the sealed bid is closed with `close_sealed_bid`.
+    /// Gated auctions take the same `allowlist_proof` and remaining accounts as `buy`.
+    pub fn commit_bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerCommitBid<'info>>,
+        escrow_payment_bump: u8,
+        auctioneer_authority_bump: u8,
+        token_size: u64,
+        commitment: [u8; 32],
+        deposit: u64,
+        allowlist_proof: Option<Vec<[u8; 32]>>,
+    ) -> Result<()> {
+        auctioneer_commit_bid(
+            ctx,
+            escrow_payment_bump,
+            auctioneer_authority_bump,
+            token_size,
+            commitment,
+            deposit,
+            allowlist_proof,
         )
     }
+
+    /// Reveal a sealed bid after the auction has ended, recording it if it is one of the two highest bids.
+    /// The highest bidder claims the item with `buy` within the claim period, after which the second highest bidder can claim it instead for another claim period.
+    /// Gated auctions take the same `allowlist_proof` and remaining accounts as `buy`.
+    pub fn reveal_bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerRevealBid<'info>>,
+        token_size: u64,
+        price: u64,
+        salt: [u8; 32],
+        allowlist_proof: Option<Vec<[u8; 32]>>,
+    ) -> Result<()> {
+        auctioneer_reveal_bid(ctx, token_size, price, salt, allowlist_proof)
+    }
+
+    /// Close a sealed bid that can no longer win, releasing its deposit to be withdrawn from escrow.
+    pub fn close_sealed_bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerCloseSealedBid<'info>>,
+    ) -> Result<()> {
+        auctioneer_close_sealed_bid(ctx)
+    }
 }
@@ -0,0 +1,24 @@
+//! These functions deal with verification of Merkle trees (hash trees), matching the proofs
+//! built for Gumdrop's distribution trees.
+//! Direct port of https://github.com/OpenZeppelin/openzeppelin-contracts/blob/v3.4.0/contracts/cryptography/MerkleProof.sol
+
+/// Returns true if a `leaf` can be proved to be a part of a Merkle tree
+/// defined by `root`. For this, a `proof` must be provided, containing
+/// sibling hashes on the branch from the leaf to the root of the tree. Each
+/// pair of leaves and each pair of pre-images are assumed to be sorted.
+pub fn verify(proof: Vec<[u8; 32]>, root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut computed_hash = leaf;
+    for proof_element in proof.into_iter() {
+        if computed_hash <= proof_element {
+            // Hash(current computed hash + current element of the proof)
+            computed_hash =
+                solana_program::keccak::hashv(&[&[0x01], &computed_hash, &proof_element]).0;
+        } else {
+            // Hash(current element of the proof + current computed hash)
+            computed_hash =
+                solana_program::keccak::hashv(&[&[0x01], &proof_element, &computed_hash]).0;
+        }
+    }
+    // Check if the computed hash (root) is equal to the provided root
+    computed_hash == root
+}
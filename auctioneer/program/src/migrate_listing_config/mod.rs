@@ -0,0 +1,54 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::sell::config::*;
+
+/// Accounts for the [`migrate_listing_config` handler](fn.auctioneer_migrate_listing_config.html).
+#[derive(Accounts)]
+pub struct AuctioneerMigrateListingConfig<'info> {
+    /// CHECK: Loaded with the layout of its version in auctioneer_migrate_listing_config.
+    /// The Listing Config to migrate.
+    #[account(mut, owner = crate::id())]
+    pub listing_config: UncheckedAccount<'info>,
+
+    /// Anyone paying the rent of the Listing Config's additional space.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow a Listing Config created by an earlier version of the program to the current layout.
+/// Listing Configs already on the current layout are left untouched.
+pub fn auctioneer_migrate_listing_config<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerMigrateListingConfig<'info>>,
+) -> Result<()> {
+    let listing_config_info = ctx.accounts.listing_config.to_account_info();
+    if listing_config_info.data_len() >= LISTING_CONFIG_SIZE {
+        return Ok(());
+    }
+    let listing_config =
+        ListingConfig::try_deserialize_versioned(&listing_config_info.try_borrow_data()?)?;
+
+    let rent_minimum = Rent::get()?.minimum_balance(LISTING_CONFIG_SIZE);
+    let rent_shortfall = rent_minimum.saturating_sub(listing_config_info.lamports());
+    if rent_shortfall > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: listing_config_info.clone(),
+                },
+            ),
+            rent_shortfall,
+        )?;
+    }
+    listing_config_info.realloc(LISTING_CONFIG_SIZE, false)?;
+
+    let mut data = listing_config_info.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    listing_config.try_serialize(&mut writer)
+}
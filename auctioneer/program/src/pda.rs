@@ -28,3 +28,25 @@ pub fn find_listing_config_address(
 pub fn find_auctioneer_authority_seeds(auction_house: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[AUCTIONEER.as_bytes(), auction_house.as_ref()], &id())
 }
+
+pub fn find_sealed_bid_address(listing_config: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SEALED_BID.as_bytes(),
+            listing_config.as_ref(),
+            wallet.as_ref(),
+        ],
+        &id(),
+    )
+}
+
+pub fn find_escrow_lock_address(auction_house: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ESCROW_LOCK.as_bytes(),
+            auction_house.as_ref(),
+            wallet.as_ref(),
+        ],
+        &id(),
+    )
+}
@@ -0,0 +1,115 @@
+use anchor_lang::{prelude::*, AnchorDeserialize};
+use anchor_spl::token::TokenAccount;
+
+use mpl_auction_house::{
+    constants::PREFIX, program::AuctionHouse as AuctionHouseProgram, AuctionHouse,
+};
+
+use crate::{
+    commit_bid::sealed_bid::*, constants::*, errors::*, events::*, sell::config::*, utils::*,
+};
+
+/// Accounts for the [`reveal_bid` handler](fn.auctioneer_reveal_bid.html).
+#[derive(Accounts)]
+#[instruction(token_size: u64)]
+pub struct AuctioneerRevealBid<'info> {
+    /// Auction House Program
+    pub auction_house_program: Program<'info, AuctionHouseProgram>,
+
+    /// The Listing Config used for listing settings
+    #[account(
+        mut,
+        seeds=[
+            LISTING_CONFIG.as_bytes(),
+            seller.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &token_size.to_le_bytes()
+        ],
+        bump=listing_config.bump,
+    )]
+    pub listing_config: Box<Account<'info, ListingConfig>>,
+
+    /// The sealed bid being revealed, kept until `close_sealed_bid` releases its deposit.
+    #[account(
+        mut,
+        seeds=[
+            SEALED_BID.as_bytes(),
+            listing_config.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump=sealed_bid.bump,
+        has_one=listing_config,
+        constraint = sealed_bid.bidder == wallet.key(),
+    )]
+    pub sealed_bid: Account<'info, SealedBid>,
+
+    /// The seller of the NFT
+    /// CHECK: Checked via listing config seeds
+    pub seller: UncheckedAccount<'info>,
+
+    /// SPL token account of the listing.
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// The bidder revealing their sealed bid.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(seeds=[PREFIX.as_bytes(), auction_house.creator.as_ref(), auction_house.treasury_mint.as_ref()], seeds::program=auction_house_program, bump=auction_house.bump)]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+}
+
+/// Reveal a sealed bid after the listing's `end_time`. Once the reveal period is over the highest
+/// bidder claims the item by calling `buy` at the clearing price and settling with `execute_sale`.
+/// If they haven't claimed it by the end of the claim period, the second highest bidder can claim
+/// it at their own bid instead. Everyone else recovers their deposit with `close_sealed_bid` and
+/// `withdraw`.
+pub fn auctioneer_reveal_bid<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerRevealBid<'info>>,
+    _token_size: u64,
+    price: u64,
+    salt: [u8; 32],
+    allowlist_proof: Option<Vec<[u8; 32]>>,
+) -> Result<()> {
+    assert_reveal_period_active(&ctx.accounts.listing_config)?;
+    assert_bid_gates(
+        &ctx.accounts.listing_config,
+        &ctx.accounts.wallet.key(),
+        allowlist_proof,
+        ctx.remaining_accounts,
+    )?;
+
+    let sealed_bid = &mut ctx.accounts.sealed_bid;
+    if sealed_bid.revealed || sealed_bid_commitment(price, &salt) != sealed_bid.commitment {
+        return err!(AuctioneerError::InvalidSealedBidReveal);
+    } else if price > sealed_bid.deposit {
+        return err!(AuctioneerError::SealedBidExceedsDeposit);
+    }
+    sealed_bid.revealed = true;
+
+    process_sealed_bid_reveal(
+        &mut ctx.accounts.listing_config,
+        ctx.accounts.wallet.key(),
+        price,
+    )?;
+    if ctx.accounts.listing_config.records_bid_history {
+        record_bid_history(
+            &mut ctx.accounts.listing_config,
+            BidHistoryEntry {
+                bidder: ctx.accounts.wallet.key(),
+                amount: price,
+                timestamp: Clock::get()?.unix_timestamp,
+            },
+        );
+    }
+    emit!(SealedBidRevealedEvent {
+        listing_config: ctx.accounts.listing_config.key(),
+        bidder: ctx.accounts.wallet.key(),
+        price,
+    });
+
+    Ok(())
+}
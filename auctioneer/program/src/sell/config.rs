@@ -1,12 +1,48 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{error::ErrorCode, prelude::*, Discriminator};
 use solana_program::clock::UnixTimestamp;
 
 pub const BID_SIZE: usize = 8 + 1 + 32;
-pub const LISTING_CONFIG_SIZE: usize = 8 + 1 + 8 + 8 + BID_SIZE + 1 + 8 + 8 + 4 + 4 + 1;
+pub const LISTING_CONFIG_V0_SIZE: usize = 8 + 1 + 8 + 8 + BID_SIZE + 1 + 8 + 8 + 4 + 4 + 1;
+pub const DUTCH_AUCTION_CONFIG_SIZE: usize = 8 + 8 + 1 + 4 + 2;
+pub const SEALED_BID_CONFIG_SIZE: usize = 4 + 1 + 4;
+pub const ANTI_SNIPING_CONFIG_SIZE: usize = 4 + 2 + 8;
+pub const EDITION_CONFIG_SIZE: usize = 1 + 1;
+pub const BID_BOOK_ENTRY_SIZE: usize = 32 + 8 + 32 + 1 + 8;
+pub const SELLER_CANCEL_RULE_SIZE: usize = 1 + 8;
+pub const BID_GATE_SIZE: usize = (1 + 32) + (1 + 32);
+pub const BID_HISTORY_ENTRY_SIZE: usize = 32 + 8 + 8;
+/// The bid history keeps this many of the most recent bids.
+pub const BID_HISTORY_LEN: usize = 8;
+/// Edition auctions have at most this many winners, each holding a place in the bid book.
+pub const MAX_EDITION_WINNERS: usize = 10;
+// The largest variant determines the size of the enum.
+pub const AUCTION_TYPE_SIZE: usize = 1 + DUTCH_AUCTION_CONFIG_SIZE;
+// Listings other than edition auctions keep an empty bid book, and listings not recording their
+// bids an empty bid history, see `ListingConfig::space`.
+pub const LISTING_CONFIG_SIZE: usize = LISTING_CONFIG_V0_SIZE
+    + AUCTION_TYPE_SIZE
+    + 32
+    + 8
+    + 9
+    + 1
+    + ANTI_SNIPING_CONFIG_SIZE
+    + 2
+    + 4
+    + 4
+    + 8
+    + 8
+    + SELLER_CANCEL_RULE_SIZE
+    + BID_GATE_SIZE
+    + 4
+    + 4
+    + 32
+    + 8
+    + 1;
 
-#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug)]
 pub enum ListingConfigVersion {
     V0,
+    V1,
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone)]
@@ -16,6 +52,117 @@ pub struct Bid {
     pub buyer_trade_state: Pubkey,
 }
 
+/// The shape of the price decay of a Dutch auction between `start_time` and `end_time`.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DutchAuctionCurve {
+    /// The price falls continuously from `start_price` to `end_price`.
+    Linear,
+    /// Every `interval` seconds the amount above `end_price` shrinks by `decay_bps`.
+    Exponential,
+    /// The price falls linearly but only changes every `interval` seconds.
+    Stepped,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug)]
+pub struct DutchAuctionConfig {
+    pub start_price: u64,
+    pub end_price: u64,
+    pub curve: DutchAuctionCurve,
+    pub interval: u32,
+    pub decay_bps: u16,
+}
+
+/// The price the winner of a sealed-bid auction pays.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SealedBidPricing {
+    /// The winner pays their own revealed bid.
+    FirstPrice,
+    /// The winner pays the second highest revealed bid, or the reserve price if it is higher.
+    SecondPrice,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug)]
+pub struct SealedBidConfig {
+    /// Seconds after `end_time` during which committed bids can be revealed.
+    pub reveal_period: u32,
+    pub pricing: SealedBidPricing,
+    /// Seconds after the reveal period during which only the highest bidder can claim the item.
+    /// Once it is over the second highest bidder can claim it instead at their own bid.
+    pub claim_period: u32,
+}
+
+/// The price the winners of an edition auction pay.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EditionPricing {
+    /// Every winner pays their own bid.
+    FirstPrice,
+    /// Every winner pays the highest losing bid, or the reserve price if it is higher.
+    UniformPrice,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug)]
+pub struct EditionConfig {
+    /// Number of units sold, one to each of the highest bids. Must equal the listing's token size.
+    pub winners: u8,
+    pub pricing: EditionPricing,
+}
+
+/// A winning bid of an edition auction, each for a single unit.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug)]
+pub struct BidBookEntry {
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub buyer_trade_state: Pubkey,
+    /// Whether the bidder keeps their funds in escrow rather than being refunded when outbid.
+    pub keeps_escrow: bool,
+    /// Amount the bid added to the bidder's escrow, which is shared by all of their bids. At most
+    /// this much is refunded when the bid is outbid.
+    pub deposit: u64,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug)]
+pub enum AuctionType {
+    /// Ascending-price auction settled to the highest bidder once `end_time` has passed.
+    English,
+    /// Descending-price auction won by the first bid at or above the current price.
+    Dutch(DutchAuctionConfig),
+    /// Commit-reveal auction where bids stay hidden until `end_time` and the highest reveal wins.
+    SealedBid(SealedBidConfig),
+    /// English auction of identical units won by the highest bids, one unit per bid.
+    Edition(EditionConfig),
+}
+
+/// A bid recorded in the bid history of a listing.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug)]
+pub struct BidHistoryEntry {
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub timestamp: UnixTimestamp,
+}
+
+/// When the seller may cancel a listing that has bids.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SellerCancelRule {
+    /// The listing can be canceled at any time.
+    Anytime,
+    /// The listing can no longer be canceled once a bid meets the reserve price.
+    BeforeReserveMet,
+    /// Canceling once a bid meets the reserve price pays these lamports to the highest bidder.
+    /// The penalty is held by the Listing Config from listing time.
+    WithPenalty(u64),
+}
+
+/// Limits on how far late bids can push back `end_time`. Zero disables a limit.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Default, PartialEq, Eq, Debug)]
+pub struct AntiSnipingConfig {
+    /// Total seconds `end_time` can be extended by over the whole auction.
+    pub max_total_extension: u32,
+    /// Number of times `end_time` can be extended.
+    pub max_extensions: u16,
+    /// Bids must raise the highest bid by more than this amount to extend `end_time`.
+    pub min_price_increase: u64,
+}
+
 #[account]
 pub struct ListingConfig {
     pub version: ListingConfigVersion,
@@ -28,4 +175,141 @@ pub struct ListingConfig {
     pub time_ext_period: u32,
     pub time_ext_delta: u32,
     pub allow_high_bid_cancel: bool,
+    // V1 fields
+    pub auction_type: AuctionType,
+    /// Wallet of the highest bid, or of the highest revealed bid in a sealed-bid auction.
+    pub highest_bidder: Pubkey,
+    /// Second highest revealed bid in a sealed-bid auction.
+    pub second_highest_bid: u64,
+    /// Bidding at least this amount on an English auction wins it immediately.
+    pub buy_now_price: Option<u64>,
+    /// Whether the highest bidder keeps their funds in escrow rather than being refunded when outbid.
+    pub highest_bid_keeps_escrow: bool,
+    pub anti_sniping: AntiSnipingConfig,
+    /// Number of times `end_time` has been extended.
+    pub extension_count: u16,
+    /// Total seconds `end_time` has been extended by.
+    pub total_extension: u32,
+    /// Winning bids of an edition auction, highest first.
+    pub bid_book: Vec<BidBookEntry>,
+    /// Highest bid pushed out of the bid book of an edition auction.
+    pub clearing_bid: u64,
+    /// Lamports paid to whoever settles each sale, held by this account until then.
+    pub keeper_reward: u64,
+    pub seller_cancel_rule: SellerCancelRule,
+    /// Only holders of an NFT of this verified collection may bid.
+    pub collection_gate: Option<Pubkey>,
+    /// Only wallets in the allowlist merkle tree with this root may bid.
+    pub allowlist_root: Option<[u8; 32]>,
+    /// The most recent bids when `records_bid_history` is set, the oldest is overwritten once full.
+    pub bid_history: Vec<BidHistoryEntry>,
+    /// Number of bids recorded in the history, the next is written at `bid_count % BID_HISTORY_LEN`.
+    pub bid_count: u32,
+    /// Wallet of the second highest revealed bid in a sealed-bid auction.
+    pub second_highest_bidder: Pubkey,
+    /// Amount the highest bid added to the bidder's escrow.
+    pub highest_bid_deposit: u64,
+    /// Whether the listing keeps the bid history, allocated when the listing is created.
+    pub records_bid_history: bool,
+}
+
+/// The layout of Listing Configs created before auction types were added, which are migrated to
+/// the current layout by `migrate_listing_config`.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct ListingConfigV0 {
+    pub version: ListingConfigVersion,
+    pub start_time: UnixTimestamp,
+    pub end_time: UnixTimestamp,
+    pub highest_bid: Bid,
+    pub bump: u8,
+    pub reserve_price: u64,
+    pub min_bid_increment: u64,
+    pub time_ext_period: u32,
+    pub time_ext_delta: u32,
+    pub allow_high_bid_cancel: bool,
+}
+
+impl ListingConfig {
+    /// Load a Listing Config of any version, filling in the fields added since V0 with the
+    /// settings of an English auction.
+    pub fn try_deserialize_versioned(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != ListingConfig::discriminator() {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+        if data.len() > 8 && data[8] != ListingConfigVersion::V0 as u8 {
+            return ListingConfig::try_deserialize(&mut &data[..]);
+        }
+
+        let v0 = ListingConfigV0::deserialize(&mut &data[8..])
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+        Ok(ListingConfig {
+            version: ListingConfigVersion::V1,
+            start_time: v0.start_time,
+            end_time: v0.end_time,
+            highest_bid: Bid {
+                version: ListingConfigVersion::V1,
+                ..v0.highest_bid
+            },
+            bump: v0.bump,
+            reserve_price: v0.reserve_price,
+            min_bid_increment: v0.min_bid_increment,
+            time_ext_period: v0.time_ext_period,
+            time_ext_delta: v0.time_ext_delta,
+            allow_high_bid_cancel: v0.allow_high_bid_cancel,
+            auction_type: AuctionType::English,
+            // V0 listings did not record the wallet of the highest bid.
+            highest_bidder: Pubkey::default(),
+            second_highest_bid: 0,
+            buy_now_price: None,
+            highest_bid_keeps_escrow: false,
+            anti_sniping: AntiSnipingConfig::default(),
+            extension_count: 0,
+            total_extension: 0,
+            bid_book: vec![],
+            clearing_bid: 0,
+            keeper_reward: 0,
+            seller_cancel_rule: SellerCancelRule::Anytime,
+            collection_gate: None,
+            allowlist_root: None,
+            bid_history: vec![],
+            bid_count: 0,
+            second_highest_bidder: Pubkey::default(),
+            highest_bid_deposit: 0,
+            records_bid_history: false,
+        })
+    }
+
+    /// The number of sales settled for this listing, each paying the keeper reward.
+    pub fn sale_count(&self) -> u64 {
+        match &self.auction_type {
+            AuctionType::Edition(edition_config) => edition_config.winners as u64,
+            _ => 1,
+        }
+    }
+
+    /// Account size of a listing, with room in the bid book for each winner of an edition auction
+    /// and room for the bid history if the listing records it.
+    pub fn space(auction_type: Option<&AuctionType>, record_bid_history: Option<bool>) -> usize {
+        let bid_book_size = match auction_type {
+            Some(AuctionType::Edition(edition_config)) => {
+                usize::from(edition_config.winners) * BID_BOOK_ENTRY_SIZE
+            }
+            _ => 0,
+        };
+        let bid_history_size = if record_bid_history.unwrap_or(false) {
+            BID_HISTORY_LEN * BID_HISTORY_ENTRY_SIZE
+        } else {
+            0
+        };
+
+        LISTING_CONFIG_SIZE + bid_book_size + bid_history_size
+    }
+
+    /// The number of units each bid is for, used in the buyer trade state.
+    pub fn bid_size(&self, token_size: u64) -> u64 {
+        match self.auction_type {
+            AuctionType::Edition(_) => 1,
+            _ => token_size,
+        }
+    }
 }
@@ -1,8 +1,12 @@
 pub mod config;
 
-use crate::{constants::*, errors::*, sell::config::*};
+use crate::{constants::*, errors::*, events::*, sell::config::*, utils::*};
 
-use anchor_lang::{prelude::*, AnchorDeserialize, InstructionData};
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+    AnchorDeserialize, InstructionData,
+};
 use anchor_spl::token::{Token, TokenAccount};
 
 use mpl_auction_house::{
@@ -17,7 +21,7 @@ use solana_program::{clock::UnixTimestamp, program::invoke_signed};
 
 /// Accounts for the [`sell_with_auctioneer` handler](auction_house/fn.sell_with_auctioneer.html).
 #[derive(Accounts, Clone)]
-#[instruction(trade_state_bump: u8, free_trade_state_bump: u8, program_as_signer_bump: u8, auctioneer_authority_bump: u8, token_size: u64)]
+#[instruction(trade_state_bump: u8, free_trade_state_bump: u8, program_as_signer_bump: u8, auctioneer_authority_bump: u8, token_size: u64, start_time: UnixTimestamp, end_time: UnixTimestamp, reserve_price: Option<u64>, min_bid_increment: Option<u64>, time_ext_period: Option<u32>, time_ext_delta: Option<u32>, allow_high_bid_cancel: Option<bool>, auction_type: Option<AuctionType>, buy_now_price: Option<u64>, anti_sniping: Option<AntiSnipingConfig>, keeper_reward: Option<u64>, seller_cancel_rule: Option<SellerCancelRule>, collection_gate: Option<Pubkey>, allowlist_root: Option<[u8; 32]>, record_bid_history: Option<bool>)]
 pub struct AuctioneerSell<'info> {
     /// Auction House Program used for CPI call
     pub auction_house_program: Program<'info, AuctionHouseProgram>,
@@ -27,7 +31,7 @@ pub struct AuctioneerSell<'info> {
     #[account(
         init,
         payer=wallet,
-        space=LISTING_CONFIG_SIZE,
+        space=ListingConfig::space(auction_type.as_ref(), record_bid_history),
         seeds=[
             LISTING_CONFIG.as_bytes(),
             wallet.key().as_ref(),
@@ -119,9 +123,35 @@ pub fn auctioneer_sell<'info>(
     time_ext_period: Option<u32>,
     time_ext_delta: Option<u32>,
     allow_high_bid_cancel: Option<bool>,
+    auction_type: Option<AuctionType>,
+    buy_now_price: Option<u64>,
+    anti_sniping: Option<AntiSnipingConfig>,
+    keeper_reward: Option<u64>,
+    seller_cancel_rule: Option<SellerCancelRule>,
+    collection_gate: Option<Pubkey>,
+    allowlist_root: Option<[u8; 32]>,
+    record_bid_history: Option<bool>,
 ) -> Result<()> {
-    ctx.accounts.listing_config.version = ListingConfigVersion::V0;
-    ctx.accounts.listing_config.highest_bid.version = ListingConfigVersion::V0;
+    assert_valid_auction_times(start_time, end_time)?;
+    let auction_type = auction_type.unwrap_or(AuctionType::English);
+    match &auction_type {
+        AuctionType::English => {}
+        AuctionType::Dutch(dutch_config) => {
+            assert_valid_dutch_auction_config(dutch_config, start_time, end_time)?
+        }
+        AuctionType::SealedBid(sealed_bid_config) => {
+            assert_valid_sealed_bid_config(sealed_bid_config)?
+        }
+        AuctionType::Edition(edition_config) => {
+            assert_valid_edition_config(edition_config, token_size)?
+        }
+    }
+    if let Some(buy_now_price) = buy_now_price {
+        assert_valid_buy_now_price(&auction_type, reserve_price.unwrap_or(0), buy_now_price)?;
+    }
+
+    ctx.accounts.listing_config.version = ListingConfigVersion::V1;
+    ctx.accounts.listing_config.highest_bid.version = ListingConfigVersion::V1;
     ctx.accounts.listing_config.start_time = start_time;
     ctx.accounts.listing_config.end_time = end_time;
     ctx.accounts.listing_config.reserve_price = reserve_price.unwrap_or(0);
@@ -129,11 +159,45 @@ pub fn auctioneer_sell<'info>(
     ctx.accounts.listing_config.time_ext_period = time_ext_period.unwrap_or(0);
     ctx.accounts.listing_config.time_ext_delta = time_ext_delta.unwrap_or(0);
     ctx.accounts.listing_config.allow_high_bid_cancel = allow_high_bid_cancel.unwrap_or(false);
+    ctx.accounts.listing_config.auction_type = auction_type;
+    ctx.accounts.listing_config.buy_now_price = buy_now_price;
+    ctx.accounts.listing_config.anti_sniping = anti_sniping.unwrap_or_default();
+    ctx.accounts.listing_config.keeper_reward = keeper_reward.unwrap_or(0);
+    ctx.accounts.listing_config.seller_cancel_rule =
+        seller_cancel_rule.unwrap_or(SellerCancelRule::Anytime);
+    ctx.accounts.listing_config.collection_gate = collection_gate;
+    ctx.accounts.listing_config.allowlist_root = allowlist_root;
+    ctx.accounts.listing_config.records_bid_history = record_bid_history.unwrap_or(false);
     ctx.accounts.listing_config.bump = *ctx
         .bumps
         .get("listing_config")
         .ok_or(AuctioneerError::BumpSeedNotInHashMap)?;
 
+    // The seller funds the keeper reward of every sale and any cancellation penalty up front.
+    let cancel_penalty = match ctx.accounts.listing_config.seller_cancel_rule {
+        SellerCancelRule::WithPenalty(penalty) => penalty,
+        _ => 0,
+    };
+    let deposit = ctx
+        .accounts
+        .listing_config
+        .keeper_reward
+        .checked_mul(ctx.accounts.listing_config.sale_count())
+        .and_then(|keeper_rewards| keeper_rewards.checked_add(cancel_penalty))
+        .ok_or(AuctioneerError::NumericalOverflow)?;
+    if deposit > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.wallet.to_account_info(),
+                    to: ctx.accounts.listing_config.to_account_info(),
+                },
+            ),
+            deposit,
+        )?;
+    }
+
     let cpi_program = ctx.accounts.auction_house_program.to_account_info();
     let cpi_accounts = AHSell {
         wallet: ctx.accounts.wallet.to_account_info(),
@@ -189,5 +253,16 @@ pub fn auctioneer_sell<'info>(
 
     invoke_signed(&ix, &cpi_accounts.to_account_infos(), &[&auctioneer_seeds])?;
 
+    emit!(ListingCreatedEvent {
+        listing_config: ctx.accounts.listing_config.key(),
+        seller: ctx.accounts.wallet.key(),
+        token_mint: ctx.accounts.token_account.mint,
+        token_size,
+        auction_type: ctx.accounts.listing_config.auction_type.clone(),
+        start_time,
+        end_time,
+        reserve_price: ctx.accounts.listing_config.reserve_price,
+    });
+
     Ok(())
 }
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use mpl_auction_house::{
+    self, constants::PREFIX, program::AuctionHouse as AuctionHouseProgram, AuctionHouse,
+};
+use solana_program::clock::UnixTimestamp;
+
+use crate::{constants::*, events::*, sell::config::*, utils::*};
+
+/// Accounts for the [`update_listing_config` handler](fn.auctioneer_update_listing_config.html).
+#[derive(Accounts)]
+#[instruction(token_size: u64)]
+pub struct AuctioneerUpdateListingConfig<'info> {
+    /// Auction House Program
+    pub auction_house_program: Program<'info, AuctionHouseProgram>,
+
+    /// The Listing Config used for listing settings
+    #[account(
+        mut,
+        seeds=[
+            LISTING_CONFIG.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &token_size.to_le_bytes()
+        ],
+        bump=listing_config.bump,
+    )]
+    pub listing_config: Account<'info, ListingConfig>,
+
+    /// The seller's wallet account.
+    pub wallet: Signer<'info>,
+
+    /// SPL token account of the listing.
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Auction House instance PDA account.
+    #[account(seeds=[PREFIX.as_bytes(), auction_house.creator.as_ref(), auction_house.treasury_mint.as_ref()], seeds::program=auction_house_program, bump=auction_house.bump)]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+}
+
+/// Change the timing and bidding settings of a listing that has not started or has no bids.
+pub fn auctioneer_update_listing_config<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerUpdateListingConfig<'info>>,
+    _token_size: u64,
+    start_time: Option<UnixTimestamp>,
+    end_time: Option<UnixTimestamp>,
+    reserve_price: Option<u64>,
+    min_bid_increment: Option<u64>,
+) -> Result<()> {
+    let listing_config = &mut ctx.accounts.listing_config;
+    assert_listing_config_updatable(listing_config)?;
+
+    let start_time = start_time.unwrap_or(listing_config.start_time);
+    let end_time = end_time.unwrap_or(listing_config.end_time);
+    let reserve_price = reserve_price.unwrap_or(listing_config.reserve_price);
+    assert_valid_auction_times(start_time, end_time)?;
+    if let AuctionType::Dutch(dutch_config) = &listing_config.auction_type {
+        assert_valid_dutch_auction_config(dutch_config, start_time, end_time)?;
+    }
+    if let Some(buy_now_price) = listing_config.buy_now_price {
+        assert_valid_buy_now_price(&listing_config.auction_type, reserve_price, buy_now_price)?;
+    }
+
+    listing_config.start_time = start_time;
+    listing_config.end_time = end_time;
+    listing_config.reserve_price = reserve_price;
+    if let Some(min_bid_increment) = min_bid_increment {
+        listing_config.min_bid_increment = min_bid_increment;
+    }
+    emit!(ListingConfigUpdatedEvent {
+        listing_config: listing_config.key(),
+        start_time,
+        end_time,
+        reserve_price,
+        min_bid_increment: listing_config.min_bid_increment,
+    });
+
+    Ok(())
+}
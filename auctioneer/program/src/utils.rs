@@ -1,6 +1,40 @@
 use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{spl_token, TokenAccount},
+};
+use mpl_token_metadata::{
+    pda::find_metadata_account,
+    state::{Metadata, TokenMetadataAccount},
+};
+use solana_program::{clock::UnixTimestamp, keccak};
 
-use crate::{errors::*, sell::config::*};
+use crate::{constants::*, errors::*, events::*, merkle_proof, sell::config::*};
+
+pub fn assert_valid_auction_times(
+    start_time: UnixTimestamp,
+    end_time: UnixTimestamp,
+) -> Result<()> {
+    if start_time >= end_time {
+        return err!(AuctioneerError::InvalidAuctionTimes);
+    }
+
+    Ok(())
+}
+
+/// Listings can be changed before the auction starts, or later as long as it has no bids. Sealed
+/// bids are committed without touching the listing, so those auctions lock at `start_time`.
+pub fn assert_listing_config_updatable(listing_config: &ListingConfig) -> Result<()> {
+    let clock = Clock::get()?;
+    let has_bids = listing_config.highest_bid.amount > 0 || !listing_config.bid_book.is_empty();
+    let is_sealed_bid = matches!(listing_config.auction_type, AuctionType::SealedBid(_));
+
+    if clock.unix_timestamp >= listing_config.start_time && (has_bids || is_sealed_bid) {
+        return err!(AuctioneerError::ListingConfigLocked);
+    }
+
+    Ok(())
+}
 
 pub fn assert_auction_active(listing_config: &Account<ListingConfig>) -> Result<()> {
     let clock = Clock::get()?;
@@ -15,6 +49,569 @@ pub fn assert_auction_active(listing_config: &Account<ListingConfig>) -> Result<
     Ok(())
 }
 
+/// Compute the price of a Dutch auction listing at `current_timestamp`, clamped to the listing's
+/// `start_time` and `end_time`.
+pub fn get_dutch_auction_price(
+    listing_config: &ListingConfig,
+    current_timestamp: UnixTimestamp,
+) -> Result<u64> {
+    let dutch_config = match &listing_config.auction_type {
+        AuctionType::Dutch(dutch_config) => dutch_config,
+        _ => return err!(AuctioneerError::NotDutchAuction),
+    };
+
+    let duration = (listing_config.end_time - listing_config.start_time).max(0);
+    let elapsed = (current_timestamp - listing_config.start_time).clamp(0, duration);
+    if duration == 0 {
+        return Ok(dutch_config.end_price);
+    }
+
+    let price_drop = dutch_config
+        .start_price
+        .checked_sub(dutch_config.end_price)
+        .ok_or(AuctioneerError::InvalidDutchAuctionConfig)?;
+    let interval = i64::from(dutch_config.interval.max(1));
+
+    let price = match dutch_config.curve {
+        DutchAuctionCurve::Linear => {
+            linear_price(dutch_config.start_price, price_drop, elapsed, duration)
+        }
+        DutchAuctionCurve::Stepped => linear_price(
+            dutch_config.start_price,
+            price_drop,
+            elapsed - (elapsed % interval),
+            duration,
+        ),
+        DutchAuctionCurve::Exponential => {
+            let steps = (elapsed / interval) as u64;
+            let ratio = u128::from(BASIS_POINTS - u64::from(dutch_config.decay_bps))
+                * DUTCH_DECAY_PRECISION
+                / u128::from(BASIS_POINTS);
+            let remaining =
+                u128::from(price_drop) * pow_fixed_point(ratio, steps) / DUTCH_DECAY_PRECISION;
+            dutch_config.end_price + remaining as u64
+        }
+    };
+
+    Ok(price)
+}
+
+fn linear_price(start_price: u64, price_drop: u64, elapsed: i64, duration: i64) -> u64 {
+    let drop_so_far = u128::from(price_drop) * elapsed as u128 / duration as u128;
+    start_price - drop_so_far as u64
+}
+
+/// Raise a `DUTCH_DECAY_PRECISION` fixed point `base` to `exp` by repeated squaring.
+fn pow_fixed_point(mut base: u128, mut exp: u64) -> u128 {
+    let mut result = DUTCH_DECAY_PRECISION;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base / DUTCH_DECAY_PRECISION;
+        }
+        base = base * base / DUTCH_DECAY_PRECISION;
+        exp >>= 1;
+    }
+
+    result
+}
+
+pub fn assert_valid_dutch_auction_config(
+    dutch_config: &DutchAuctionConfig,
+    start_time: UnixTimestamp,
+    end_time: UnixTimestamp,
+) -> Result<()> {
+    let valid_curve = match dutch_config.curve {
+        DutchAuctionCurve::Linear => true,
+        DutchAuctionCurve::Stepped => dutch_config.interval > 0,
+        DutchAuctionCurve::Exponential => {
+            dutch_config.interval > 0
+                && dutch_config.decay_bps > 0
+                && u64::from(dutch_config.decay_bps) <= BASIS_POINTS
+        }
+    };
+
+    if !valid_curve || dutch_config.start_price <= dutch_config.end_price || start_time >= end_time
+    {
+        return err!(AuctioneerError::InvalidDutchAuctionConfig);
+    }
+
+    Ok(())
+}
+
+pub fn assert_dutch_auction_price(
+    listing_config: &Account<ListingConfig>,
+    new_bid_price: u64,
+) -> Result<()> {
+    // The first accepted bid wins a Dutch auction, so any later bid is too late.
+    if listing_config.highest_bid.buyer_trade_state != Pubkey::default() {
+        return err!(AuctioneerError::AuctionEnded);
+    }
+
+    let clock = Clock::get()?;
+    if new_bid_price < get_dutch_auction_price(listing_config, clock.unix_timestamp)? {
+        return err!(AuctioneerError::BelowDutchAuctionPrice);
+    }
+
+    Ok(())
+}
+
+pub fn get_sealed_bid_config(listing_config: &ListingConfig) -> Result<&SealedBidConfig> {
+    match &listing_config.auction_type {
+        AuctionType::SealedBid(sealed_bid_config) => Ok(sealed_bid_config),
+        _ => err!(AuctioneerError::NotSealedBidAuction),
+    }
+}
+
+pub fn assert_valid_sealed_bid_config(sealed_bid_config: &SealedBidConfig) -> Result<()> {
+    if sealed_bid_config.reveal_period == 0 || sealed_bid_config.claim_period == 0 {
+        return err!(AuctioneerError::InvalidSealedBidConfig);
+    }
+
+    Ok(())
+}
+
+/// The commitment a bidder submits for a sealed bid of `price` hidden with `salt`.
+pub fn sealed_bid_commitment(price: u64, salt: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[&price.to_le_bytes(), salt]).0
+}
+
+pub fn assert_reveal_period_active(listing_config: &Account<ListingConfig>) -> Result<()> {
+    let sealed_bid_config = get_sealed_bid_config(listing_config)?;
+    let clock = Clock::get()?;
+    let current_timestamp = clock.unix_timestamp;
+
+    if current_timestamp <= listing_config.end_time {
+        return err!(AuctioneerError::AuctionActive);
+    } else if current_timestamp
+        > listing_config.end_time + i64::from(sealed_bid_config.reveal_period)
+    {
+        return err!(AuctioneerError::RevealPeriodEnded);
+    }
+
+    Ok(())
+}
+
+pub fn assert_reveal_period_over(listing_config: &Account<ListingConfig>) -> Result<()> {
+    let sealed_bid_config = get_sealed_bid_config(listing_config)?;
+    let clock = Clock::get()?;
+    let current_timestamp = clock.unix_timestamp;
+
+    if current_timestamp <= listing_config.end_time + i64::from(sealed_bid_config.reveal_period) {
+        return err!(AuctioneerError::RevealPeriodActive);
+    }
+
+    Ok(())
+}
+
+/// Record a revealed sealed bid, keeping track of the two highest bids.
+pub fn process_sealed_bid_reveal(
+    listing_config: &mut Account<ListingConfig>,
+    bidder: Pubkey,
+    price: u64,
+) -> Result<()> {
+    assert_exceeds_reserve_price(listing_config, price)?;
+
+    if price > listing_config.highest_bid.amount {
+        listing_config.second_highest_bid = listing_config.highest_bid.amount;
+        listing_config.second_highest_bidder = listing_config.highest_bidder;
+        listing_config.highest_bid.amount = price;
+        listing_config.highest_bidder = bidder;
+    } else if price > listing_config.second_highest_bid {
+        listing_config.second_highest_bid = price;
+        listing_config.second_highest_bidder = bidder;
+    }
+
+    Ok(())
+}
+
+/// The wallet that can claim a sealed-bid auction at `current_timestamp` and the price it must bid
+/// to claim the item. The highest bidder has until the end of the claim period, after which the win
+/// passes to the second highest bidder at their own bid for another claim period. Once nobody can
+/// claim it the auction has ended unsold.
+pub fn get_sealed_bid_claim(
+    listing_config: &ListingConfig,
+    current_timestamp: UnixTimestamp,
+) -> Result<(Pubkey, u64)> {
+    let sealed_bid_config = get_sealed_bid_config(listing_config)?;
+    let claim_period = i64::from(sealed_bid_config.claim_period);
+    let claim_deadline =
+        listing_config.end_time + i64::from(sealed_bid_config.reveal_period) + claim_period;
+
+    if current_timestamp <= claim_deadline {
+        let price = match sealed_bid_config.pricing {
+            SealedBidPricing::FirstPrice => listing_config.highest_bid.amount,
+            SealedBidPricing::SecondPrice => listing_config
+                .second_highest_bid
+                .max(listing_config.reserve_price),
+        };
+        return Ok((listing_config.highest_bidder, price));
+    }
+
+    if current_timestamp <= claim_deadline + claim_period
+        && listing_config.second_highest_bidder != Pubkey::default()
+    {
+        return Ok((
+            listing_config.second_highest_bidder,
+            listing_config.second_highest_bid,
+        ));
+    }
+
+    err!(AuctioneerError::AuctionEnded)
+}
+
+/// Whether a sealed-bid auction has ended without anyone claiming the item in time.
+pub fn is_sealed_bid_unclaimed(
+    listing_config: &ListingConfig,
+    current_timestamp: UnixTimestamp,
+) -> bool {
+    matches!(listing_config.auction_type, AuctionType::SealedBid(_))
+        && listing_config.highest_bid.buyer_trade_state == Pubkey::default()
+        && get_sealed_bid_claim(listing_config, current_timestamp).is_err()
+}
+
+/// Whether the highest bid can no longer be settled, letting the seller cancel despite it: the
+/// sealed-bid winners let their claims lapse, nobody settled it within `SETTLEMENT_GRACE_PERIOD`,
+/// or, when its trade state and escrow are passed in, the bid was canceled or its escrow no longer
+/// covers it.
+pub fn is_highest_bid_unsettleable(
+    listing_config: &ListingConfig,
+    auction_house: &Pubkey,
+    treasury_mint: &Pubkey,
+    highest_bid_accounts: &[AccountInfo],
+    current_timestamp: UnixTimestamp,
+) -> Result<bool> {
+    // Sealed-bid winners can claim the item until the claim periods after the reveal period end.
+    let settlement_start = match &listing_config.auction_type {
+        AuctionType::SealedBid(sealed_bid_config) => {
+            listing_config.end_time
+                + i64::from(sealed_bid_config.reveal_period)
+                + 2 * i64::from(sealed_bid_config.claim_period)
+        }
+        _ => listing_config.end_time,
+    };
+    if is_sealed_bid_unclaimed(listing_config, current_timestamp)
+        || current_timestamp > settlement_start.saturating_add(SETTLEMENT_GRACE_PERIOD)
+    {
+        return Ok(true);
+    }
+
+    let (trade_state, escrow_payment_account) = match highest_bid_accounts {
+        [trade_state, escrow_payment_account, ..] => (trade_state, escrow_payment_account),
+        _ => return Ok(false),
+    };
+    let (escrow_payment_key, _) = Pubkey::find_program_address(
+        &[
+            mpl_auction_house::constants::PREFIX.as_bytes(),
+            auction_house.as_ref(),
+            listing_config.highest_bidder.as_ref(),
+        ],
+        &mpl_auction_house::id(),
+    );
+    if trade_state.key() != listing_config.highest_bid.buyer_trade_state
+        || escrow_payment_account.key() != escrow_payment_key
+    {
+        return err!(AuctioneerError::InvalidHighestBidAccounts);
+    }
+    if trade_state.data_is_empty() {
+        return Ok(true);
+    }
+
+    // A sealed-bid claim may be paid at the second price, which the escrow only needs to cover.
+    if matches!(listing_config.auction_type, AuctionType::SealedBid(_)) {
+        return Ok(false);
+    }
+    let is_native = *treasury_mint == spl_token::native_mint::id();
+    Ok(get_escrow_balance(escrow_payment_account, is_native)? < listing_config.highest_bid.amount)
+}
+
+/// Forget a canceled highest bid, so it no longer meets the reserve price or has to be outbid. A
+/// canceled sealed-bid claim only gives up the claim, the revealed bids still decide who can claim.
+pub fn clear_highest_bid(listing_config: &mut ListingConfig) {
+    listing_config.highest_bid.buyer_trade_state = Pubkey::default();
+    listing_config.highest_bid_deposit = 0;
+    if !matches!(listing_config.auction_type, AuctionType::SealedBid(_)) {
+        listing_config.highest_bid.amount = 0;
+        listing_config.highest_bidder = Pubkey::default();
+    }
+}
+
+/// Assert the sealed bid of `bidder` can no longer win the auction, so its deposit can be released.
+pub fn assert_sealed_bid_released(
+    listing_config: &Account<ListingConfig>,
+    bidder: Pubkey,
+) -> Result<()> {
+    assert_reveal_period_over(listing_config)?;
+
+    let is_open = if listing_config.highest_bid.buyer_trade_state != Pubkey::default() {
+        // The claiming bid is paid from the winner's escrow when the sale is executed.
+        listing_config.highest_bidder == bidder
+    } else {
+        // Either of the two highest bidders can still claim the item until the claim periods end.
+        !is_sealed_bid_unclaimed(listing_config, Clock::get()?.unix_timestamp)
+            && (listing_config.highest_bidder == bidder
+                || listing_config.second_highest_bidder == bidder)
+    };
+    if is_open {
+        return err!(AuctioneerError::SealedBidStillOpen);
+    }
+
+    Ok(())
+}
+
+pub fn assert_sealed_bid_winner(
+    listing_config: &Account<ListingConfig>,
+    bidder: Pubkey,
+    new_bid_price: u64,
+) -> Result<()> {
+    // The first bid placed after the reveal period claims the item.
+    if listing_config.highest_bid.buyer_trade_state != Pubkey::default() {
+        return err!(AuctioneerError::AuctionEnded);
+    }
+
+    let clock = Clock::get()?;
+    let (winner, price) = get_sealed_bid_claim(listing_config, clock.unix_timestamp)?;
+    if winner != bidder {
+        return err!(AuctioneerError::NotHighestBidder);
+    } else if new_bid_price != price {
+        return err!(AuctioneerError::SealedBidPriceMismatch);
+    }
+
+    Ok(())
+}
+
+pub fn get_edition_config(listing_config: &ListingConfig) -> Result<&EditionConfig> {
+    match &listing_config.auction_type {
+        AuctionType::Edition(edition_config) => Ok(edition_config),
+        _ => err!(AuctioneerError::NotEditionAuction),
+    }
+}
+
+pub fn assert_valid_edition_config(edition_config: &EditionConfig, token_size: u64) -> Result<()> {
+    if edition_config.winners == 0
+        || usize::from(edition_config.winners) > MAX_EDITION_WINNERS
+        || u64::from(edition_config.winners) != token_size
+    {
+        return err!(AuctioneerError::InvalidEditionConfig);
+    }
+
+    Ok(())
+}
+
+/// The price every winner of a uniform price edition auction pays.
+pub fn get_edition_clearing_price(listing_config: &ListingConfig) -> u64 {
+    listing_config
+        .clearing_bid
+        .max(listing_config.reserve_price)
+}
+
+/// Record a new highest bid on a single item auction, returning the bid it replaces.
+pub fn replace_highest_bid(listing_config: &mut ListingConfig, bid: BidBookEntry) -> BidBookEntry {
+    let outbid = BidBookEntry {
+        bidder: listing_config.highest_bidder,
+        amount: listing_config.highest_bid.amount,
+        buyer_trade_state: listing_config.highest_bid.buyer_trade_state,
+        keeps_escrow: listing_config.highest_bid_keeps_escrow,
+        deposit: listing_config.highest_bid_deposit,
+    };
+
+    listing_config.highest_bid.amount = bid.amount;
+    listing_config.highest_bid.buyer_trade_state = bid.buyer_trade_state;
+    listing_config.highest_bidder = bid.bidder;
+    listing_config.highest_bid_keeps_escrow = bid.keeps_escrow;
+    listing_config.highest_bid_deposit = bid.deposit;
+
+    outbid
+}
+
+/// Add a bid to the bid book of an edition auction, returning the bid it pushes out of the book.
+pub fn process_edition_bid(
+    listing_config: &mut ListingConfig,
+    bid: BidBookEntry,
+) -> Result<Option<BidBookEntry>> {
+    let winners = usize::from(get_edition_config(listing_config)?.winners);
+    let min_bid_increment = listing_config.min_bid_increment;
+    let book = &mut listing_config.bid_book;
+
+    // Each bidder holds a single place in the book, so a raised bid replaces their previous one.
+    let outbid = if let Some(index) = book.iter().position(|entry| entry.bidder == bid.bidder) {
+        if bid.amount <= book[index].amount {
+            return err!(AuctioneerError::BidTooLow);
+        }
+        Some(book.remove(index))
+    } else if book.len() >= winners {
+        let lowest_bid = book[book.len() - 1].amount;
+        if bid.amount <= lowest_bid {
+            return err!(AuctioneerError::BidTooLow);
+        } else if bid.amount < lowest_bid.saturating_add(min_bid_increment) {
+            return err!(AuctioneerError::BelowBidIncrement);
+        }
+        let outbid = book.pop();
+        listing_config.clearing_bid = listing_config.clearing_bid.max(lowest_bid);
+        outbid
+    } else {
+        None
+    };
+
+    let book = &mut listing_config.bid_book;
+    let index = book
+        .iter()
+        .position(|entry| entry.amount < bid.amount)
+        .unwrap_or(book.len());
+    book.insert(index, bid);
+
+    let top_bid = book[0].clone();
+    listing_config.highest_bid.amount = top_bid.amount;
+    listing_config.highest_bid.buyer_trade_state = top_bid.buyer_trade_state;
+    listing_config.highest_bidder = top_bid.bidder;
+
+    Ok(outbid)
+}
+
+/// Lower a winning bid of a uniform price edition auction to the clearing price once the auction
+/// is over, returning the bid it replaces. The replaced bid's funds stay in escrow for the new one.
+pub fn process_edition_claim(
+    listing_config: &mut ListingConfig,
+    bidder: Pubkey,
+    new_bid_price: u64,
+    buyer_trade_state: Pubkey,
+) -> Result<BidBookEntry> {
+    if get_edition_config(listing_config)?.pricing != EditionPricing::UniformPrice {
+        return err!(AuctioneerError::AuctionEnded);
+    }
+
+    let clearing_price = get_edition_clearing_price(listing_config);
+    let entry = listing_config
+        .bid_book
+        .iter_mut()
+        .find(|entry| entry.bidder == bidder)
+        .ok_or(AuctioneerError::NotEditionWinner)?;
+    if new_bid_price != clearing_price || entry.amount == clearing_price {
+        return err!(AuctioneerError::EditionPriceMismatch);
+    }
+
+    let outbid = BidBookEntry {
+        keeps_escrow: true,
+        ..entry.clone()
+    };
+    entry.amount = new_bid_price;
+    entry.buyer_trade_state = buyer_trade_state;
+
+    Ok(outbid)
+}
+
+/// The funds available in an escrow payment account, excluding the rent of a native SOL escrow.
+pub fn get_escrow_balance(escrow_payment_account: &AccountInfo, is_native: bool) -> Result<u64> {
+    if is_native {
+        let rent_minimum = Rent::get()?.minimum_balance(escrow_payment_account.data_len());
+        Ok(escrow_payment_account
+            .lamports()
+            .saturating_sub(rent_minimum))
+    } else if escrow_payment_account.data_is_empty() {
+        Ok(0)
+    } else {
+        Ok(Account::<TokenAccount>::try_from_unchecked(escrow_payment_account)?.amount)
+    }
+}
+
+/// Whether Auction House can pay a refund of `treasury_mint` to `receipt_account`. SOL refunds go to
+/// the bidder's wallet, SPL refunds to the bidder's associated token account as long as it exists,
+/// isn't frozen and has no delegate.
+pub fn can_receive_refund(
+    receipt_account: &AccountInfo,
+    wallet: &Pubkey,
+    treasury_mint: &Pubkey,
+) -> bool {
+    if *treasury_mint == spl_token::native_mint::id() {
+        return receipt_account.key() == *wallet;
+    }
+    if receipt_account.key() != get_associated_token_address(wallet, treasury_mint)
+        || receipt_account.owner != &spl_token::id()
+        || receipt_account.data_is_empty()
+    {
+        return false;
+    }
+
+    match Account::<TokenAccount>::try_from_unchecked(receipt_account) {
+        Ok(token_account) => {
+            token_account.owner == *wallet
+                && token_account.delegate.is_none()
+                && !token_account.is_frozen()
+        }
+        Err(_) => false,
+    }
+}
+
+/// Record the amount a bid added to its bidder's escrow, adding to any funds it took over from the
+/// bid it replaced.
+pub fn record_bid_deposit(
+    listing_config: &mut ListingConfig,
+    buyer_trade_state: Pubkey,
+    deposit: u64,
+) {
+    if let AuctionType::Edition(_) = listing_config.auction_type {
+        if let Some(entry) = listing_config
+            .bid_book
+            .iter_mut()
+            .find(|entry| entry.buyer_trade_state == buyer_trade_state)
+        {
+            entry.deposit = entry.deposit.saturating_add(deposit);
+        }
+    } else if listing_config.highest_bid.buyer_trade_state == buyer_trade_state {
+        listing_config.highest_bid_deposit =
+            listing_config.highest_bid_deposit.saturating_add(deposit);
+    }
+}
+
+/// Remove a winning bid being settled from the bid book of an edition auction.
+pub fn process_edition_settlement(
+    listing_config: &mut ListingConfig,
+    buyer: Pubkey,
+    buyer_trade_state: Pubkey,
+    buyer_price: u64,
+) -> Result<()> {
+    let pricing = get_edition_config(listing_config)?.pricing;
+    let clearing_price = get_edition_clearing_price(listing_config);
+    let index = listing_config
+        .bid_book
+        .iter()
+        .position(|entry| entry.bidder == buyer && entry.buyer_trade_state == buyer_trade_state)
+        .ok_or(AuctioneerError::NotEditionWinner)?;
+
+    let amount = listing_config.bid_book[index].amount;
+    if amount != buyer_price
+        || (pricing == EditionPricing::UniformPrice && amount != clearing_price)
+    {
+        return err!(AuctioneerError::EditionPriceMismatch);
+    }
+    listing_config.bid_book.remove(index);
+
+    Ok(())
+}
+
+pub fn assert_valid_buy_now_price(
+    auction_type: &AuctionType,
+    reserve_price: u64,
+    buy_now_price: u64,
+) -> Result<()> {
+    if *auction_type != AuctionType::English || buy_now_price == 0 || buy_now_price < reserve_price
+    {
+        return err!(AuctioneerError::InvalidBuyNowPrice);
+    }
+
+    Ok(())
+}
+
+pub fn is_buy_now_bid(listing_config: &ListingConfig, bid_price: u64) -> bool {
+    listing_config
+        .buy_now_price
+        .map_or(false, |buy_now_price| bid_price >= buy_now_price)
+}
+
+/// Whether the English auction was already won by a bid at the buy now price.
+pub fn is_bought_now(listing_config: &ListingConfig) -> bool {
+    is_buy_now_bid(listing_config, listing_config.highest_bid.amount)
+}
+
 pub fn assert_auction_over(listing_config: &Account<ListingConfig>) -> Result<()> {
     let clock = Clock::get()?;
     let current_timestamp = clock.unix_timestamp;
@@ -63,13 +660,133 @@ pub fn assert_highest_bidder(
     Ok(())
 }
 
-pub fn process_time_extension(listing_config: &mut Account<ListingConfig>) -> Result<()> {
+/// Whether a bid at or above the reserve price has been placed on the listing.
+pub fn is_reserve_met(listing_config: &ListingConfig) -> bool {
+    listing_config.highest_bid.amount > 0
+        && listing_config.highest_bid.amount >= listing_config.reserve_price
+}
+
+/// Extend a late English auction bid's `end_time`, within the listing's anti-sniping limits.
+/// Must be called before the new bid replaces `highest_bid`.
+pub fn process_time_extension(
+    listing_config: &mut Account<ListingConfig>,
+    new_bid_price: u64,
+) -> Result<()> {
     let clock = Clock::get()?;
     let current_timestamp = clock.unix_timestamp;
 
-    if current_timestamp >= (listing_config.end_time - i64::from(listing_config.time_ext_period)) {
-        listing_config.end_time += i64::from(listing_config.time_ext_delta);
+    if current_timestamp < (listing_config.end_time - i64::from(listing_config.time_ext_period)) {
+        return Ok(());
+    }
+
+    let anti_sniping = &listing_config.anti_sniping;
+    let price_increase = new_bid_price.saturating_sub(listing_config.highest_bid.amount);
+    if price_increase <= anti_sniping.min_price_increase {
+        return Ok(());
+    }
+    if anti_sniping.max_extensions > 0
+        && listing_config.extension_count >= anti_sniping.max_extensions
+    {
+        return Ok(());
+    }
+
+    let mut delta = listing_config.time_ext_delta;
+    if anti_sniping.max_total_extension > 0 {
+        delta = delta.min(
+            anti_sniping
+                .max_total_extension
+                .saturating_sub(listing_config.total_extension),
+        );
+    }
+    if delta == 0 {
+        return Ok(());
+    }
+
+    listing_config.end_time += i64::from(delta);
+    listing_config.extension_count += 1;
+    listing_config.total_extension += delta;
+
+    emit!(AuctionExtendedEvent {
+        listing_config: listing_config.key(),
+        end_time: listing_config.end_time,
+        extension_count: listing_config.extension_count,
+    });
+
+    Ok(())
+}
+
+/// Record a bid in the bid history ring buffer of the listing.
+pub fn record_bid_history(listing_config: &mut ListingConfig, entry: BidHistoryEntry) {
+    let index = listing_config.bid_count as usize % BID_HISTORY_LEN;
+    if index < listing_config.bid_history.len() {
+        listing_config.bid_history[index] = entry;
+    } else {
+        listing_config.bid_history.push(entry);
+    }
+    listing_config.bid_count = listing_config.bid_count.wrapping_add(1);
+}
+
+/// The allowlist merkle tree leaf of `wallet`.
+pub fn allowlist_leaf(wallet: &Pubkey) -> [u8; 32] {
+    keccak::hashv(&[&[0x00], wallet.as_ref()]).0
+}
+
+pub fn assert_allowlisted(
+    listing_config: &ListingConfig,
+    wallet: &Pubkey,
+    allowlist_proof: Option<Vec<[u8; 32]>>,
+) -> Result<()> {
+    if let Some(root) = listing_config.allowlist_root {
+        let proof = allowlist_proof.ok_or(AuctioneerError::NotAllowlisted)?;
+        if !merkle_proof::verify(proof, root, allowlist_leaf(wallet)) {
+            return err!(AuctioneerError::NotAllowlisted);
+        }
     }
 
     Ok(())
 }
+
+/// Check `wallet` holds a token whose metadata is verified as part of `collection`.
+pub fn assert_collection_member(
+    collection: &Pubkey,
+    wallet: &Pubkey,
+    token_account: &AccountInfo,
+    metadata: &AccountInfo,
+) -> Result<()> {
+    let token = Account::<TokenAccount>::try_from(token_account)
+        .map_err(|_| AuctioneerError::NotCollectionMember)?;
+    if token.owner != *wallet || token.amount == 0 {
+        return err!(AuctioneerError::NotCollectionMember);
+    }
+
+    let (metadata_key, _) = find_metadata_account(&token.mint);
+    if metadata.key() != metadata_key {
+        return err!(AuctioneerError::NotCollectionMember);
+    }
+    let metadata =
+        Metadata::from_account_info(metadata).map_err(|_| AuctioneerError::NotCollectionMember)?;
+    match metadata.collection {
+        Some(member_of) if member_of.verified && member_of.key == *collection => Ok(()),
+        _ => err!(AuctioneerError::NotCollectionMember),
+    }
+}
+
+/// Check the bidder passes the allowlist and collection gates of the listing. The token account and
+/// metadata of the gating collection are taken from the front of `remaining_accounts`, the accounts
+/// after them are returned.
+pub fn assert_bid_gates<'a, 'info>(
+    listing_config: &ListingConfig,
+    wallet: &Pubkey,
+    allowlist_proof: Option<Vec<[u8; 32]>>,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<&'a [AccountInfo<'info>]> {
+    assert_allowlisted(listing_config, wallet, allowlist_proof)?;
+    match (listing_config.collection_gate, remaining_accounts) {
+        (None, _) => Ok(remaining_accounts),
+        (Some(collection), [token_account, metadata, rest @ ..]) => {
+            assert_collection_member(&collection, wallet, token_account, metadata)?;
+            Ok(rest)
+        }
+        (Some(_), _) => err!(AuctioneerError::NotCollectionMember),
+    }
+}
@@ -1,7 +1,7 @@
 use anchor_lang::{prelude::*, AnchorDeserialize, InstructionData};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token},
+    token::{spl_token, Mint, Token},
 };
 
 use mpl_auction_house::{
@@ -14,6 +14,8 @@ use mpl_auction_house::{
 
 use solana_program::program::invoke_signed;
 
+use crate::{commit_bid::sealed_bid::*, constants::*, errors::*, utils::*};
+
 /// Accounts for the [`withdraw_with_auctioneer` handler](auction_house/fn.withdraw_with_auctioneer.html).
 #[derive(Accounts, Clone)]
 #[instruction(escrow_payment_bump: u8, auctioneer_authority_bump: u8)]
@@ -69,6 +71,11 @@ pub struct AuctioneerWithdraw<'info> {
     )]
     pub ah_auctioneer_pda: Account<'info, mpl_auction_house::Auctioneer>,
 
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// The deposits locked in the escrow by the wallet's open sealed bids, empty if it has none.
+    #[account(seeds=[ESCROW_LOCK.as_bytes(), auction_house.key().as_ref(), wallet.key().as_ref()], bump)]
+    pub escrow_lock: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub ata_program: Program<'info, AssociatedToken>,
@@ -82,6 +89,17 @@ pub fn auctioneer_withdraw<'info>(
     auctioneer_authority_bump: u8,
     amount: u64,
 ) -> Result<()> {
+    // Deposits backing open sealed bids stay in escrow.
+    if !ctx.accounts.escrow_lock.data_is_empty() {
+        let escrow_lock =
+            Account::<EscrowLock>::try_from(&ctx.accounts.escrow_lock.to_account_info())?;
+        let is_native = ctx.accounts.treasury_mint.key() == spl_token::native_mint::id();
+        let escrow_balance = get_escrow_balance(&ctx.accounts.escrow_payment_account, is_native)?;
+        if escrow_balance.saturating_sub(amount) < escrow_lock.locked {
+            return err!(AuctioneerError::EscrowLocked);
+        }
+    }
+
     let cpi_program = ctx.accounts.auction_house_program.to_account_info();
     let cpi_accounts = AHWithdraw {
         wallet: ctx.accounts.wallet.to_account_info(),
@@ -3,7 +3,15 @@ pub mod common;
 pub mod utils;
 
 use common::*;
-use mpl_auctioneer::sell::config::ListingConfig;
+use mpl_auctioneer::{
+    events::BidPlacedEvent,
+    sell::config::{
+        AntiSnipingConfig, AuctionType, DutchAuctionConfig, DutchAuctionCurve, ListingConfig,
+        BID_HISTORY_LEN, LISTING_CONFIG_SIZE,
+    },
+    utils::allowlist_leaf,
+};
+use solana_program::{instruction::AccountMeta, keccak, program_pack::Pack};
 use std::{assert_eq, time::SystemTime};
 use utils::setup_functions::*;
 
@@ -94,6 +102,18 @@ async fn buy_success() {
         .process_transaction(buy_tx)
         .await
         .unwrap();
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(listing.len(), LISTING_CONFIG_SIZE);
+    let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
+    assert!(config.bid_history.is_empty());
+    assert_eq!(config.bid_count, 0);
 }
 
 #[tokio::test]
@@ -122,7 +142,7 @@ async fn multiple_bids() {
         .await
         .unwrap();
 
-    let ((sell_acc, listing_config_address), sell_tx) = sell(
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
         &mut context,
         &ahkey,
         &ah,
@@ -142,6 +162,10 @@ async fn multiple_bids() {
         None,
         None,
         None,
+        SellOptions {
+            record_bid_history: Some(true),
+            ..SellOptions::default()
+        },
     );
     context
         .banks_client
@@ -202,7 +226,7 @@ async fn multiple_bids() {
         .await
         .unwrap();
 
-    let (_acc1, buy_tx1) = buy(
+    let (_acc1, buy_tx1) = buy_with_options(
         &mut context,
         &ahkey,
         &ah,
@@ -212,13 +236,149 @@ async fn multiple_bids() {
         &sell_acc.wallet,
         &listing_config_address,
         10000000000,
+        BuyOptions {
+            outbid_accounts: outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &buyer0.pubkey(),
+                1000000000,
+            ),
+            ..BuyOptions::default()
+        },
+    );
+    let logs = process_transaction_with_logs(&mut context, buy_tx1).await;
+    let events = parse_events::<BidPlacedEvent>(&logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].listing_config, listing_config_address);
+    assert_eq!(events[0].bidder, buyer1.pubkey());
+    assert_eq!(events[0].amount, 10000000000);
+    assert_eq!(events[0].previous_bidder, buyer0.pubkey());
+    assert_eq!(events[0].previous_bid, 1000000000);
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
+    assert_eq!(config.highest_bid.amount, 10000000000);
+    assert_eq!(config.bid_count, 2);
+    assert_eq!(config.bid_history[0].bidder, buyer0.pubkey());
+    assert_eq!(config.bid_history[0].amount, 1000000000);
+    assert_eq!(config.bid_history[1].bidder, buyer1.pubkey());
+    assert_eq!(config.bid_history[1].amount, 10000000000);
+}
+
+#[tokio::test]
+async fn bid_history_wraps_around() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+
+    airdrop(&mut context, &test_metadata.token.pubkey(), 1000000000)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            - 60) as i64,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 60) as i64,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            record_bid_history: Some(true),
+            ..SellOptions::default()
+        },
     );
     context
         .banks_client
-        .process_transaction(buy_tx1)
+        .process_transaction(sell_tx)
         .await
         .unwrap();
 
+    let bid_total = BID_HISTORY_LEN + 2;
+    let mut bids: Vec<(Keypair, u64)> = vec![];
+    for i in 0..bid_total {
+        let buyer = Keypair::new();
+        let price = (i as u64 + 1) * ONE_SOL / 10;
+        airdrop(&mut context, &buyer.pubkey(), 10000000000)
+            .await
+            .unwrap();
+        let (_, deposit_tx) = deposit(&mut context, &ahkey, &ah, &test_metadata, &buyer, price);
+        context
+            .banks_client
+            .process_transaction(deposit_tx)
+            .await
+            .unwrap();
+
+        let outbid = match bids.last() {
+            Some((previous_buyer, previous_price)) => outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &previous_buyer.pubkey(),
+                *previous_price,
+            ),
+            None => vec![],
+        };
+        let (_acc, buy_tx) = buy_with_options(
+            &mut context,
+            &ahkey,
+            &ah,
+            &test_metadata,
+            &test_metadata.token.pubkey(),
+            &buyer,
+            &sell_acc.wallet,
+            &listing_config_address,
+            price,
+            BuyOptions {
+                outbid_accounts: outbid,
+                ..BuyOptions::default()
+            },
+        );
+        context
+            .banks_client
+            .process_transaction(buy_tx)
+            .await
+            .unwrap();
+        bids.push((buyer, price));
+    }
+
     let listing = context
         .banks_client
         .get_account(listing_config_address)
@@ -227,7 +387,18 @@ async fn multiple_bids() {
         .unwrap()
         .data;
     let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
-    assert_eq!(config.highest_bid.amount, 10000000000);
+    assert_eq!(config.bid_count as usize, bid_total);
+    assert_eq!(config.bid_history.len(), BID_HISTORY_LEN);
+    // The two newest bids overwrote the two oldest, the rest are in bid order.
+    for (slot, entry) in config.bid_history.iter().enumerate() {
+        let bid = if slot < bid_total - BID_HISTORY_LEN {
+            slot + BID_HISTORY_LEN
+        } else {
+            slot
+        };
+        assert_eq!(entry.bidder, bids[bid].0.pubkey());
+        assert_eq!(entry.amount, bids[bid].1);
+    }
 }
 
 #[tokio::test]
@@ -650,7 +821,7 @@ async fn multiple_bids_increment_success() {
         .await
         .unwrap();
 
-    let (_acc1, buy_tx1) = buy(
+    let (_acc1, buy_tx1) = buy_with_options(
         &mut context,
         &ahkey,
         &ah,
@@ -660,6 +831,17 @@ async fn multiple_bids_increment_success() {
         &sell_acc.wallet,
         &listing_config_address,
         30000000000,
+        BuyOptions {
+            outbid_accounts: outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &buyer0.pubkey(),
+                1000000000,
+            ),
+            ..BuyOptions::default()
+        },
     );
     context
         .banks_client
@@ -810,7 +992,7 @@ async fn multiple_bids_time_ext_success() {
         .await
         .unwrap();
 
-    let (_acc1, buy_tx1) = buy(
+    let (_acc1, buy_tx1) = buy_with_options(
         &mut context,
         &ahkey,
         &ah,
@@ -820,6 +1002,17 @@ async fn multiple_bids_time_ext_success() {
         &sell_acc.wallet,
         &listing_config_address,
         10000000000,
+        BuyOptions {
+            outbid_accounts: outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &buyer0.pubkey(),
+                1000000000,
+            ),
+            ..BuyOptions::default()
+        },
     );
     context
         .banks_client
@@ -838,3 +1031,1013 @@ async fn multiple_bids_time_ext_success() {
     let config2 = ListingConfig::try_deserialize(&mut listing2.as_ref()).unwrap();
     assert_eq!(config2.end_time, end_time_t0 + 120);
 }
+
+#[tokio::test]
+async fn dutch_auction_first_bid_at_price_wins() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+
+    airdrop(&mut context, &test_metadata.token.pubkey(), 1000000000)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            - 60) as i64,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 60) as i64,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            auction_type: Some(AuctionType::Dutch(DutchAuctionConfig {
+                start_price: 2000000000,
+                end_price: 1000000000,
+                curve: DutchAuctionCurve::Linear,
+                interval: 0,
+                decay_bps: 0,
+            })),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    // The price is roughly halfway between the start and end price.
+    let buyer0 = Keypair::new();
+    airdrop(&mut context, &buyer0.pubkey(), 10000000000)
+        .await
+        .unwrap();
+    let (_acc0, buy_tx0) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer0,
+        &sell_acc.wallet,
+        &listing_config_address,
+        1100000000,
+    );
+    let result = context
+        .banks_client
+        .process_transaction(buy_tx0)
+        .await
+        .unwrap_err();
+    assert_error!(result, BELOW_DUTCH_AUCTION_PRICE);
+
+    let buyer1 = Keypair::new();
+    airdrop(&mut context, &buyer1.pubkey(), 10000000000)
+        .await
+        .unwrap();
+    let (_acc1, buy_tx1) = buy_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer1,
+        &sell_acc.wallet,
+        &listing_config_address,
+        1900000000,
+        BuyOptions {
+            sale_accounts: dutch_sale_accounts(&ahkey, &ah, &test_metadata, &buyer1.pubkey()),
+            ..BuyOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx1)
+        .await
+        .unwrap();
+
+    // The winning bid settles the sale and closes the listing.
+    let buyer1_token_account =
+        get_associated_token_address(&buyer1.pubkey(), &test_metadata.mint.pubkey());
+    let token_account = context
+        .banks_client
+        .get_account(buyer1_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let token = spl_token::state::Account::unpack_from_slice(&token_account.data).unwrap();
+    assert_eq!(token.amount, 1);
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap();
+    assert!(listing.is_none());
+
+    let buyer2 = Keypair::new();
+    airdrop(&mut context, &buyer2.pubkey(), 10000000000)
+        .await
+        .unwrap();
+    let (_acc2, buy_tx2) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer2,
+        &sell_acc.wallet,
+        &listing_config_address,
+        2000000000,
+    );
+    let result = context
+        .banks_client
+        .process_transaction(buy_tx2)
+        .await
+        .unwrap_err();
+    assert_error!(result, ACCOUNT_NOT_INITIALIZED);
+}
+
+#[tokio::test]
+async fn buy_now_ends_auction_and_refunds_previous_bid() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+
+    airdrop(&mut context, &test_metadata.token.pubkey(), 1000000000)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            - 60) as i64,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 60) as i64,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            buy_now_price: Some(2 * ONE_SOL),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    let buyer0 = Keypair::new();
+    airdrop(&mut context, &buyer0.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (acc0, buy_tx0) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer0,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx0)
+        .await
+        .unwrap();
+    let buyer0_before = context
+        .banks_client
+        .get_account(buyer0.pubkey())
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+
+    let buyer1 = Keypair::new();
+    airdrop(&mut context, &buyer1.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (acc1, buy_tx1) = buy_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer1,
+        &sell_acc.wallet,
+        &listing_config_address,
+        2 * ONE_SOL,
+        BuyOptions {
+            outbid_accounts: outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &buyer0.pubkey(),
+                ONE_SOL,
+            ),
+            ..BuyOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx1)
+        .await
+        .unwrap();
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
+    assert_eq!(config.highest_bid.amount, 2 * ONE_SOL);
+    assert_eq!(config.highest_bid.buyer_trade_state, acc1.buyer_trade_state);
+
+    // The outbid trade state is closed and its bid returned to the wallet.
+    let trade_state0 = context
+        .banks_client
+        .get_account(acc0.buyer_trade_state)
+        .await
+        .unwrap();
+    assert!(trade_state0.is_none());
+    let buyer0_after = context
+        .banks_client
+        .get_account(buyer0.pubkey())
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    assert!(buyer0_after > buyer0_before + ONE_SOL);
+
+    let buyer2 = Keypair::new();
+    airdrop(&mut context, &buyer2.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (_acc2, buy_tx2) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer2,
+        &sell_acc.wallet,
+        &listing_config_address,
+        3 * ONE_SOL,
+    );
+    let result = context
+        .banks_client
+        .process_transaction(buy_tx2)
+        .await
+        .unwrap_err();
+    assert_error!(result, AUCTION_ENDED);
+}
+
+#[tokio::test]
+async fn outbid_keeps_escrow_success() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+
+    airdrop(&mut context, &test_metadata.token.pubkey(), 1000000000)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    let ((sell_acc, listing_config_address), sell_tx) = sell(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            - 60) as i64,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 60) as i64,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    let buyer0 = Keypair::new();
+    airdrop(&mut context, &buyer0.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (acc0, buy_tx0) = buy_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer0,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+        BuyOptions {
+            keep_escrow_on_outbid: Some(true),
+            ..BuyOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx0)
+        .await
+        .unwrap();
+
+    // Accounts passed for the outbid bid must be those of its bidder.
+    let buyer1 = Keypair::new();
+    airdrop(&mut context, &buyer1.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (_acc1, buy_tx1) = buy_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer1,
+        &sell_acc.wallet,
+        &listing_config_address,
+        2 * ONE_SOL,
+        BuyOptions {
+            outbid_accounts: outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &buyer1.pubkey(),
+                ONE_SOL,
+            ),
+            ..BuyOptions::default()
+        },
+    );
+    let result = context
+        .banks_client
+        .process_transaction(buy_tx1)
+        .await
+        .unwrap_err();
+    assert_error!(result, INVALID_PREVIOUS_BID_ACCOUNTS);
+
+    let (_acc1, buy_tx1) = buy_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer1,
+        &sell_acc.wallet,
+        &listing_config_address,
+        2 * ONE_SOL,
+        BuyOptions {
+            outbid_accounts: outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &buyer0.pubkey(),
+                ONE_SOL,
+            ),
+            ..BuyOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx1)
+        .await
+        .unwrap();
+
+    // The trade state is closed but the bid stays in escrow for the next bid.
+    let trade_state0 = context
+        .banks_client
+        .get_account(acc0.buyer_trade_state)
+        .await
+        .unwrap();
+    assert!(trade_state0.is_none());
+    let escrow0 = context
+        .banks_client
+        .get_account(acc0.escrow_payment_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(escrow0.lamports >= ONE_SOL);
+}
+
+#[tokio::test]
+async fn outbid_without_refund_accounts_success() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+
+    airdrop(&mut context, &test_metadata.token.pubkey(), 1000000000)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    let ((sell_acc, listing_config_address), sell_tx) = sell(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            - 60) as i64,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 60) as i64,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    let buyer0 = Keypair::new();
+    airdrop(&mut context, &buyer0.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (acc0, buy_tx0) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer0,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx0)
+        .await
+        .unwrap();
+
+    // Outbidding doesn't need the outbid bid's accounts.
+    let buyer1 = Keypair::new();
+    airdrop(&mut context, &buyer1.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (_acc1, buy_tx1) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer1,
+        &sell_acc.wallet,
+        &listing_config_address,
+        2 * ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx1)
+        .await
+        .unwrap();
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
+    assert_eq!(config.highest_bidder, buyer1.pubkey());
+
+    // The outbid bid stays open with its funds in escrow for its bidder to cancel and withdraw.
+    let trade_state0 = context
+        .banks_client
+        .get_account(acc0.buyer_trade_state)
+        .await
+        .unwrap();
+    assert!(trade_state0.is_some());
+    let escrow0 = context
+        .banks_client
+        .get_account(acc0.escrow_payment_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(escrow0.lamports >= ONE_SOL);
+}
+
+#[tokio::test]
+async fn multiple_bids_max_extensions_success() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+
+    airdrop(&mut context, &test_metadata.token.pubkey(), 1000000000)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            - 60) as i64,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 60) as i64,
+        None,
+        None,
+        Some(60),
+        Some(60),
+        None,
+        SellOptions {
+            anti_sniping: Some(AntiSnipingConfig {
+                max_extensions: 1,
+                ..AntiSnipingConfig::default()
+            }),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    context.warp_to_slot(400).unwrap();
+
+    let listing0 = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+
+    let config0 = ListingConfig::try_deserialize(&mut listing0.as_ref()).unwrap();
+    let end_time_t0 = config0.end_time;
+
+    let buyer0 = Keypair::new();
+    airdrop(&mut context, &buyer0.pubkey(), 10000000000)
+        .await
+        .unwrap();
+    let (_, deposit_tx0) = deposit(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &buyer0,
+        1000000000,
+    );
+    context
+        .banks_client
+        .process_transaction(deposit_tx0)
+        .await
+        .unwrap();
+
+    let (_acc0, buy_tx0) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer0,
+        &sell_acc.wallet,
+        &listing_config_address,
+        1000000000,
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx0)
+        .await
+        .unwrap();
+
+    let listing1 = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+
+    let config1 = ListingConfig::try_deserialize(&mut listing1.as_ref()).unwrap();
+    assert_eq!(config1.end_time, end_time_t0 + 60);
+
+    context.warp_to_slot(121 * 400).unwrap();
+
+    let buyer1 = Keypair::new();
+    airdrop(&mut context, &buyer1.pubkey(), 100000000000)
+        .await
+        .unwrap();
+    let (_, deposit_tx1) = deposit(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &buyer1,
+        10000000000,
+    );
+    context
+        .banks_client
+        .process_transaction(deposit_tx1)
+        .await
+        .unwrap();
+
+    let (_acc1, buy_tx1) = buy_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer1,
+        &sell_acc.wallet,
+        &listing_config_address,
+        10000000000,
+        BuyOptions {
+            outbid_accounts: outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &buyer0.pubkey(),
+                1000000000,
+            ),
+            ..BuyOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx1)
+        .await
+        .unwrap();
+
+    let listing2 = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+
+    let config2 = ListingConfig::try_deserialize(&mut listing2.as_ref()).unwrap();
+    // The only allowed extension was used by the first bid.
+    assert_eq!(config2.end_time, end_time_t0 + 60);
+    assert_eq!(config2.extension_count, 1);
+    assert_eq!(config2.total_extension, 60);
+}
+
+#[tokio::test]
+async fn buy_allowlist_success() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+    airdrop(&mut context, &test_metadata.token.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    // A two wallet allowlist, each leaf is the proof of the other.
+    let buyer = Keypair::new();
+    let listed = Keypair::new();
+    let buyer_leaf = allowlist_leaf(&buyer.pubkey());
+    let listed_leaf = allowlist_leaf(&listed.pubkey());
+    let root = if buyer_leaf <= listed_leaf {
+        keccak::hashv(&[&[0x01], &buyer_leaf, &listed_leaf]).0
+    } else {
+        keccak::hashv(&[&[0x01], &listed_leaf, &buyer_leaf]).0
+    };
+
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            - 60) as i64,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 60) as i64,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            allowlist_root: Some(root),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    let outsider = Keypair::new();
+    airdrop(&mut context, &outsider.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (_, outsider_tx) = buy_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &outsider,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+        BuyOptions {
+            allowlist_proof: Some(vec![listed_leaf]),
+            ..BuyOptions::default()
+        },
+    );
+    let result = context
+        .banks_client
+        .process_transaction(outsider_tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, NOT_ALLOWLISTED);
+
+    airdrop(&mut context, &buyer.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (acc, buy_tx) = buy_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+        BuyOptions {
+            allowlist_proof: Some(vec![listed_leaf]),
+            ..BuyOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx)
+        .await
+        .unwrap();
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
+    assert_eq!(config.highest_bid.buyer_trade_state, acc.buyer_trade_state);
+}
+
+#[tokio::test]
+async fn buy_collection_gate_failure() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+    airdrop(&mut context, &test_metadata.token.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            - 60) as i64,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 60) as i64,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            collection_gate: Some(Keypair::new().pubkey()),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    // The bidder holds an NFT outside of the gating collection.
+    let membership = Metadata::new();
+    airdrop(&mut context, &membership.token.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    membership
+        .create(
+            &mut context,
+            "Member".to_string(),
+            "MBR".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+    let membership_token =
+        get_associated_token_address(&membership.token.pubkey(), &membership.mint.pubkey());
+
+    let (_, buy_tx) = buy_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &membership.token,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+        BuyOptions {
+            gate_accounts: vec![
+                AccountMeta::new_readonly(membership_token, false),
+                AccountMeta::new_readonly(membership.pubkey, false),
+            ],
+            ..BuyOptions::default()
+        },
+    );
+    let result = context
+        .banks_client
+        .process_transaction(buy_tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, NOT_COLLECTION_MEMBER);
+}
@@ -3,7 +3,11 @@ pub mod common;
 pub mod utils;
 
 use common::*;
-use mpl_auctioneer::pda::*;
+use mpl_auctioneer::{
+    pda::*,
+    sell::config::{ListingConfig, SellerCancelRule},
+};
+use solana_program::instruction::AccountMeta;
 use solana_sdk::signature::Keypair;
 use std::time::SystemTime;
 use utils::setup_functions::*;
@@ -366,7 +370,7 @@ async fn cancel_highest_bid() {
     airdrop(&mut context, &buyer1.pubkey(), 2000000000)
         .await
         .unwrap();
-    let (acc1, buy_tx1) = buy(
+    let (acc1, buy_tx1) = buy_with_options(
         &mut context,
         &ahkey,
         &ah,
@@ -376,6 +380,17 @@ async fn cancel_highest_bid() {
         &sell_acc.wallet,
         &listing_config_address,
         price + 1,
+        BuyOptions {
+            outbid_accounts: outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &buyer0.pubkey(),
+                price,
+            ),
+            ..BuyOptions::default()
+        },
     );
 
     context
@@ -429,41 +444,304 @@ async fn cancel_highest_bid() {
     assert_error!(result1, CANNOT_CANCEL_HIGHEST_BID);
     context.warp_to_slot(500).unwrap();
 
-    // Rerun the cancel on the lower bid to verify it now succeeds.
-    let (auctioneer_authority, aa_bump) = find_auctioneer_authority_seeds(&ahkey);
-    let (auctioneer_pda, _) = find_auctioneer_pda(&ahkey, &auctioneer_authority);
-    let accounts2 = mpl_auctioneer::accounts::AuctioneerCancel {
-        auction_house_program: mpl_auction_house::id(),
-        listing_config: listing_config_address,
-        seller: sell_acc.wallet,
-        auction_house: ahkey,
-        wallet: buyer0.pubkey(),
-        token_account: acc0.token_account,
-        authority: ah.authority,
-        trade_state: acc0.buyer_trade_state,
-        token_program: spl_token::id(),
-        token_mint: test_metadata.mint.pubkey(),
-        auction_house_fee_account: ah.auction_house_fee_account,
-        auctioneer_authority,
-        ah_auctioneer_pda: auctioneer_pda,
-    }
-    .to_account_metas(None);
-    let instruction2 = Instruction {
-        program_id: mpl_auctioneer::id(),
-        data: mpl_auctioneer::instruction::Cancel {
-            auctioneer_authority_bump: aa_bump,
-            buyer_price: price,
-            token_size: 1,
-        }
-        .data(),
-        accounts: accounts2,
-    };
+    // The lower bid was closed when it was outbid, so there is nothing left to cancel.
+    let trade_state0 = context
+        .banks_client
+        .get_account(acc0.buyer_trade_state)
+        .await
+        .unwrap();
+    assert!(trade_state0.is_none());
+}
 
-    let tx2 = Transaction::new_signed_with_payer(
-        &[instruction2],
-        Some(&buyer0.pubkey()),
-        &[&buyer0],
-        context.last_blockhash,
+/// List with `seller_cancel_rule` and place a bid meeting the reserve price.
+async fn sell_with_bid(
+    seller_cancel_rule: SellerCancelRule,
+    allow_high_bid_cancel: bool,
+) -> (
+    ProgramTestContext,
+    Pubkey,
+    AuctionHouse,
+    Metadata,
+    Keypair,
+    Pubkey,
+    Pubkey,
+) {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+    airdrop(&mut context, &test_metadata.token.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Tests".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+    let ((acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            - 60) as i64,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 60) as i64,
+        Some(ONE_SOL),
+        None,
+        None,
+        None,
+        Some(allow_high_bid_cancel),
+        SellOptions {
+            seller_cancel_rule: Some(seller_cancel_rule),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    let buyer = Keypair::new();
+    airdrop(&mut context, &buyer.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (_, buy_tx) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer,
+        &acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx)
+        .await
+        .unwrap();
+
+    (
+        context,
+        ahkey,
+        ah,
+        test_metadata,
+        buyer,
+        listing_config_address,
+        acc.seller_trade_state,
+    )
+}
+
+#[tokio::test]
+async fn cancel_listing_after_reserve_met_failure() {
+    let (mut context, ahkey, ah, test_metadata, _, listing_config_address, seller_trade_state) =
+        sell_with_bid(SellerCancelRule::BeforeReserveMet, false).await;
+
+    let tx = cancel_listing_tx(
+        &context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &listing_config_address,
+        &seller_trade_state,
+        vec![],
     );
-    context.banks_client.process_transaction(tx2).await.unwrap();
+    let result = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, CANNOT_CANCEL_LISTING_WITH_BIDS);
+}
+
+#[tokio::test]
+async fn cancel_listing_with_penalty_success() {
+    let penalty = 100_000_000;
+    let (mut context, ahkey, ah, test_metadata, buyer, listing_config_address, seller_trade_state) =
+        sell_with_bid(SellerCancelRule::WithPenalty(penalty), false).await;
+
+    // The highest bidder's wallet must be passed to receive the penalty.
+    let tx = cancel_listing_tx(
+        &context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &listing_config_address,
+        &seller_trade_state,
+        vec![],
+    );
+    let result = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, INVALID_HIGHEST_BIDDER_ACCOUNT);
+
+    let buyer_balance = context
+        .banks_client
+        .get_balance(buyer.pubkey())
+        .await
+        .unwrap();
+    let tx = cancel_listing_tx(
+        &context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &listing_config_address,
+        &seller_trade_state,
+        vec![AccountMeta::new(buyer.pubkey(), false)],
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let buyer_balance_after = context
+        .banks_client
+        .get_balance(buyer.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(buyer_balance_after, buyer_balance + penalty);
+
+    let listing_config_closed = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap();
+    assert!(listing_config_closed.is_none());
+}
+
+#[tokio::test]
+async fn cancel_listing_after_highest_bid_canceled_success() {
+    let (mut context, ahkey, ah, test_metadata, buyer, listing_config_address, seller_trade_state) =
+        sell_with_bid(SellerCancelRule::BeforeReserveMet, true).await;
+
+    let tx = cancel_bid_tx(
+        &context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &listing_config_address,
+        &buyer,
+        ONE_SOL,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // The canceled bid no longer counts as the highest bid.
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
+    assert_eq!(config.highest_bid.amount, 0);
+    assert_eq!(config.highest_bid.buyer_trade_state, Pubkey::default());
+    assert_eq!(config.highest_bidder, Pubkey::default());
+
+    let tx = cancel_listing_tx(
+        &context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &listing_config_address,
+        &seller_trade_state,
+        vec![],
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let listing_config_closed = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap();
+    assert!(listing_config_closed.is_none());
+}
+
+#[tokio::test]
+async fn cancel_listing_after_highest_bid_withdrawn_success() {
+    let (mut context, ahkey, ah, test_metadata, buyer, listing_config_address, seller_trade_state) =
+        sell_with_bid(SellerCancelRule::BeforeReserveMet, false).await;
+
+    let (_, withdraw_tx) = withdraw(
+        &mut context,
+        &buyer,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        ONE_SOL,
+        ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(withdraw_tx)
+        .await
+        .unwrap();
+
+    // The highest bidder's wallet, trade state and escrow payment account.
+    let bid_accounts = outbid_accounts(
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer.pubkey(),
+        ONE_SOL,
+    );
+    let tx = cancel_listing_tx(
+        &context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &listing_config_address,
+        &seller_trade_state,
+        vec![
+            bid_accounts[0].clone(),
+            bid_accounts[3].clone(),
+            bid_accounts[0].clone(),
+        ],
+    );
+    let result = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, INVALID_HIGHEST_BID_ACCOUNTS);
+
+    let tx = cancel_listing_tx(
+        &context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &listing_config_address,
+        &seller_trade_state,
+        vec![
+            bid_accounts[0].clone(),
+            bid_accounts[3].clone(),
+            bid_accounts[2].clone(),
+        ],
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let listing_config_closed = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap();
+    assert!(listing_config_closed.is_none());
 }
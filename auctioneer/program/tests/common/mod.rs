@@ -22,6 +22,7 @@ pub use std::assert_eq;
 
 pub const HAS_ONE_CONSTRAINT_VIOLATION: u32 = 2001;
 pub const INVALID_SEEDS: u32 = 2006;
+pub const ACCOUNT_NOT_INITIALIZED: u32 = 3012;
 
 pub const MISSING_AUCTIONEER_SCOPE: u32 = 6029;
 pub const NO_AUCTIONEER_PROGRAM_SET: u32 = 6031;
@@ -38,3 +39,18 @@ pub const NOT_HIGH_BIDDER: u32 = 6006;
 pub const BELOW_RESERVE_PRICE: u32 = 6007;
 pub const BELOW_BID_INCREMENT: u32 = 6008;
 pub const CANNOT_CANCEL_HIGHEST_BID: u32 = 6009;
+pub const INVALID_DUTCH_AUCTION_CONFIG: u32 = 6010;
+pub const BELOW_DUTCH_AUCTION_PRICE: u32 = 6011;
+pub const INVALID_SEALED_BID_REVEAL: u32 = 6015;
+pub const REVEAL_PERIOD_ACTIVE: u32 = 6018;
+pub const INVALID_BUY_NOW_PRICE: u32 = 6020;
+pub const INVALID_PREVIOUS_BID_ACCOUNTS: u32 = 6021;
+pub const CANNOT_CANCEL_LISTING_WITH_BIDS: u32 = 6027;
+pub const INVALID_HIGHEST_BIDDER_ACCOUNT: u32 = 6028;
+pub const NOT_ALLOWLISTED: u32 = 6029;
+pub const NOT_COLLECTION_MEMBER: u32 = 6030;
+pub const INVALID_AUCTION_TIMES: u32 = 6031;
+pub const LISTING_CONFIG_LOCKED: u32 = 6032;
+pub const ESCROW_LOCKED: u32 = 6034;
+pub const SEALED_BID_STILL_OPEN: u32 = 6035;
+pub const INVALID_HIGHEST_BID_ACCOUNTS: u32 = 6036;
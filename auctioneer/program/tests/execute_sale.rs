@@ -24,7 +24,13 @@ use mpl_auction_house::pda::{
     find_auctioneer_pda, find_escrow_payment_address, find_program_as_signer_address,
     find_trade_state_address,
 };
-use mpl_auctioneer::pda::find_auctioneer_authority_seeds;
+use mpl_auctioneer::{
+    pda::find_auctioneer_authority_seeds,
+    sell::config::{
+        AuctionType, EditionConfig, EditionPricing, ListingConfig, BID_BOOK_ENTRY_SIZE,
+        LISTING_CONFIG_SIZE,
+    },
+};
 use mpl_token_metadata::state::Creator;
 use solana_sdk::{pubkey::Pubkey, signature::Keypair, transaction::Transaction};
 use spl_associated_token_account::get_associated_token_address;
@@ -467,7 +473,7 @@ async fn execute_sale_two_bids_success() {
     airdrop(&mut context, &buyer1.pubkey(), 10_000_000_000)
         .await
         .unwrap();
-    let (bid1_acc, buy1_tx) = buy(
+    let (bid1_acc, buy1_tx) = buy_with_options(
         &mut context,
         &ahkey,
         &ah,
@@ -477,6 +483,17 @@ async fn execute_sale_two_bids_success() {
         &sell_acc.wallet,
         &listing_config_address,
         100_000_001,
+        BuyOptions {
+            outbid_accounts: outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &buyer0.pubkey(),
+                100_000_000,
+            ),
+            ..BuyOptions::default()
+        },
     );
     context
         .banks_client
@@ -693,7 +710,7 @@ async fn execute_sale_two_bids_failure() {
     airdrop(&mut context, &buyer1.pubkey(), 10_000_000_000)
         .await
         .unwrap();
-    let (_bid1_acc, buy1_tx) = buy(
+    let (_bid1_acc, buy1_tx) = buy_with_options(
         &mut context,
         &ahkey,
         &ah,
@@ -703,6 +720,17 @@ async fn execute_sale_two_bids_failure() {
         &sell_acc.wallet,
         &listing_config_address,
         100_000_001,
+        BuyOptions {
+            outbid_accounts: outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &buyer0.pubkey(),
+                100_000_000,
+            ),
+            ..BuyOptions::default()
+        },
     );
     context
         .banks_client
@@ -1066,3 +1094,381 @@ async fn execute_sale_with_creators(metadata_creators: Vec<(Pubkey, u8)>) {
 
     assert!(listing_config_closed.is_none());
 }
+
+#[tokio::test]
+async fn execute_sale_edition_uniform_price_success() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, authority) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+    airdrop(&mut context, &test_metadata.token.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            2,
+        )
+        .await
+        .unwrap();
+    let end_time = (SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+        + 60) as i64;
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            - 60) as i64,
+        end_time,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            auction_type: Some(AuctionType::Edition(EditionConfig {
+                winners: 2,
+                pricing: EditionPricing::UniformPrice,
+            })),
+            token_size: Some(2),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    // The third bid pushes the lowest out of the book and sets the clearing price.
+    let bids = [
+        (Keypair::new(), ONE_SOL),
+        (Keypair::new(), 2 * ONE_SOL),
+        (Keypair::new(), 3 * ONE_SOL),
+    ];
+    let mut trade_states = vec![];
+    for (i, (buyer, price)) in bids.iter().enumerate() {
+        airdrop(&mut context, &buyer.pubkey(), TEN_SOL)
+            .await
+            .unwrap();
+        let outbid_accounts = if i == 2 {
+            outbid_accounts(
+                &ahkey,
+                &ah,
+                &test_metadata,
+                &test_metadata.token.pubkey(),
+                &bids[0].0.pubkey(),
+                bids[0].1,
+            )
+        } else {
+            vec![]
+        };
+        let (acc, buy_tx) = buy_with_options(
+            &mut context,
+            &ahkey,
+            &ah,
+            &test_metadata,
+            &test_metadata.token.pubkey(),
+            buyer,
+            &sell_acc.wallet,
+            &listing_config_address,
+            *price,
+            BuyOptions {
+                listing_token_size: Some(2),
+                outbid_accounts,
+                ..BuyOptions::default()
+            },
+        );
+        context
+            .banks_client
+            .process_transaction(buy_tx)
+            .await
+            .unwrap();
+        trade_states.push(acc.buyer_trade_state);
+    }
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    // The bid book is allocated for the two winners only.
+    assert_eq!(listing.len(), LISTING_CONFIG_SIZE + 2 * BID_BOOK_ENTRY_SIZE);
+    let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
+    assert_eq!(config.bid_book.len(), 2);
+    assert_eq!(config.bid_book[0].amount, 3 * ONE_SOL);
+    assert_eq!(config.bid_book[1].amount, 2 * ONE_SOL);
+    assert_eq!(config.clearing_bid, ONE_SOL);
+    let outbid_trade_state = context
+        .banks_client
+        .get_account(trade_states[0])
+        .await
+        .unwrap();
+    assert!(outbid_trade_state.is_none());
+
+    set_clock_timestamp(&mut context, end_time + 1).await;
+    airdrop(&mut context, &ah.auction_house_fee_account, 10_000_000_000)
+        .await
+        .unwrap();
+
+    // Each winner moves their bid to the clearing price and is settled for a single unit.
+    for (i, (buyer, price)) in bids.iter().enumerate().skip(1) {
+        let (acc, claim_tx) = buy_with_options(
+            &mut context,
+            &ahkey,
+            &ah,
+            &test_metadata,
+            &test_metadata.token.pubkey(),
+            buyer,
+            &sell_acc.wallet,
+            &listing_config_address,
+            ONE_SOL,
+            BuyOptions {
+                listing_token_size: Some(2),
+                outbid_accounts: outbid_accounts(
+                    &ahkey,
+                    &ah,
+                    &test_metadata,
+                    &test_metadata.token.pubkey(),
+                    &buyer.pubkey(),
+                    *price,
+                ),
+                ..BuyOptions::default()
+            },
+        );
+        context
+            .banks_client
+            .process_transaction(claim_tx)
+            .await
+            .unwrap();
+        let replaced_trade_state = context
+            .banks_client
+            .get_account(trade_states[i])
+            .await
+            .unwrap();
+        assert!(replaced_trade_state.is_none());
+
+        let (_, execute_sale_tx) = execute_sale(
+            &mut context,
+            &listing_config_address,
+            &ahkey,
+            &ah,
+            &authority,
+            &test_metadata,
+            &buyer.pubkey(),
+            &test_metadata.token.pubkey(),
+            &sell_acc.token_account,
+            &sell_acc.seller_trade_state,
+            &acc.buyer_trade_state,
+            2,
+            ONE_SOL,
+        );
+        context
+            .banks_client
+            .process_transaction(execute_sale_tx)
+            .await
+            .unwrap();
+
+        let buyer_token_account =
+            get_associated_token_address(&buyer.pubkey(), &test_metadata.mint.pubkey());
+        let buyer_token = Account::unpack_from_slice(
+            context
+                .banks_client
+                .get_account(buyer_token_account)
+                .await
+                .unwrap()
+                .unwrap()
+                .data
+                .as_slice(),
+        )
+        .unwrap();
+        assert_eq!(buyer_token.amount, 1);
+    }
+
+    let listing_config_closed = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap();
+    assert!(listing_config_closed.is_none());
+}
+
+#[tokio::test]
+async fn execute_sale_keeper_reward_success() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+    airdrop(&mut context, &test_metadata.token.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+    let end_time = (SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+        + 60) as i64;
+    let keeper_reward = 5_000_000;
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            - 60) as i64,
+        end_time,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            keeper_reward: Some(keeper_reward),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        listing.lamports,
+        rent.minimum_balance(listing.data.len()) + keeper_reward
+    );
+
+    let buyer = Keypair::new();
+    airdrop(&mut context, &buyer.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let (bid_acc, buy_tx) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx)
+        .await
+        .unwrap();
+
+    set_clock_timestamp(&mut context, end_time + 1).await;
+    let fee_account_balance = context
+        .banks_client
+        .get_balance(ah.auction_house_fee_account)
+        .await
+        .unwrap();
+
+    // Neither the buyer, the seller nor the Auction House authority sign the settlement.
+    let keeper = Keypair::new();
+    airdrop(&mut context, &keeper.pubkey(), ONE_SOL)
+        .await
+        .unwrap();
+    let (_, execute_sale_tx) = keeper_execute_sale(
+        &mut context,
+        &listing_config_address,
+        &ahkey,
+        &ah,
+        &keeper,
+        &test_metadata,
+        &buyer.pubkey(),
+        &test_metadata.token.pubkey(),
+        &sell_acc.token_account,
+        &sell_acc.seller_trade_state,
+        &bid_acc.buyer_trade_state,
+        1,
+        ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(execute_sale_tx)
+        .await
+        .unwrap();
+
+    let buyer_token_account =
+        get_associated_token_address(&buyer.pubkey(), &test_metadata.mint.pubkey());
+    let buyer_token = Account::unpack_from_slice(
+        context
+            .banks_client
+            .get_account(buyer_token_account)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(buyer_token.amount, 1);
+
+    // The keeper pays the rent and transaction fee out of the reward, the Auction House fee account
+    // pays nothing.
+    let keeper_balance = context
+        .banks_client
+        .get_balance(keeper.pubkey())
+        .await
+        .unwrap();
+    assert!(keeper_balance > ONE_SOL);
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(ah.auction_house_fee_account)
+            .await
+            .unwrap(),
+        fee_account_balance
+    );
+
+    let listing_config_closed = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap();
+    assert!(listing_config_closed.is_none());
+}
@@ -0,0 +1,161 @@
+#![cfg(feature = "test-bpf")]
+pub mod common;
+pub mod utils;
+
+use anchor_lang::{AnchorSerialize, Discriminator};
+use common::*;
+use mpl_auctioneer::sell::config::{
+    AuctionType, Bid, ListingConfig, ListingConfigV0, ListingConfigVersion, SellerCancelRule,
+    LISTING_CONFIG_SIZE, LISTING_CONFIG_V0_SIZE,
+};
+use utils::setup_functions::*;
+
+use solana_sdk::account::{Account as SolanaAccount, AccountSharedData};
+use std::{assert_eq, time::SystemTime};
+
+#[tokio::test]
+async fn migrate_v0_listing_config_success() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+    airdrop(&mut context, &test_metadata.token.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+    let start_time = (SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+        - 60) as i64;
+    let end_time = start_time + 120;
+    let ((sell_acc, listing_config_address), sell_tx) = sell(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        start_time,
+        end_time,
+        Some(ONE_SOL),
+        None,
+        None,
+        None,
+        Some(true),
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    // Replace the listing with one created before auction types were added.
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap();
+    let config = ListingConfig::try_deserialize(&mut listing.data.as_ref()).unwrap();
+    let v0 = ListingConfigV0 {
+        version: ListingConfigVersion::V0,
+        start_time,
+        end_time,
+        highest_bid: Bid {
+            version: ListingConfigVersion::V0,
+            amount: 0,
+            buyer_trade_state: Pubkey::default(),
+        },
+        bump: config.bump,
+        reserve_price: ONE_SOL,
+        min_bid_increment: 0,
+        time_ext_period: 0,
+        time_ext_delta: 0,
+        allow_high_bid_cancel: true,
+    };
+    let mut data = ListingConfig::discriminator().to_vec();
+    data.extend(v0.try_to_vec().unwrap());
+    assert_eq!(data.len(), LISTING_CONFIG_V0_SIZE);
+    let rent = context.banks_client.get_rent().await.unwrap();
+    context.set_account(
+        &listing_config_address,
+        &AccountSharedData::from(SolanaAccount {
+            lamports: rent.minimum_balance(data.len()),
+            data: data.clone(),
+            owner: mpl_auctioneer::id(),
+            executable: false,
+            rent_epoch: 0,
+        }),
+    );
+
+    // The V0 layout only loads through the versioned load.
+    assert!(ListingConfig::try_deserialize(&mut data.as_ref()).is_err());
+    let loaded = ListingConfig::try_deserialize_versioned(&data).unwrap();
+    assert_eq!(loaded.version, ListingConfigVersion::V1);
+    assert_eq!(loaded.end_time, end_time);
+    assert_eq!(loaded.reserve_price, ONE_SOL);
+    assert!(loaded.allow_high_bid_cancel);
+    assert_eq!(loaded.auction_type, AuctionType::English);
+    assert_eq!(loaded.seller_cancel_rule, SellerCancelRule::Anytime);
+
+    let payer = Keypair::new();
+    airdrop(&mut context, &payer.pubkey(), ONE_SOL)
+        .await
+        .unwrap();
+    let migrate_tx = migrate_listing_config(&mut context, &listing_config_address, &payer);
+    context
+        .banks_client
+        .process_transaction(migrate_tx)
+        .await
+        .unwrap();
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(listing.data.len(), LISTING_CONFIG_SIZE);
+    assert!(listing.lamports >= rent.minimum_balance(LISTING_CONFIG_SIZE));
+    let config = ListingConfig::try_deserialize(&mut listing.data.as_ref()).unwrap();
+    assert_eq!(config.version, ListingConfigVersion::V1);
+    assert_eq!(config.start_time, start_time);
+    assert_eq!(config.end_time, end_time);
+    assert_eq!(config.reserve_price, ONE_SOL);
+    assert_eq!(config.auction_type, AuctionType::English);
+
+    // The migrated listing takes bids again.
+    let buyer = Keypair::new();
+    airdrop(&mut context, &buyer.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (_, buy_tx) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,656 @@
+#![cfg(feature = "test-bpf")]
+pub mod common;
+pub mod utils;
+
+use common::*;
+use mpl_auctioneer::{
+    events::{SealedBidCommittedEvent, SealedBidRevealedEvent},
+    pda::find_sealed_bid_address,
+    sell::config::{
+        AuctionType, ListingConfig, SealedBidConfig, SealedBidPricing, SellerCancelRule,
+    },
+    utils::allowlist_leaf,
+};
+use std::{assert_eq, time::SystemTime};
+use utils::setup_functions::*;
+
+#[tokio::test]
+async fn sealed_bid_second_price_success() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+
+    airdrop(&mut context, &test_metadata.token.pubkey(), ONE_SOL)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64;
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        now - 60,
+        now + 60,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            auction_type: Some(AuctionType::SealedBid(SealedBidConfig {
+                reveal_period: 600,
+                pricing: SealedBidPricing::SecondPrice,
+                claim_period: 600,
+            })),
+            record_bid_history: Some(true),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    let buyer0 = Keypair::new();
+    let salt0 = [7; 32];
+    airdrop(&mut context, &buyer0.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (_, commit_tx0) = commit_bid(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &buyer0,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+        salt0,
+        2 * ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(commit_tx0)
+        .await
+        .unwrap();
+
+    let buyer1 = Keypair::new();
+    let salt1 = [9; 32];
+    airdrop(&mut context, &buyer1.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (_, commit_tx1) = commit_bid(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &buyer1,
+        &sell_acc.wallet,
+        &listing_config_address,
+        3 * ONE_SOL,
+        salt1,
+        3 * ONE_SOL,
+    );
+    let logs = process_transaction_with_logs(&mut context, commit_tx1).await;
+    let events = parse_events::<SealedBidCommittedEvent>(&logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].listing_config, listing_config_address);
+    assert_eq!(events[0].bidder, buyer1.pubkey());
+    assert_eq!(events[0].deposit, 3 * ONE_SOL);
+
+    set_clock_timestamp(&mut context, now + 61).await;
+
+    // The commitment does not match a different salt.
+    let (_, bad_reveal_tx) = reveal_bid(
+        &mut context,
+        &ahkey,
+        &test_metadata,
+        &buyer0,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+        salt1,
+    );
+    let result = context
+        .banks_client
+        .process_transaction(bad_reveal_tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, INVALID_SEALED_BID_REVEAL);
+
+    for (buyer, price, salt) in [(&buyer0, ONE_SOL, salt0), (&buyer1, 3 * ONE_SOL, salt1)] {
+        let (_, reveal_tx) = reveal_bid(
+            &mut context,
+            &ahkey,
+            &test_metadata,
+            buyer,
+            &sell_acc.wallet,
+            &listing_config_address,
+            price,
+            salt,
+        );
+        let logs = process_transaction_with_logs(&mut context, reveal_tx).await;
+        let events = parse_events::<SealedBidRevealedEvent>(&logs);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].listing_config, listing_config_address);
+        assert_eq!(events[0].bidder, buyer.pubkey());
+        assert_eq!(events[0].price, price);
+    }
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
+    assert_eq!(config.highest_bidder, buyer1.pubkey());
+    assert_eq!(config.highest_bid.amount, 3 * ONE_SOL);
+    assert_eq!(config.second_highest_bid, ONE_SOL);
+    // Reveals are recorded in the bid history.
+    assert_eq!(config.bid_count, 2);
+    assert_eq!(config.bid_history[0].bidder, buyer0.pubkey());
+    assert_eq!(config.bid_history[0].amount, ONE_SOL);
+    assert_eq!(config.bid_history[1].bidder, buyer1.pubkey());
+    assert_eq!(config.bid_history[1].amount, 3 * ONE_SOL);
+
+    // The winner can only claim the item once every bid has had the chance to be revealed.
+    let (_, early_buy_tx) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer1,
+        &sell_acc.wallet,
+        &listing_config_address,
+        3 * ONE_SOL,
+    );
+    let result = context
+        .banks_client
+        .process_transaction(early_buy_tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, REVEAL_PERIOD_ACTIVE);
+
+    set_clock_timestamp(&mut context, now + 661).await;
+
+    let (buy_acc, buy_tx) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer1,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx)
+        .await
+        .unwrap();
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
+    assert_eq!(config.highest_bid.amount, ONE_SOL);
+    assert_eq!(
+        config.highest_bid.buyer_trade_state,
+        buy_acc.buyer_trade_state
+    );
+    // So is the claim, at the clearing price.
+    assert_eq!(config.bid_count, 3);
+    assert_eq!(config.bid_history[2].bidder, buyer1.pubkey());
+    assert_eq!(config.bid_history[2].amount, ONE_SOL);
+}
+
+#[tokio::test]
+async fn sealed_bid_unclaimed_win_passes_to_second_bidder() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+
+    airdrop(&mut context, &test_metadata.token.pubkey(), ONE_SOL)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64;
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        now - 60,
+        now + 60,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            auction_type: Some(AuctionType::SealedBid(SealedBidConfig {
+                reveal_period: 600,
+                pricing: SealedBidPricing::FirstPrice,
+                claim_period: 600,
+            })),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    let buyer0 = Keypair::new();
+    let buyer1 = Keypair::new();
+    let bids = [(&buyer0, ONE_SOL, [7; 32]), (&buyer1, 3 * ONE_SOL, [9; 32])];
+    for (buyer, price, salt) in bids {
+        airdrop(&mut context, &buyer.pubkey(), TEN_SOL)
+            .await
+            .unwrap();
+        let (_, commit_tx) = commit_bid(
+            &mut context,
+            &ahkey,
+            &ah,
+            &test_metadata,
+            buyer,
+            &sell_acc.wallet,
+            &listing_config_address,
+            price,
+            salt,
+            price,
+        );
+        context
+            .banks_client
+            .process_transaction(commit_tx)
+            .await
+            .unwrap();
+    }
+
+    set_clock_timestamp(&mut context, now + 61).await;
+
+    for (buyer, price, salt) in bids {
+        let (_, reveal_tx) = reveal_bid(
+            &mut context,
+            &ahkey,
+            &test_metadata,
+            buyer,
+            &sell_acc.wallet,
+            &listing_config_address,
+            price,
+            salt,
+        );
+        context
+            .banks_client
+            .process_transaction(reveal_tx)
+            .await
+            .unwrap();
+    }
+
+    // The highest bidder did not claim the item before the end of the claim period.
+    set_clock_timestamp(&mut context, now + 1261).await;
+
+    let (_, late_buy_tx) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer1,
+        &sell_acc.wallet,
+        &listing_config_address,
+        3 * ONE_SOL,
+    );
+    let result = context
+        .banks_client
+        .process_transaction(late_buy_tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, NOT_HIGH_BIDDER);
+
+    let (buy_acc, buy_tx) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer0,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx)
+        .await
+        .unwrap();
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
+    assert_eq!(config.highest_bidder, buyer0.pubkey());
+    assert_eq!(config.highest_bid.amount, ONE_SOL);
+    assert_eq!(
+        config.highest_bid.buyer_trade_state,
+        buy_acc.buyer_trade_state
+    );
+}
+
+#[tokio::test]
+async fn sealed_bid_allowlist_commit_failure() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+
+    airdrop(&mut context, &test_metadata.token.pubkey(), ONE_SOL)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    // Only another wallet is on the allowlist.
+    let listed = Keypair::new();
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64;
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        now - 60,
+        now + 60,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            auction_type: Some(AuctionType::SealedBid(SealedBidConfig {
+                reveal_period: 600,
+                pricing: SealedBidPricing::FirstPrice,
+                claim_period: 600,
+            })),
+            allowlist_root: Some(allowlist_leaf(&listed.pubkey())),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    // Sealed bids are gated when committed, not only when claimed.
+    let buyer = Keypair::new();
+    airdrop(&mut context, &buyer.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (_, commit_tx) = commit_bid(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &buyer,
+        &sell_acc.wallet,
+        &listing_config_address,
+        ONE_SOL,
+        [7; 32],
+        ONE_SOL,
+    );
+    let result = context
+        .banks_client
+        .process_transaction(commit_tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, NOT_ALLOWLISTED);
+}
+
+#[tokio::test]
+async fn sealed_bid_deposit_locked_until_released() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+
+    airdrop(&mut context, &test_metadata.token.pubkey(), ONE_SOL)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64;
+    let ((sell_acc, listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        now - 60,
+        now + 60,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            auction_type: Some(AuctionType::SealedBid(SealedBidConfig {
+                reveal_period: 600,
+                pricing: SealedBidPricing::FirstPrice,
+                claim_period: 600,
+            })),
+            seller_cancel_rule: Some(SellerCancelRule::BeforeReserveMet),
+            ..SellOptions::default()
+        },
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    let buyer0 = Keypair::new();
+    let buyer1 = Keypair::new();
+    let bids = [
+        (&buyer0, ONE_SOL, [7; 32], 2 * ONE_SOL),
+        (&buyer1, 3 * ONE_SOL, [9; 32], 3 * ONE_SOL),
+    ];
+    for (buyer, price, salt, deposit) in bids {
+        airdrop(&mut context, &buyer.pubkey(), TEN_SOL)
+            .await
+            .unwrap();
+        let (_, commit_tx) = commit_bid(
+            &mut context,
+            &ahkey,
+            &ah,
+            &test_metadata,
+            buyer,
+            &sell_acc.wallet,
+            &listing_config_address,
+            price,
+            salt,
+            deposit,
+        );
+        context
+            .banks_client
+            .process_transaction(commit_tx)
+            .await
+            .unwrap();
+    }
+
+    // The deposit backing the sealed bid can't be withdrawn.
+    let (_, withdraw_tx) = withdraw(
+        &mut context,
+        &buyer0,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        ONE_SOL,
+        ONE_SOL,
+    );
+    let result = context
+        .banks_client
+        .process_transaction(withdraw_tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, ESCROW_LOCKED);
+
+    set_clock_timestamp(&mut context, now + 61).await;
+
+    for (buyer, price, salt, _) in bids {
+        let (_, reveal_tx) = reveal_bid(
+            &mut context,
+            &ahkey,
+            &test_metadata,
+            buyer,
+            &sell_acc.wallet,
+            &listing_config_address,
+            price,
+            salt,
+        );
+        context
+            .banks_client
+            .process_transaction(reveal_tx)
+            .await
+            .unwrap();
+    }
+
+    // The second highest bidder can still win if the highest bidder doesn't claim the item.
+    set_clock_timestamp(&mut context, now + 661).await;
+    let (_, close_tx) = close_sealed_bid(&mut context, &ahkey, &buyer0, &listing_config_address);
+    let result = context
+        .banks_client
+        .process_transaction(close_tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, SEALED_BID_STILL_OPEN);
+
+    // Neither bidder claimed the item in time, so the seller can cancel and the deposits are released.
+    set_clock_timestamp(&mut context, now + 1861).await;
+    let cancel_tx = cancel_listing_tx(
+        &context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &listing_config_address,
+        &sell_acc.seller_trade_state,
+        vec![],
+    );
+    context
+        .banks_client
+        .process_transaction(cancel_tx)
+        .await
+        .unwrap();
+
+    let (_, close_tx) = close_sealed_bid(&mut context, &ahkey, &buyer0, &listing_config_address);
+    context
+        .banks_client
+        .process_transaction(close_tx)
+        .await
+        .unwrap();
+    let sealed_bid = context
+        .banks_client
+        .get_account(find_sealed_bid_address(&listing_config_address, &buyer0.pubkey()).0)
+        .await
+        .unwrap();
+    assert!(sealed_bid.is_none());
+
+    let (_, withdraw_tx) = withdraw(
+        &mut context,
+        &buyer0,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        ONE_SOL,
+        2 * ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(withdraw_tx)
+        .await
+        .unwrap();
+}
@@ -3,6 +3,9 @@ pub mod common;
 pub mod utils;
 
 use common::*;
+use mpl_auctioneer::sell::config::{
+    AuctionType, DutchAuctionConfig, DutchAuctionCurve, LISTING_CONFIG_SIZE,
+};
 use utils::setup_functions::*;
 
 use mpl_testing_utils::{solana::airdrop, utils::Metadata};
@@ -32,7 +35,7 @@ async fn sell_success() {
         )
         .await
         .unwrap();
-    let ((acc, _listing_config_address), sell_tx) = sell(
+    let ((acc, listing_config_address), sell_tx) = sell(
         &mut context,
         &ahkey,
         &ah,
@@ -65,4 +68,133 @@ async fn sell_success() {
         .expect("Error Getting Trade State")
         .expect("Trade State Empty");
     assert_eq!(sts.data.len(), 1);
+
+    // Only edition auctions allocate room in the bid book.
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(listing.data.len(), LISTING_CONFIG_SIZE);
+}
+
+#[tokio::test]
+async fn sell_dutch_auction_invalid_config() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+    let owner_pubkey = &test_metadata.token.pubkey();
+    airdrop(&mut context, owner_pubkey, TEN_SOL).await.unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+    // The price of a Dutch auction must descend.
+    let ((_acc, _listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()) as i64,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 60) as i64,
+        None,
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            auction_type: Some(AuctionType::Dutch(DutchAuctionConfig {
+                start_price: ONE_SOL,
+                end_price: TEN_SOL,
+                curve: DutchAuctionCurve::Stepped,
+                interval: 10,
+                decay_bps: 0,
+            })),
+            ..SellOptions::default()
+        },
+    );
+
+    let result = context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, INVALID_DUTCH_AUCTION_CONFIG);
+}
+
+#[tokio::test]
+async fn sell_buy_now_below_reserve_failure() {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+    let owner_pubkey = &test_metadata.token.pubkey();
+    airdrop(&mut context, owner_pubkey, TEN_SOL).await.unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+    // Buying now must not undercut the reserve price.
+    let ((_acc, _listing_config_address), sell_tx) = sell_with_options(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()) as i64,
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            + 60) as i64,
+        Some(2 * ONE_SOL),
+        None,
+        None,
+        None,
+        None,
+        SellOptions {
+            buy_now_price: Some(ONE_SOL),
+            ..SellOptions::default()
+        },
+    );
+
+    let result = context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, INVALID_BUY_NOW_PRICE);
 }
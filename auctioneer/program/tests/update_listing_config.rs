@@ -0,0 +1,175 @@
+#![cfg(feature = "test-bpf")]
+pub mod common;
+pub mod utils;
+
+use common::*;
+use mpl_auctioneer::{events::ListingConfigUpdatedEvent, sell::config::ListingConfig};
+use std::{assert_eq, time::SystemTime};
+use utils::setup_functions::*;
+
+/// List an English auction running from a minute ago to a minute from now.
+async fn setup_listing() -> (
+    ProgramTestContext,
+    AuctionHouse,
+    Pubkey,
+    Metadata,
+    Pubkey,
+    i64,
+) {
+    let mut context = auctioneer_program_test().start_with_context().await;
+    // Payer Wallet
+    let (ah, ahkey, _) = existing_auction_house_test_context(&mut context)
+        .await
+        .unwrap();
+    let test_metadata = Metadata::new();
+    airdrop(&mut context, &test_metadata.token.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    test_metadata
+        .create(
+            &mut context,
+            "Test".to_string(),
+            "TST".to_string(),
+            "uri".to_string(),
+            None,
+            10,
+            false,
+            1,
+        )
+        .await
+        .unwrap();
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64;
+    let ((_, listing_config_address), sell_tx) = sell(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        now - 60,
+        now + 60,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    context
+        .banks_client
+        .process_transaction(sell_tx)
+        .await
+        .unwrap();
+
+    (
+        context,
+        ah,
+        ahkey,
+        test_metadata,
+        listing_config_address,
+        now,
+    )
+}
+
+#[tokio::test]
+async fn update_listing_config_success() {
+    let (mut context, _, ahkey, test_metadata, listing_config_address, now) = setup_listing().await;
+
+    let tx = update_listing_config(
+        &mut context,
+        &ahkey,
+        &test_metadata,
+        &listing_config_address,
+        None,
+        Some(now + 3600),
+        Some(ONE_SOL),
+        Some(100_000_000),
+    );
+    let logs = process_transaction_with_logs(&mut context, tx).await;
+    let events = parse_events::<ListingConfigUpdatedEvent>(&logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].listing_config, listing_config_address);
+    assert_eq!(events[0].start_time, now - 60);
+    assert_eq!(events[0].end_time, now + 3600);
+    assert_eq!(events[0].reserve_price, ONE_SOL);
+    assert_eq!(events[0].min_bid_increment, 100_000_000);
+
+    let listing = context
+        .banks_client
+        .get_account(listing_config_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let config = ListingConfig::try_deserialize(&mut listing.as_ref()).unwrap();
+    assert_eq!(config.start_time, now - 60);
+    assert_eq!(config.end_time, now + 3600);
+    assert_eq!(config.reserve_price, ONE_SOL);
+    assert_eq!(config.min_bid_increment, 100_000_000);
+}
+
+#[tokio::test]
+async fn update_listing_config_invalid_times_failure() {
+    let (mut context, _, ahkey, test_metadata, listing_config_address, now) = setup_listing().await;
+
+    let tx = update_listing_config(
+        &mut context,
+        &ahkey,
+        &test_metadata,
+        &listing_config_address,
+        None,
+        Some(now - 120),
+        None,
+        None,
+    );
+    let result = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, INVALID_AUCTION_TIMES);
+}
+
+#[tokio::test]
+async fn update_listing_config_after_bid_failure() {
+    let (mut context, ah, ahkey, test_metadata, listing_config_address, now) =
+        setup_listing().await;
+
+    let buyer = Keypair::new();
+    airdrop(&mut context, &buyer.pubkey(), TEN_SOL)
+        .await
+        .unwrap();
+    let (_, buy_tx) = buy(
+        &mut context,
+        &ahkey,
+        &ah,
+        &test_metadata,
+        &test_metadata.token.pubkey(),
+        &buyer,
+        &test_metadata.token.pubkey(),
+        &listing_config_address,
+        ONE_SOL,
+    );
+    context
+        .banks_client
+        .process_transaction(buy_tx)
+        .await
+        .unwrap();
+
+    let tx = update_listing_config(
+        &mut context,
+        &ahkey,
+        &test_metadata,
+        &listing_config_address,
+        None,
+        Some(now + 3600),
+        None,
+        None,
+    );
+    let result = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err();
+    assert_error!(result, LISTING_CONFIG_LOCKED);
+}
@@ -15,17 +15,56 @@ use mpl_auction_house::{
     },
     AuctionHouse,
 };
-use mpl_auctioneer::pda::*;
+use mpl_auctioneer::{
+    pda::*,
+    sell::config::{AntiSnipingConfig, AuctionType, SellerCancelRule},
+    utils::sealed_bid_commitment,
+};
 use mpl_testing_utils::{solana::airdrop, utils::Metadata};
 use std::result::Result as StdResult;
 
 use mpl_token_metadata::pda::find_metadata_account;
 use solana_program_test::*;
-use solana_sdk::{clock::UnixTimestamp, instruction::Instruction, transaction::Transaction};
+use solana_sdk::{
+    clock::{Clock, UnixTimestamp},
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    transaction::Transaction,
+};
 use spl_associated_token_account::get_associated_token_address;
 
 use crate::utils::helpers::default_scopes;
 
+/// Listing settings beyond the original English auction arguments of `sell`.
+#[derive(Default)]
+pub struct SellOptions {
+    pub auction_type: Option<AuctionType>,
+    pub buy_now_price: Option<u64>,
+    pub anti_sniping: Option<AntiSnipingConfig>,
+    pub keeper_reward: Option<u64>,
+    pub seller_cancel_rule: Option<SellerCancelRule>,
+    pub collection_gate: Option<Pubkey>,
+    pub allowlist_root: Option<[u8; 32]>,
+    pub record_bid_history: Option<bool>,
+    /// Number of units listed, defaults to 1.
+    pub token_size: Option<u64>,
+}
+
+/// Bid settings beyond the arguments of `buy`.
+#[derive(Default)]
+pub struct BuyOptions {
+    pub keep_escrow_on_outbid: Option<bool>,
+    /// Token size of the listing, defaults to 1.
+    pub listing_token_size: Option<u64>,
+    /// Accounts of the highest bid being outbid, see `outbid_accounts`.
+    pub outbid_accounts: Vec<AccountMeta>,
+    pub allowlist_proof: Option<Vec<[u8; 32]>>,
+    /// Token account and metadata of the gating collection NFT held by the bidder.
+    pub gate_accounts: Vec<AccountMeta>,
+    /// Accounts settling a winning Dutch auction bid, see `dutch_sale_accounts`.
+    pub sale_accounts: Vec<AccountMeta>,
+}
+
 pub fn auctioneer_program_test() -> ProgramTest {
     let mut program = ProgramTest::new("mpl_auctioneer", mpl_auctioneer::id(), None);
     program.add_program("mpl_auction_house", mpl_auction_house::id(), None);
@@ -33,6 +72,37 @@ pub fn auctioneer_program_test() -> ProgramTest {
     program
 }
 
+pub async fn set_clock_timestamp(context: &mut ProgramTestContext, unix_timestamp: UnixTimestamp) {
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+/// Processes `tx` and returns its log messages.
+pub async fn process_transaction_with_logs(
+    context: &mut ProgramTestContext,
+    tx: Transaction,
+) -> Vec<String> {
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    result.result.unwrap();
+    result.metadata.unwrap().log_messages
+}
+
+/// Decodes the events of type `T` emitted in `log_messages`.
+pub fn parse_events<T: AnchorDeserialize + Discriminator>(log_messages: &[String]) -> Vec<T> {
+    log_messages
+        .iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|data| base64::decode(data).ok())
+        .filter(|data| data.len() >= 8 && data[..8] == T::discriminator())
+        .map(|data| T::deserialize(&mut &data[8..]).unwrap())
+        .collect()
+}
+
 pub async fn create_auction_house(
     context: &mut ProgramTestContext,
     payer_wallet: &Keypair,
@@ -212,6 +282,101 @@ pub fn buy(
     seller: &Pubkey,
     listing_config: &Pubkey,
     sale_price: u64,
+) -> (mpl_auctioneer::accounts::AuctioneerBuy, Transaction) {
+    buy_with_options(
+        context,
+        ahkey,
+        ah,
+        test_metadata,
+        owner,
+        buyer,
+        seller,
+        listing_config,
+        sale_price,
+        BuyOptions::default(),
+    )
+}
+
+/// Accounts of an outbid SOL bid, passed as remaining accounts to `buy` so it can be refunded.
+pub fn outbid_accounts(
+    ahkey: &Pubkey,
+    ah: &AuctionHouse,
+    test_metadata: &Metadata,
+    owner: &Pubkey,
+    previous_buyer: &Pubkey,
+    previous_price: u64,
+) -> Vec<AccountMeta> {
+    let seller_token_account = get_associated_token_address(owner, &test_metadata.mint.pubkey());
+    let (trade_state, _) = find_trade_state_address(
+        previous_buyer,
+        ahkey,
+        &seller_token_account,
+        &ah.treasury_mint,
+        &test_metadata.mint.pubkey(),
+        previous_price,
+        1,
+    );
+    let (escrow, _) = find_escrow_payment_address(ahkey, previous_buyer);
+    vec![
+        AccountMeta::new(*previous_buyer, false),
+        AccountMeta::new(*previous_buyer, false),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new(trade_state, false),
+    ]
+}
+
+/// Accounts of `execute_sale` beyond those of `buy`, passed as remaining accounts to settle a winning
+/// Dutch auction bid.
+pub fn dutch_sale_accounts(
+    ahkey: &Pubkey,
+    ah: &AuctionHouse,
+    test_metadata: &Metadata,
+    buyer: &Pubkey,
+) -> Vec<AccountMeta> {
+    let seller = test_metadata.token.pubkey();
+    let token_mint = test_metadata.mint.pubkey();
+    let token_account = get_associated_token_address(&seller, &token_mint);
+    let (seller_trade_state, _) = find_auctioneer_trade_state_address(
+        &seller,
+        ahkey,
+        &token_account,
+        &ah.treasury_mint,
+        &token_mint,
+        1,
+    );
+    let (free_trade_state, _) = find_trade_state_address(
+        &seller,
+        ahkey,
+        &token_account,
+        &ah.treasury_mint,
+        &token_mint,
+        0,
+        1,
+    );
+    let (program_as_signer, _) = find_program_as_signer_address();
+    vec![
+        AccountMeta::new_readonly(token_mint, false),
+        AccountMeta::new(seller_trade_state, false),
+        AccountMeta::new(free_trade_state, false),
+        AccountMeta::new(seller, false),
+        AccountMeta::new(get_associated_token_address(buyer, &token_mint), false),
+        AccountMeta::new(ah.auction_house_treasury, false),
+        AccountMeta::new_readonly(program_as_signer, false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ]
+}
+
+pub fn buy_with_options(
+    context: &mut ProgramTestContext,
+    ahkey: &Pubkey,
+    ah: &AuctionHouse,
+    test_metadata: &Metadata,
+    owner: &Pubkey,
+    buyer: &Keypair,
+    seller: &Pubkey,
+    listing_config: &Pubkey,
+    sale_price: u64,
+    options: BuyOptions,
 ) -> (mpl_auctioneer::accounts::AuctioneerBuy, Transaction) {
     let seller_token_account = get_associated_token_address(owner, &test_metadata.mint.pubkey());
     let trade_state = find_trade_state_address(
@@ -249,14 +414,28 @@ pub fn buy(
         ah_auctioneer_pda: auctioneer_pda,
     };
 
-    let account_metas = accounts.to_account_metas(None);
+    let mut account_metas = accounts.to_account_metas(None);
+    let settles_sale = !options.sale_accounts.is_empty();
+    if settles_sale {
+        // Settling the sale pays the seller and transfers the token out of its account.
+        for meta in account_metas.iter_mut() {
+            if meta.pubkey == *seller || meta.pubkey == seller_token_account {
+                meta.is_writable = true;
+            }
+        }
+    }
+    account_metas.extend(options.gate_accounts);
+    account_metas.extend(options.outbid_accounts);
+    account_metas.extend(options.sale_accounts);
 
     let buy_ix = mpl_auctioneer::instruction::Buy {
         trade_state_bump: bts_bump,
         escrow_payment_bump: escrow_bump,
         auctioneer_authority_bump: aa_bump,
-        token_size: 1,
+        token_size: options.listing_token_size.unwrap_or(1),
         buyer_price: sale_price,
+        allowlist_proof: options.allowlist_proof,
+        keep_escrow_on_outbid: options.keep_escrow_on_outbid,
     };
     let data = buy_ix.data();
 
@@ -266,6 +445,270 @@ pub fn buy(
         accounts: account_metas,
     };
 
+    let mut instructions = vec![instruction];
+    if settles_sale {
+        instructions.insert(0, ComputeBudgetInstruction::set_compute_unit_limit(400_000));
+    }
+
+    (
+        accounts,
+        Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&buyer.pubkey()),
+            &[buyer],
+            context.last_blockhash,
+        ),
+    )
+}
+
+pub fn commit_bid(
+    context: &mut ProgramTestContext,
+    ahkey: &Pubkey,
+    ah: &AuctionHouse,
+    test_metadata: &Metadata,
+    buyer: &Keypair,
+    seller: &Pubkey,
+    listing_config: &Pubkey,
+    price: u64,
+    salt: [u8; 32],
+    deposit: u64,
+) -> (mpl_auctioneer::accounts::AuctioneerCommitBid, Transaction) {
+    let token_account = get_associated_token_address(seller, &test_metadata.mint.pubkey());
+    let (sealed_bid, _) = find_sealed_bid_address(listing_config, &buyer.pubkey());
+    let (escrow_lock, _) = find_escrow_lock_address(ahkey, &buyer.pubkey());
+    let (escrow, escrow_bump) = find_escrow_payment_address(ahkey, &buyer.pubkey());
+    let (auctioneer_authority, aa_bump) = find_auctioneer_authority_seeds(ahkey);
+    let (auctioneer_pda, _) = find_auctioneer_pda(ahkey, &auctioneer_authority);
+    let accounts = mpl_auctioneer::accounts::AuctioneerCommitBid {
+        auction_house_program: mpl_auction_house::id(),
+        listing_config: *listing_config,
+        sealed_bid,
+        escrow_lock,
+        seller: *seller,
+        token_account,
+        wallet: buyer.pubkey(),
+        payment_account: buyer.pubkey(),
+        transfer_authority: buyer.pubkey(),
+        escrow_payment_account: escrow,
+        treasury_mint: ah.treasury_mint,
+        authority: ah.authority,
+        auction_house: *ahkey,
+        auction_house_fee_account: ah.auction_house_fee_account,
+        auctioneer_authority,
+        ah_auctioneer_pda: auctioneer_pda,
+        token_program: spl_token::id(),
+        system_program: solana_program::system_program::id(),
+        rent: sysvar::rent::id(),
+    };
+
+    let data = mpl_auctioneer::instruction::CommitBid {
+        escrow_payment_bump: escrow_bump,
+        auctioneer_authority_bump: aa_bump,
+        token_size: 1,
+        commitment: sealed_bid_commitment(price, &salt),
+        deposit,
+        allowlist_proof: None,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: mpl_auctioneer::id(),
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (
+        accounts,
+        Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&buyer.pubkey()),
+            &[buyer],
+            context.last_blockhash,
+        ),
+    )
+}
+
+pub fn reveal_bid(
+    context: &mut ProgramTestContext,
+    ahkey: &Pubkey,
+    test_metadata: &Metadata,
+    buyer: &Keypair,
+    seller: &Pubkey,
+    listing_config: &Pubkey,
+    price: u64,
+    salt: [u8; 32],
+) -> (mpl_auctioneer::accounts::AuctioneerRevealBid, Transaction) {
+    let token_account = get_associated_token_address(seller, &test_metadata.mint.pubkey());
+    let (sealed_bid, _) = find_sealed_bid_address(listing_config, &buyer.pubkey());
+    let accounts = mpl_auctioneer::accounts::AuctioneerRevealBid {
+        auction_house_program: mpl_auction_house::id(),
+        listing_config: *listing_config,
+        sealed_bid,
+        seller: *seller,
+        token_account,
+        wallet: buyer.pubkey(),
+        auction_house: *ahkey,
+    };
+
+    let data = mpl_auctioneer::instruction::RevealBid {
+        token_size: 1,
+        price,
+        salt,
+        allowlist_proof: None,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: mpl_auctioneer::id(),
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
+    (
+        accounts,
+        Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&buyer.pubkey()),
+            &[buyer],
+            context.last_blockhash,
+        ),
+    )
+}
+
+/// Cancel the seller's listing, passing `remaining_accounts` after the cancel accounts.
+pub fn cancel_listing_tx(
+    context: &ProgramTestContext,
+    ahkey: &Pubkey,
+    ah: &AuctionHouse,
+    test_metadata: &Metadata,
+    listing_config: &Pubkey,
+    seller_trade_state: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Transaction {
+    let token =
+        get_associated_token_address(&test_metadata.token.pubkey(), &test_metadata.mint.pubkey());
+    let (auctioneer_authority, aa_bump) = find_auctioneer_authority_seeds(ahkey);
+    let (auctioneer_pda, _) = find_auctioneer_pda(ahkey, &auctioneer_authority);
+    let mut accounts = mpl_auctioneer::accounts::AuctioneerCancel {
+        auction_house_program: mpl_auction_house::id(),
+        listing_config: *listing_config,
+        seller: test_metadata.token.pubkey(),
+        auction_house: *ahkey,
+        wallet: test_metadata.token.pubkey(),
+        token_account: token,
+        authority: ah.authority,
+        trade_state: *seller_trade_state,
+        token_program: spl_token::id(),
+        token_mint: test_metadata.mint.pubkey(),
+        auction_house_fee_account: ah.auction_house_fee_account,
+        auctioneer_authority,
+        ah_auctioneer_pda: auctioneer_pda,
+    }
+    .to_account_metas(None);
+    accounts.extend(remaining_accounts);
+    let instruction = Instruction {
+        program_id: mpl_auctioneer::id(),
+        data: mpl_auctioneer::instruction::Cancel {
+            auctioneer_authority_bump: aa_bump,
+            buyer_price: u64::MAX,
+            token_size: 1,
+        }
+        .data(),
+        accounts,
+    };
+
+    Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&test_metadata.token.pubkey()),
+        &[&test_metadata.token],
+        context.last_blockhash,
+    )
+}
+
+/// Cancel the bid `buyer` placed at `price` on the listing.
+pub fn cancel_bid_tx(
+    context: &ProgramTestContext,
+    ahkey: &Pubkey,
+    ah: &AuctionHouse,
+    test_metadata: &Metadata,
+    listing_config: &Pubkey,
+    buyer: &Keypair,
+    price: u64,
+) -> Transaction {
+    let token =
+        get_associated_token_address(&test_metadata.token.pubkey(), &test_metadata.mint.pubkey());
+    let (trade_state, _) = find_trade_state_address(
+        &buyer.pubkey(),
+        ahkey,
+        &token,
+        &ah.treasury_mint,
+        &test_metadata.mint.pubkey(),
+        price,
+        1,
+    );
+    let (auctioneer_authority, aa_bump) = find_auctioneer_authority_seeds(ahkey);
+    let (auctioneer_pda, _) = find_auctioneer_pda(ahkey, &auctioneer_authority);
+    let accounts = mpl_auctioneer::accounts::AuctioneerCancel {
+        auction_house_program: mpl_auction_house::id(),
+        listing_config: *listing_config,
+        seller: test_metadata.token.pubkey(),
+        auction_house: *ahkey,
+        wallet: buyer.pubkey(),
+        token_account: token,
+        authority: ah.authority,
+        trade_state,
+        token_program: spl_token::id(),
+        token_mint: test_metadata.mint.pubkey(),
+        auction_house_fee_account: ah.auction_house_fee_account,
+        auctioneer_authority,
+        ah_auctioneer_pda: auctioneer_pda,
+    }
+    .to_account_metas(None);
+    let instruction = Instruction {
+        program_id: mpl_auctioneer::id(),
+        data: mpl_auctioneer::instruction::Cancel {
+            auctioneer_authority_bump: aa_bump,
+            buyer_price: price,
+            token_size: 1,
+        }
+        .data(),
+        accounts,
+    };
+
+    Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&buyer.pubkey()),
+        &[buyer],
+        context.last_blockhash,
+    )
+}
+
+pub fn close_sealed_bid(
+    context: &mut ProgramTestContext,
+    ahkey: &Pubkey,
+    buyer: &Keypair,
+    listing_config: &Pubkey,
+) -> (
+    mpl_auctioneer::accounts::AuctioneerCloseSealedBid,
+    Transaction,
+) {
+    let (sealed_bid, _) = find_sealed_bid_address(listing_config, &buyer.pubkey());
+    let (escrow_lock, _) = find_escrow_lock_address(ahkey, &buyer.pubkey());
+    let accounts = mpl_auctioneer::accounts::AuctioneerCloseSealedBid {
+        listing_config: *listing_config,
+        sealed_bid,
+        escrow_lock,
+        wallet: buyer.pubkey(),
+    };
+
+    let data = mpl_auctioneer::instruction::CloseSealedBid {}.data();
+
+    let instruction = Instruction {
+        program_id: mpl_auctioneer::id(),
+        data,
+        accounts: accounts.to_account_metas(None),
+    };
+
     (
         accounts,
         Transaction::new_signed_with_payer(
@@ -292,6 +735,135 @@ pub fn execute_sale(
     token_size: u64,
     buyer_price: u64,
 ) -> (mpl_auctioneer::accounts::AuctioneerExecuteSale, Transaction) {
+    let (mut execute_sale_accounts, data) = execute_sale_accounts(
+        listing_config,
+        ahkey,
+        ah,
+        test_metadata,
+        buyer,
+        seller,
+        token_account,
+        seller_trade_state,
+        buyer_trade_state,
+        token_size,
+        buyer_price,
+    );
+    execute_sale_accounts.authority = authority.pubkey();
+
+    let execute_sale_instruction = Instruction {
+        program_id: mpl_auctioneer::id(),
+        data: mpl_auctioneer::instruction::ExecuteSale {
+            escrow_payment_bump: data.escrow_payment_bump,
+            free_trade_state_bump: data.free_trade_state_bump,
+            program_as_signer_bump: data.program_as_signer_bump,
+            auctioneer_authority_bump: data.auctioneer_authority_bump,
+            token_size: data.token_size,
+            buyer_price: data.buyer_price,
+        }
+        .data(),
+        accounts: execute_sale_accounts.to_account_metas(None),
+    };
+
+    let compute_ix = ComputeBudgetInstruction::set_compute_unit_limit(350_000);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[compute_ix, execute_sale_instruction],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    (execute_sale_accounts, tx)
+}
+
+/// Settle a sale signed by neither the buyer, the seller nor the Auction House authority.
+pub fn keeper_execute_sale(
+    context: &mut ProgramTestContext,
+    listing_config: &Pubkey,
+    ahkey: &Pubkey,
+    ah: &AuctionHouse,
+    keeper: &Keypair,
+    test_metadata: &Metadata,
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    token_account: &Pubkey,
+    seller_trade_state: &Pubkey,
+    buyer_trade_state: &Pubkey,
+    token_size: u64,
+    buyer_price: u64,
+) -> (
+    mpl_auctioneer::accounts::AuctioneerKeeperExecuteSale,
+    Transaction,
+) {
+    let (execute_sale_accounts, data) = execute_sale_accounts(
+        listing_config,
+        ahkey,
+        ah,
+        test_metadata,
+        buyer,
+        seller,
+        token_account,
+        seller_trade_state,
+        buyer_trade_state,
+        token_size,
+        buyer_price,
+    );
+    let auctioneer_authority = execute_sale_accounts.auctioneer_authority;
+    let keeper_execute_sale_accounts = mpl_auctioneer::accounts::AuctioneerKeeperExecuteSale {
+        execute_sale: execute_sale_accounts,
+        keeper: keeper.pubkey(),
+    };
+
+    // The auctioneer authority pays the rent and fees of the sale out of the keeper's funds.
+    let mut account_metas = keeper_execute_sale_accounts.to_account_metas(None);
+    for account_meta in account_metas.iter_mut() {
+        if account_meta.pubkey == auctioneer_authority {
+            account_meta.is_writable = true;
+        }
+    }
+
+    let keeper_execute_sale_instruction = Instruction {
+        program_id: mpl_auctioneer::id(),
+        data: mpl_auctioneer::instruction::KeeperExecuteSale {
+            escrow_payment_bump: data.escrow_payment_bump,
+            free_trade_state_bump: data.free_trade_state_bump,
+            program_as_signer_bump: data.program_as_signer_bump,
+            auctioneer_authority_bump: data.auctioneer_authority_bump,
+            token_size: data.token_size,
+            buyer_price: data.buyer_price,
+        }
+        .data(),
+        accounts: account_metas,
+    };
+
+    let compute_ix = ComputeBudgetInstruction::set_compute_unit_limit(350_000);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[compute_ix, keeper_execute_sale_instruction],
+        Some(&keeper.pubkey()),
+        &[keeper],
+        context.last_blockhash,
+    );
+
+    (keeper_execute_sale_accounts, tx)
+}
+
+fn execute_sale_accounts(
+    listing_config: &Pubkey,
+    ahkey: &Pubkey,
+    ah: &AuctionHouse,
+    test_metadata: &Metadata,
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    token_account: &Pubkey,
+    seller_trade_state: &Pubkey,
+    buyer_trade_state: &Pubkey,
+    token_size: u64,
+    buyer_price: u64,
+) -> (
+    mpl_auctioneer::accounts::AuctioneerExecuteSale,
+    mpl_auctioneer::instruction::ExecuteSale,
+) {
     let buyer_token_account = get_associated_token_address(buyer, &test_metadata.mint.pubkey());
 
     let (program_as_signer, pas_bump) = find_program_as_signer_address();
@@ -332,35 +904,20 @@ pub fn execute_sale(
         system_program: system_program::id(),
         ata_program: spl_associated_token_account::id(),
         rent: sysvar::rent::id(),
-        authority: authority.pubkey(),
+        authority: ah.authority,
         auctioneer_authority,
         ah_auctioneer_pda: auctioneer_pda,
     };
-
-    let execute_sale_account_metas = execute_sale_accounts.to_account_metas(None);
-
-    let execute_sale_instruction = Instruction {
-        program_id: mpl_auctioneer::id(),
-        data: mpl_auctioneer::instruction::ExecuteSale {
-            escrow_payment_bump: escrow_bump,
-            free_trade_state_bump: free_sts_bump,
-            program_as_signer_bump: pas_bump,
-            auctioneer_authority_bump: aa_bump,
-            token_size,
-            buyer_price,
-        }
-        .data(),
-        accounts: execute_sale_account_metas,
+    let data = mpl_auctioneer::instruction::ExecuteSale {
+        escrow_payment_bump: escrow_bump,
+        free_trade_state_bump: free_sts_bump,
+        program_as_signer_bump: pas_bump,
+        auctioneer_authority_bump: aa_bump,
+        token_size,
+        buyer_price,
     };
 
-    let tx = Transaction::new_signed_with_payer(
-        &[execute_sale_instruction],
-        Some(&authority.pubkey()),
-        &[authority],
-        context.last_blockhash,
-    );
-
-    (execute_sale_accounts, tx)
+    (execute_sale_accounts, data)
 }
 
 pub fn sell_mint(
@@ -446,6 +1003,14 @@ pub fn sell_mint(
         time_ext_period,
         time_ext_delta,
         allow_high_bid_cancel,
+        auction_type: None,
+        buy_now_price: None,
+        anti_sniping: None,
+        keeper_reward: None,
+        seller_cancel_rule: None,
+        collection_gate: None,
+        allowlist_root: None,
+        record_bid_history: None,
     }
     .data();
 
@@ -482,6 +1047,40 @@ pub fn sell(
     (mpl_auctioneer::accounts::AuctioneerSell, Pubkey),
     Transaction,
 ) {
+    sell_with_options(
+        context,
+        ahkey,
+        ah,
+        test_metadata,
+        start_time,
+        end_time,
+        reserve_price,
+        min_bid_increment,
+        time_ext_period,
+        time_ext_delta,
+        allow_high_bid_cancel,
+        SellOptions::default(),
+    )
+}
+
+pub fn sell_with_options(
+    context: &mut ProgramTestContext,
+    ahkey: &Pubkey,
+    ah: &AuctionHouse,
+    test_metadata: &Metadata,
+    start_time: UnixTimestamp,
+    end_time: UnixTimestamp,
+    reserve_price: Option<u64>,
+    min_bid_increment: Option<u64>,
+    time_ext_period: Option<u32>,
+    time_ext_delta: Option<u32>,
+    allow_high_bid_cancel: Option<bool>,
+    options: SellOptions,
+) -> (
+    (mpl_auctioneer::accounts::AuctioneerSell, Pubkey),
+    Transaction,
+) {
+    let token_size = options.token_size.unwrap_or(1);
     let token =
         get_associated_token_address(&test_metadata.token.pubkey(), &test_metadata.mint.pubkey());
     let (seller_trade_state, sts_bump) = find_auctioneer_trade_state_address(
@@ -490,7 +1089,7 @@ pub fn sell(
         &token,
         &ah.treasury_mint,
         &test_metadata.mint.pubkey(),
-        1,
+        token_size,
     );
 
     let (free_seller_trade_state, free_sts_bump) = find_trade_state_address(
@@ -500,7 +1099,7 @@ pub fn sell(
         &ah.treasury_mint,
         &test_metadata.mint.pubkey(),
         0,
-        1,
+        token_size,
     );
 
     let (listing_config_address, _list_bump) = find_listing_config_address(
@@ -509,7 +1108,7 @@ pub fn sell(
         &token,
         &ah.treasury_mint,
         &test_metadata.mint.pubkey(),
-        1,
+        token_size,
     );
 
     let (pas, pas_bump) = find_program_as_signer_address();
@@ -541,7 +1140,7 @@ pub fn sell(
         free_trade_state_bump: free_sts_bump,
         program_as_signer_bump: pas_bump,
         auctioneer_authority_bump: aa_bump,
-        token_size: 1,
+        token_size,
         start_time,
         end_time,
         reserve_price,
@@ -549,6 +1148,14 @@ pub fn sell(
         time_ext_period,
         time_ext_delta,
         allow_high_bid_cancel,
+        auction_type: options.auction_type,
+        buy_now_price: options.buy_now_price,
+        anti_sniping: options.anti_sniping,
+        keeper_reward: options.keeper_reward,
+        seller_cancel_rule: options.seller_cancel_rule,
+        collection_gate: options.collection_gate,
+        allowlist_root: options.allowlist_root,
+        record_bid_history: options.record_bid_history,
     }
     .data();
 
@@ -569,6 +1176,72 @@ pub fn sell(
     )
 }
 
+pub fn update_listing_config(
+    context: &mut ProgramTestContext,
+    ahkey: &Pubkey,
+    test_metadata: &Metadata,
+    listing_config: &Pubkey,
+    start_time: Option<UnixTimestamp>,
+    end_time: Option<UnixTimestamp>,
+    reserve_price: Option<u64>,
+    min_bid_increment: Option<u64>,
+) -> Transaction {
+    let token =
+        get_associated_token_address(&test_metadata.token.pubkey(), &test_metadata.mint.pubkey());
+    let accounts = mpl_auctioneer::accounts::AuctioneerUpdateListingConfig {
+        auction_house_program: mpl_auction_house::id(),
+        listing_config: *listing_config,
+        wallet: test_metadata.token.pubkey(),
+        token_account: token,
+        auction_house: *ahkey,
+    };
+
+    let instruction = Instruction {
+        program_id: mpl_auctioneer::id(),
+        data: mpl_auctioneer::instruction::UpdateListingConfig {
+            token_size: 1,
+            start_time,
+            end_time,
+            reserve_price,
+            min_bid_increment,
+        }
+        .data(),
+        accounts: accounts.to_account_metas(None),
+    };
+
+    Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&test_metadata.token.pubkey()),
+        &[&test_metadata.token],
+        context.last_blockhash,
+    )
+}
+
+pub fn migrate_listing_config(
+    context: &mut ProgramTestContext,
+    listing_config: &Pubkey,
+    payer: &Keypair,
+) -> Transaction {
+    let accounts = mpl_auctioneer::accounts::AuctioneerMigrateListingConfig {
+        listing_config: *listing_config,
+        payer: payer.pubkey(),
+        system_program: system_program::id(),
+    };
+
+    let instruction = Instruction {
+        program_id: mpl_auctioneer::id(),
+        data: mpl_auctioneer::instruction::MigrateListingConfig {}.data(),
+        accounts: accounts.to_account_metas(None),
+    };
+
+    Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer],
+        context.last_blockhash,
+    )
+}
+
 pub fn withdraw(
     context: &mut ProgramTestContext,
     buyer: &Keypair,
@@ -592,6 +1265,7 @@ pub fn withdraw(
     let (escrow_payment_account, escrow_bump) = find_escrow_payment_address(ahkey, &buyer.pubkey());
     let (auctioneer_authority, aa_bump) = find_auctioneer_authority_seeds(ahkey);
     let (auctioneer_pda, _) = find_auctioneer_pda(ahkey, &auctioneer_authority);
+    let (escrow_lock, _) = find_escrow_lock_address(ahkey, &buyer.pubkey());
 
     let accounts = mpl_auctioneer::accounts::AuctioneerWithdraw {
         auction_house_program: mpl_auction_house::id(),
@@ -608,6 +1282,7 @@ pub fn withdraw(
         rent: sysvar::rent::id(),
         auctioneer_authority,
         ah_auctioneer_pda: auctioneer_pda,
+        escrow_lock,
     };
 
     let accounts_metas = accounts.to_account_metas(None);
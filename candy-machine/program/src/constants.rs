@@ -11,14 +11,76 @@ pub const FREEZE_FEE: u64 = 0; //100000; // 0.0001 SOL
 
 pub const MAX_FREEZE_TIME: i64 = 60 * 60 * 24 * 31; // 1 month
 
+// flag positions in the uuid of candy machines without a features header
 pub const COLLECTIONS_FEATURE_INDEX: usize = 0;
 pub const FREEZE_FEATURE_INDEX: usize = 1;
 pub const FREEZE_LOCK_FEATURE_INDEX: usize = 2;
+pub const CONFIG_LINE_SETTINGS_FEATURE_INDEX: usize = 3;
+
+pub const MAX_MINT_PHASES: usize = 8;
+pub const MAX_PHASE_LABEL_LENGTH: usize = 16;
+
+pub const MINT_PHASE_SIZE: usize = 4 + MAX_PHASE_LABEL_LENGTH + // label
+    8 + // start date
+    9 + // end date
+    8 + // price
+    3 + // mint limit
+    33 + // gate mint
+    33; // allowlist root
+
+pub const MINT_PHASES_SPACE: usize = 4 + MAX_MINT_PHASES * MINT_PHASE_SIZE;
+
+pub const MINT_LIMIT_SIZE: usize = 1 + 2 + 1; // option + limit + bot tax
+
+pub const MAX_PAYMENT_OPTIONS: usize = 4;
+pub const MAX_PAYMENTS_PER_OPTION: usize = 3;
+
+pub const PAYMENT_SIZE: usize = 1 + 32 + // kind
+    8 + // amount
+    33; // destination
+
+pub const PAYMENT_OPTIONS_SPACE: usize =
+    4 + MAX_PAYMENT_OPTIONS * (4 + MAX_PAYMENTS_PER_OPTION * PAYMENT_SIZE);
+
+pub const COMMIT_REVEAL_SIZE: usize = 1;
+
+pub const ON_CHAIN_REVEAL_SIZE: usize = 1;
+
+pub const PROGRAMMABLE_SIZE: usize = 1 + 33; // option + rule set
+
+pub const BOT_TAX_SIZE: usize = 1 + // option
+    8 + // lamports
+    2 + // conditions
+    1 + // pay to wallet
+    1; // skip allowlist phases
+
+pub const FREEZE_REFUND_SIZE: usize = 1;
+
+pub const MINT_RECEIPTS_SIZE: usize = 1;
+
+// space needed after the config lines region to hold every setting
+pub const MAX_SETTINGS_SPACE: usize = MINT_PHASES_SPACE
+    + MINT_LIMIT_SIZE
+    + PAYMENT_OPTIONS_SPACE
+    + COMMIT_REVEAL_SIZE
+    + ON_CHAIN_REVEAL_SIZE
+    + PROGRAMMABLE_SIZE
+    + BOT_TAX_SIZE
+    + FREEZE_REFUND_SIZE
+    + MINT_RECEIPTS_SIZE;
+
+pub const MAX_ALLOWLIST_PROOF_LENGTH: usize = 32;
 
 pub const COLLECTION_PDA_SIZE: usize = 8 + 32 + 32;
 
 pub const CONFIG_LINE_SIZE: usize = 4 + MAX_NAME_LENGTH + 4 + MAX_URI_LENGTH;
 
+// stored at CONFIG_ARRAY_START by compressed candy machines, before their config lines
+pub const CONFIG_LINE_SETTINGS_SIZE: usize = 4 + MAX_NAME_LENGTH + // prefix name
+    4 + // name length
+    4 + MAX_URI_LENGTH + // prefix uri
+    4; // uri length
+
 pub const BLOCK_HASHES: Pubkey =
     solana_program::pubkey!("SysvarRecentB1ockHashes11111111111111111111");
 pub const GUMDROP_ID: Pubkey =
@@ -56,3 +118,10 @@ pub const CONFIG_ARRAY_START: usize = 8 + // key
     9 + // discount price
     32 + // mint key for whitelist
     1 + 32 + 1; // gatekeeper
+
+// version and flags of the features, in the bytes left after the largest candy machine struct
+pub const FEATURES_SIZE: usize = 4;
+pub const FEATURES_START: usize = CONFIG_ARRAY_START - FEATURES_SIZE;
+
+// NFTs minted by a single mint_nfts, bounded by the compute budget of a transaction
+pub const MAX_MINTS_PER_TRANSACTION: u8 = 5;
@@ -112,4 +112,94 @@ pub enum CandyError {
     CannotSwitchFromHiddenSettings,
     #[msg("Invalid Metadata Account")]
     InvalidMetadataAccount,
+    #[msg("Mint phases must be ordered, non-overlapping and within the phase limits")]
+    InvalidMintPhases,
+    #[msg("Can't change mint phases after items have begun to be minted")]
+    NoChangingPhasesDuringMint,
+    #[msg("Mint phases can't be combined with whitelist mint settings")]
+    MintPhasesWithWhitelistSettings,
+    #[msg("Candy Machine account is too small to store these settings")]
+    NotEnoughSpaceForSettings,
+    #[msg("Invalid Candy Machine settings")]
+    InvalidCandyMachineSettings,
+    #[msg("Wallet has reached its mint limit")]
+    MintLimitReached,
+    #[msg("Mint limit must be greater than zero")]
+    InvalidMintLimit,
+    #[msg("Wallet is not on the allowlist of the active mint phase")]
+    NotAllowlisted,
+    #[msg("Allowlist proof is longer than MAX_ALLOWLIST_PROOF_LENGTH")]
+    AllowlistProofTooLong,
+    #[msg("Payment options must have between one and MAX_PAYMENTS_PER_OPTION payments")]
+    InvalidPaymentOptions,
+    #[msg("Selected payment option doesn't exist")]
+    InvalidPaymentOption,
+    #[msg("Payment options can't be combined with freeze")]
+    NoPaymentOptionsWithFreeze,
+    #[msg("NFT is not a verified member of the payment collection")]
+    InvalidCollectionPayment,
+    #[msg("Commit-reveal is not enabled for this candy machine")]
+    CommitRevealNotEnabled,
+    #[msg("Mint commitment can't be revealed until the next slot hash is available")]
+    MintCommitmentNotRevealable,
+    #[msg("Mint commitment slot hash is no longer available")]
+    MintCommitmentExpired,
+    #[msg("Mint commitment can only be closed once expired")]
+    MintCommitmentNotExpired,
+    #[msg("Config line prefixes and suffix lengths must fit in the name and URI limits")]
+    InvalidConfigLineSettings,
+    #[msg("Config line is longer than the candy machine allows")]
+    ConfigLineTooLong,
+    #[msg("On-chain reveal requires hidden settings and retained authority, without a collection or programmable NFTs")]
+    InvalidOnChainReveal,
+    #[msg("Can't change on-chain reveal after items have begun to be minted")]
+    NoChangingOnChainRevealDuringMint,
+    #[msg("On-chain reveal is not enabled for this candy machine")]
+    OnChainRevealNotEnabled,
+    #[msg("Reveal lines can only be added once minting is complete")]
+    MintNotComplete,
+    #[msg("Reveal lines must be added in order, or from index 0 to start over")]
+    RevealLinesOutOfOrder,
+    #[msg("Reveal lines are already verified")]
+    RevealLinesAlreadyVerified,
+    #[msg("Reveal lines don't match the hidden settings hash")]
+    RevealHashMismatch,
+    #[msg("Reveal lines are not verified")]
+    RevealLinesNotVerified,
+    #[msg("NFT can't be revealed by this candy machine")]
+    NftNotRevealable,
+    #[msg("Can't change programmable settings after items have begun to be minted")]
+    NoChangingProgrammableDuringMint,
+    #[msg("Invalid bot tax")]
+    InvalidBotTax,
+    #[msg("Freeze refunds require config lines and can't be used with programmable NFTs")]
+    InvalidFreezeRefund,
+    #[msg("Can't change freeze refunds after items have begun to be minted")]
+    NoChangingFreezeRefundDuringMint,
+    #[msg("Freeze refunds are not enabled")]
+    FreezeRefundNotEnabled,
+    #[msg("Mint funds are no longer locked, NFTs can't be refunded")]
+    RefundWindowClosed,
+    #[msg("NFT is not frozen by the candy machine")]
+    NftNotFrozen,
+    #[msg("Invalid candy machine features")]
+    InvalidFeatures,
+    #[msg("Candy machine features are already migrated")]
+    FeaturesAlreadyMigrated,
+    #[msg("Candy machine data is too large")]
+    CandyMachineDataTooLarge,
+    #[msg("Items available can only be increased")]
+    CannotShrinkCandyMachine,
+    #[msg("Can't add items once a candy machine with freeze has sold out")]
+    NoResizeAfterFrozenSellOut,
+    #[msg("Resize exceeds the maximum account growth of one instruction")]
+    ResizeTooLarge,
+    #[msg("Invalid number of NFTs to mint")]
+    InvalidMintCount,
+    #[msg("Candy machine can only mint one NFT per transaction")]
+    BatchMintNotSupported,
+    #[msg("Invalid mint receipt")]
+    InvalidMintReceipt,
+    #[msg("Missing remaining account")]
+    MissingRemainingAccount,
 }
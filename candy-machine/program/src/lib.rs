@@ -22,6 +22,14 @@ pub mod candy_machine {
         handle_initialize_candy_machine(ctx, data)
     }
 
+    pub fn initialize_compressed_candy_machine(
+        ctx: Context<InitializeCompressedCandyMachine>,
+        data: CandyMachineData,
+        config_line_settings: ConfigLineSettings,
+    ) -> Result<()> {
+        handle_initialize_compressed_candy_machine(ctx, data, config_line_settings)
+    }
+
     pub fn update_candy_machine(
         ctx: Context<UpdateCandyMachine>,
         data: CandyMachineData,
@@ -36,6 +44,90 @@ pub mod candy_machine {
         handle_update_authority(ctx, new_authority)
     }
 
+    pub fn update_mint_phases(
+        ctx: Context<UpdateCandyMachine>,
+        phases: Vec<MintPhase>,
+    ) -> Result<()> {
+        handle_update_mint_phases(ctx, phases)
+    }
+
+    pub fn update_mint_limit(
+        ctx: Context<UpdateCandyMachine>,
+        mint_limit: Option<MintLimit>,
+    ) -> Result<()> {
+        handle_update_mint_limit(ctx, mint_limit)
+    }
+
+    pub fn update_payment_options(
+        ctx: Context<UpdateCandyMachine>,
+        payment_options: Vec<PaymentOption>,
+    ) -> Result<()> {
+        handle_update_payment_options(ctx, payment_options)
+    }
+
+    pub fn update_commit_reveal(
+        ctx: Context<UpdateCandyMachine>,
+        commit_reveal: bool,
+    ) -> Result<()> {
+        handle_update_commit_reveal(ctx, commit_reveal)
+    }
+
+    pub fn update_on_chain_reveal(
+        ctx: Context<UpdateCandyMachine>,
+        on_chain_reveal: bool,
+    ) -> Result<()> {
+        handle_update_on_chain_reveal(ctx, on_chain_reveal)
+    }
+
+    pub fn update_programmable(
+        ctx: Context<UpdateCandyMachine>,
+        programmable: Option<ProgrammableSettings>,
+    ) -> Result<()> {
+        handle_update_programmable(ctx, programmable)
+    }
+
+    pub fn update_bot_tax(ctx: Context<UpdateCandyMachine>, bot_tax: Option<BotTax>) -> Result<()> {
+        handle_update_bot_tax(ctx, bot_tax)
+    }
+
+    pub fn update_freeze_refund(
+        ctx: Context<UpdateCandyMachine>,
+        freeze_refund: bool,
+    ) -> Result<()> {
+        handle_update_freeze_refund(ctx, freeze_refund)
+    }
+
+    pub fn update_mint_receipts(
+        ctx: Context<UpdateCandyMachine>,
+        mint_receipts: bool,
+    ) -> Result<()> {
+        handle_update_mint_receipts(ctx, mint_receipts)
+    }
+
+    pub fn migrate_features(ctx: Context<UpdateCandyMachine>) -> Result<()> {
+        handle_migrate_features(ctx)
+    }
+
+    pub fn resize_candy_machine(
+        ctx: Context<ResizeCandyMachine>,
+        items_available: u64,
+    ) -> Result<()> {
+        handle_resize_candy_machine(ctx, items_available)
+    }
+
+    pub fn submit_allowlist_proof(
+        ctx: Context<SubmitAllowlistProof>,
+        phase_index: u8,
+        entry: AllowlistEntry,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        handle_submit_allowlist_proof(ctx, phase_index, entry, proof)
+    }
+
+    pub fn close_allowlist_proof(ctx: Context<CloseAllowlistProof>, phase_index: u8) -> Result<()> {
+        handle_close_allowlist_proof(ctx, phase_index)
+    }
+
     pub fn add_config_lines(
         ctx: Context<AddConfigLines>,
         index: u32,
@@ -52,6 +144,14 @@ pub mod candy_machine {
         handle_remove_collection(ctx)
     }
 
+    pub fn commit_mint(ctx: Context<CommitMint>) -> Result<()> {
+        handle_commit_mint(ctx)
+    }
+
+    pub fn close_mint_commitment(ctx: Context<CloseMintCommitment>) -> Result<()> {
+        handle_close_mint_commitment(ctx)
+    }
+
     pub fn mint_nft<'info>(
         ctx: Context<'_, '_, '_, 'info, MintNFT<'info>>,
         creator_bump: u8,
@@ -59,10 +159,43 @@ pub mod candy_machine {
         handle_mint_nft(ctx, creator_bump)
     }
 
+    pub fn mint_nft_with_payment<'info>(
+        ctx: Context<'_, '_, '_, 'info, MintNFT<'info>>,
+        creator_bump: u8,
+        payment_option: u8,
+    ) -> Result<()> {
+        handle_mint_nft_with_payment(ctx, creator_bump, payment_option)
+    }
+
+    pub fn mint_nfts<'info>(
+        ctx: Context<'_, '_, '_, 'info, MintNFT<'info>>,
+        creator_bump: u8,
+        payment_option: u8,
+        count: u8,
+    ) -> Result<()> {
+        handle_mint_nfts(ctx, creator_bump, payment_option, count)
+    }
+
     pub fn set_collection_during_mint(ctx: Context<SetCollectionDuringMint>) -> Result<()> {
         handle_set_collection_during_mint(ctx)
     }
 
+    pub fn initialize_reveal_lines(ctx: Context<InitializeRevealLines>) -> Result<()> {
+        handle_initialize_reveal_lines(ctx)
+    }
+
+    pub fn add_reveal_lines(
+        ctx: Context<AddRevealLines>,
+        index: u64,
+        config_lines: Vec<ConfigLine>,
+    ) -> Result<()> {
+        handle_add_reveal_lines(ctx, index, config_lines)
+    }
+
+    pub fn reveal_nft(ctx: Context<RevealNFT>) -> Result<()> {
+        handle_reveal_nft(ctx)
+    }
+
     pub fn withdraw_funds<'info>(
         ctx: Context<'_, '_, '_, 'info, WithdrawFunds<'info>>,
     ) -> Result<()> {
@@ -81,6 +214,10 @@ pub mod candy_machine {
         handle_thaw_nft(ctx)
     }
 
+    pub fn refund_nft<'info>(ctx: Context<'_, '_, '_, 'info, RefundNFT<'info>>) -> Result<()> {
+        handle_refund_nft(ctx)
+    }
+
     pub fn unlock_funds<'info>(ctx: Context<'_, '_, '_, 'info, UnlockFunds<'info>>) -> Result<()> {
         handle_unlock_funds(ctx)
     }
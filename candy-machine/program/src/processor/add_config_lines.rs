@@ -2,12 +2,8 @@ use std::cell::RefMut;
 
 use anchor_lang::prelude::*;
 use arrayref::array_ref;
-use mpl_token_metadata::state::{MAX_NAME_LENGTH, MAX_URI_LENGTH};
 
-use crate::{
-    constants::{CONFIG_ARRAY_START, CONFIG_LINE_SIZE},
-    CandyError, CandyMachine, ConfigLine,
-};
+use crate::{CandyError, CandyMachine, ConfigLine, ConfigLinesLayout};
 
 /// Add multiple config lines to the candy machine.
 #[derive(Accounts)]
@@ -24,8 +20,9 @@ pub fn handle_add_config_lines(
 ) -> Result<()> {
     let candy_machine = &mut ctx.accounts.candy_machine;
     let account = candy_machine.to_account_info();
-    let current_count = get_config_count(&account.data.borrow_mut())?;
     let mut data = account.data.borrow_mut();
+    let layout = ConfigLinesLayout::load(&candy_machine.data, &data)?;
+    let current_count = get_config_count(&data, &layout)?;
     let mut fixed_config_lines = Vec::with_capacity(config_lines.len());
 
     // no risk overflow because you literally cannot store this many in an account
@@ -45,11 +42,15 @@ pub fn handle_add_config_lines(
     if candy_machine.data.hidden_settings.is_some() {
         return err!(CandyError::HiddenSettingsConfigsDoNotHaveConfigLines);
     }
+    // compressed candy machines only store the suffixes of the config lines
     for line in &config_lines {
-        let array_of_zeroes = vec![0u8; MAX_NAME_LENGTH - line.name.len()];
+        if line.name.len() > layout.name_length() || line.uri.len() > layout.uri_length() {
+            return err!(CandyError::ConfigLineTooLong);
+        }
+        let array_of_zeroes = vec![0u8; layout.name_length() - line.name.len()];
         let name = line.name.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
 
-        let array_of_zeroes = vec![0u8; MAX_URI_LENGTH - line.uri.len()];
+        let array_of_zeroes = vec![0u8; layout.uri_length() - line.uri.len()];
         let uri = line.uri.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
         fixed_config_lines.push(ConfigLine { name, uri })
     }
@@ -58,17 +59,14 @@ pub fn handle_add_config_lines(
     // remove unneeded u32 because we're just gonna edit the u32 at the front
     let serialized: &[u8] = &as_vec.as_slice()[4..];
 
-    let position = CONFIG_ARRAY_START + 4 + (index as usize) * CONFIG_LINE_SIZE;
+    let position = layout.line_position(index as usize);
 
     let array_slice: &mut [u8] =
-        &mut data[position..position + fixed_config_lines.len() * CONFIG_LINE_SIZE];
+        &mut data[position..position + fixed_config_lines.len() * layout.line_size()];
 
     array_slice.copy_from_slice(serialized);
 
-    let bit_mask_vec_start = CONFIG_ARRAY_START
-        + 4
-        + (candy_machine.data.items_available as usize) * CONFIG_LINE_SIZE
-        + 4;
+    let bit_mask_vec_start = layout.bit_mask_start(candy_machine.data.items_available as usize);
 
     let mut new_count = current_count;
     for i in 0..fixed_config_lines.len() {
@@ -106,12 +104,11 @@ pub fn handle_add_config_lines(
     }
 
     // plug in new count.
-    data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + 4]
-        .copy_from_slice(&(new_count as u32).to_le_bytes());
+    data[layout.start..layout.start + 4].copy_from_slice(&(new_count as u32).to_le_bytes());
 
     Ok(())
 }
 
-pub fn get_config_count(data: &RefMut<&mut [u8]>) -> Result<usize> {
-    Ok(u32::from_le_bytes(*array_ref![data, CONFIG_ARRAY_START, 4]) as usize)
+pub fn get_config_count(data: &RefMut<&mut [u8]>, layout: &ConfigLinesLayout) -> Result<usize> {
+    Ok(u32::from_le_bytes(*array_ref![data, layout.start, 4]) as usize)
 }
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    assert_keys_equal, constants::MAX_ALLOWLIST_PROOF_LENGTH, create_pda_account, AllowlistEntry,
+    AllowlistProof, CandyError, CandyMachine,
+};
+
+/// Submit (part of) a wallet's allowlist proof for a mint phase.
+#[derive(Accounts)]
+#[instruction(phase_index: u8)]
+pub struct SubmitAllowlistProof<'info> {
+    candy_machine: Account<'info, CandyMachine>,
+    /// CHECK: account seeds checked in handler, created on the first submission
+    #[account(mut)]
+    allowlist_proof: UncheckedAccount<'info>,
+    #[account(mut)]
+    wallet: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+/// Close an allowlist proof and return its rent to the wallet.
+#[derive(Accounts)]
+#[instruction(phase_index: u8)]
+pub struct CloseAllowlistProof<'info> {
+    candy_machine: Account<'info, CandyMachine>,
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [
+            AllowlistProof::PREFIX.as_bytes(),
+            candy_machine.key().as_ref(),
+            wallet.key().as_ref(),
+            &[phase_index]
+        ],
+        bump
+    )]
+    allowlist_proof: Account<'info, AllowlistProof>,
+    #[account(mut)]
+    wallet: Signer<'info>,
+}
+
+// proof elements are appended to the ones already submitted for the same entry,
+// submitting a different entry starts a new proof
+pub fn handle_submit_allowlist_proof(
+    ctx: Context<SubmitAllowlistProof>,
+    phase_index: u8,
+    entry: AllowlistEntry,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let candy_key = ctx.accounts.candy_machine.key();
+    let wallet_key = ctx.accounts.wallet.key();
+    let allowlist_proof_info = ctx.accounts.allowlist_proof.to_account_info();
+    let seeds: &[&[u8]] = &[
+        AllowlistProof::PREFIX.as_bytes(),
+        candy_key.as_ref(),
+        wallet_key.as_ref(),
+        &[phase_index],
+    ];
+    let (expected_key, bump) = Pubkey::find_program_address(seeds, &crate::id());
+    assert_keys_equal(&expected_key, allowlist_proof_info.key)?;
+
+    let mut allowlist_proof: Account<AllowlistProof> = if allowlist_proof_info.data_is_empty() {
+        let bump_seed = [bump];
+        let mut signer_seeds = seeds.to_vec();
+        signer_seeds.push(&bump_seed);
+        create_pda_account(
+            &ctx.accounts.wallet.to_account_info(),
+            &allowlist_proof_info,
+            AllowlistProof::SIZE,
+            &ctx.accounts.system_program.to_account_info(),
+            &signer_seeds,
+        )?;
+        Account::try_from_unchecked(&allowlist_proof_info)?
+    } else {
+        Account::try_from(&allowlist_proof_info)?
+    };
+
+    if allowlist_proof.entry != entry {
+        allowlist_proof.entry = entry;
+        allowlist_proof.proof.clear();
+    }
+    allowlist_proof.proof.extend(proof);
+    if allowlist_proof.proof.len() > MAX_ALLOWLIST_PROOF_LENGTH {
+        return err!(CandyError::AllowlistProofTooLong);
+    }
+    allowlist_proof.exit(&crate::id())
+}
+
+pub fn handle_close_allowlist_proof(
+    _ctx: Context<CloseAllowlistProof>,
+    _phase_index: u8,
+) -> Result<()> {
+    Ok(())
+}
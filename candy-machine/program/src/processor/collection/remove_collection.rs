@@ -2,10 +2,7 @@ use anchor_lang::prelude::*;
 use mpl_token_metadata::{instruction::revoke_collection_authority, state::Metadata};
 use solana_program::program::invoke;
 
-use crate::{
-    cmp_pubkeys, constants::COLLECTIONS_FEATURE_INDEX, remove_feature_flag, CandyError,
-    CandyMachine, CollectionPDA,
-};
+use crate::{cmp_pubkeys, CandyError, CandyMachine, CandyMachineFeatures, CollectionPDA, Feature};
 
 /// Set the collection PDA for the candy machine
 #[derive(Accounts)]
@@ -68,6 +65,8 @@ pub fn handle_remove_collection(ctx: Context<RemoveCollection>) -> Result<()> {
         ),
         revoke_collection_infos.as_slice(),
     )?;
-    remove_feature_flag(&mut candy_machine.data.uuid, COLLECTIONS_FEATURE_INDEX);
-    Ok(())
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut features = CandyMachineFeatures::load(&candy_machine_info, &candy_machine.data)?;
+    features.remove(Feature::Collections);
+    features.save(&candy_machine_info)
 }
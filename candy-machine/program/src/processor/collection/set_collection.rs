@@ -6,9 +6,8 @@ use mpl_token_metadata::{
 use solana_program::program::invoke;
 
 use crate::{
-    assert_master_edition, cmp_pubkeys,
-    constants::{COLLECTIONS_FEATURE_INDEX, COLLECTION_PDA_SIZE},
-    set_feature_flag, CandyError, CandyMachine, CollectionPDA,
+    assert_master_edition, cmp_pubkeys, constants::COLLECTION_PDA_SIZE, CandyError, CandyMachine,
+    CandyMachineFeatures, CandyMachineSettings, CollectionPDA, Feature,
 };
 
 /// Set the collection PDA for the candy machine
@@ -60,6 +59,11 @@ pub fn handle_set_collection(ctx: Context<SetCollection>) -> Result<()> {
     if !candy_machine.data.retain_authority {
         return err!(CandyError::CandyCollectionRequiresRetainAuthority);
     }
+    if CandyMachineSettings::load(&candy_machine.to_account_info(), &candy_machine.data)?
+        .on_chain_reveal
+    {
+        return err!(CandyError::InvalidOnChainReveal);
+    }
     assert_master_edition(&metadata, &edition)?;
     if authority_record.data_is_empty() {
         let approve_collection_infos = vec![
@@ -113,6 +117,8 @@ pub fn handle_set_collection(ctx: Context<SetCollection>) -> Result<()> {
     collection_pda_object.mint = mint.key();
     collection_pda_object.candy_machine = candy_machine.key();
     collection_pda_object.try_serialize(&mut data_ref)?;
-    set_feature_flag(&mut candy_machine.data.uuid, COLLECTIONS_FEATURE_INDEX);
-    Ok(())
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut features = CandyMachineFeatures::load(&candy_machine_info, &candy_machine.data)?;
+    features.set(Feature::Collections);
+    features.save(&candy_machine_info)
 }
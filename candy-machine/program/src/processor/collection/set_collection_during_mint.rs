@@ -63,8 +63,11 @@ pub fn handle_set_collection_during_mint(ctx: Context<SetCollectionDuringMint>)
         return Ok(());
     }
 
+    // mint_nft or mint_nft_with_payment
     let discriminator = &previous_instruction.data[0..8];
-    if discriminator != [211, 57, 6, 167, 15, 219, 35, 251] {
+    if discriminator != [211, 57, 6, 167, 15, 219, 35, 251]
+        && discriminator != [58, 176, 6, 207, 250, 176, 194, 85]
+    {
         msg!("Transaction had ix with data {:?}", discriminator);
         return Ok(());
     }
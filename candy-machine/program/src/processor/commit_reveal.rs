@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use solana_program::{
+    program::invoke,
+    system_instruction,
+    sysvar::{slot_hashes::SlotHashes, SysvarId},
+};
+
+use crate::{
+    get_revealed_slot_hash, CandyError, CandyMachine, CandyMachineSettings, MintCommitment,
+};
+
+/// Commit to mint from a commit-reveal candy machine.
+#[derive(Accounts)]
+pub struct CommitMint<'info> {
+    candy_machine: Account<'info, CandyMachine>,
+    #[account(
+        init,
+        seeds = [
+            MintCommitment::PREFIX.as_bytes(),
+            candy_machine.key().as_ref(),
+            payer.key().as_ref()
+        ],
+        bump,
+        space = MintCommitment::SIZE,
+        payer = payer
+    )]
+    mint_commitment: Account<'info, MintCommitment>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+/// Close a mint commitment that can no longer be revealed.
+#[derive(Accounts)]
+pub struct CloseMintCommitment<'info> {
+    #[account(mut)]
+    candy_machine: Account<'info, CandyMachine>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [
+            MintCommitment::PREFIX.as_bytes(),
+            candy_machine.key().as_ref(),
+            payer.key().as_ref()
+        ],
+        bump
+    )]
+    mint_commitment: Account<'info, MintCommitment>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    /// CHECK: account constraints checked in account trait
+    #[account(address = SlotHashes::id())]
+    recent_slothashes: UncheckedAccount<'info>,
+}
+
+pub fn handle_commit_mint(ctx: Context<CommitMint>) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    let settings =
+        CandyMachineSettings::load(&candy_machine.to_account_info(), &candy_machine.data)?;
+    if !settings.commit_reveal {
+        return err!(CandyError::CommitRevealNotEnabled);
+    }
+    // the bot tax is held by the commitment so letting it expire to pick again has a cost
+    let deposit = settings.bot_tax(None).lamports;
+    if deposit > 0 {
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.payer.key(),
+                &ctx.accounts.mint_commitment.key(),
+                deposit,
+            ),
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.mint_commitment.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    let mint_commitment = &mut ctx.accounts.mint_commitment;
+    mint_commitment.slot = Clock::get()?.slot;
+    mint_commitment.items_redeemed = candy_machine.items_redeemed;
+    mint_commitment.deposit = deposit;
+    Ok(())
+}
+
+pub fn handle_close_mint_commitment(ctx: Context<CloseMintCommitment>) -> Result<()> {
+    // closing a commitment that can still be revealed would let minters pick their item
+    let revealed = get_revealed_slot_hash(
+        &ctx.accounts.recent_slothashes.data.borrow(),
+        ctx.accounts.mint_commitment.slot,
+    );
+    match revealed {
+        Err(error) if error == error!(CandyError::MintCommitmentExpired) => {}
+        _ => return err!(CandyError::MintCommitmentNotExpired),
+    }
+
+    // the deposit of an expired commitment goes to the candy machine, only the rent is returned
+    let mint_commitment_info = ctx.accounts.mint_commitment.to_account_info();
+    let candy_machine_info = ctx.accounts.candy_machine.to_account_info();
+    let deposit = ctx.accounts.mint_commitment.deposit;
+    **mint_commitment_info.lamports.borrow_mut() = mint_commitment_info
+        .lamports()
+        .checked_sub(deposit)
+        .ok_or(CandyError::NumericalOverflowError)?;
+    **candy_machine_info.lamports.borrow_mut() = candy_machine_info
+        .lamports()
+        .checked_add(deposit)
+        .ok_or(CandyError::NumericalOverflowError)?;
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use mpl_token_metadata::instruction::{burn_nft, thaw_delegated_account};
+use solana_program::{
+    program::{invoke, invoke_signed},
+    program_option::COption,
+};
+
+use crate::{
+    assert_is_ata, cmp_pubkeys, release_minted_index, spl_token_transfer, CandyError, CandyMachine,
+    CandyMachineSettings, ConfigLinesLayout, FreezePDA, MintReceipt, RefundRecord,
+    TokenTransferParams,
+};
+
+/// Burn a frozen NFT and refund its price while the mint funds are locked in the FreezePDA
+#[derive(Accounts)]
+pub struct RefundNFT<'info> {
+    #[account(mut, seeds = [FreezePDA::PREFIX.as_bytes(), candy_machine.key().as_ref()], bump, has_one = candy_machine)]
+    freeze_pda: Account<'info, FreezePDA>,
+    #[account(mut)]
+    candy_machine: Box<Account<'info, CandyMachine>>,
+    #[account(mut, close = owner, seeds = [RefundRecord::PREFIX.as_bytes(), candy_machine.key().as_ref(), mint.key().as_ref()], bump)]
+    refund_record: Account<'info, RefundRecord>,
+    #[account(mut, has_one = mint, has_one = owner)]
+    token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    owner: Signer<'info>,
+    #[account(mut)]
+    mint: Account<'info, Mint>,
+    /// CHECK: account checked in CPI
+    #[account(mut)]
+    metadata: UncheckedAccount<'info>,
+    /// CHECK: account checked in CPI
+    #[account(mut)]
+    edition: UncheckedAccount<'info>,
+    token_program: Program<'info, Token>,
+    /// CHECK: checked in account constraints
+    #[account(address = mpl_token_metadata::id())]
+    token_metadata_program: UncheckedAccount<'info>,
+    system_program: Program<'info, System>,
+    // > Only needed if candy machine has a mint set
+    // freeze_ata (writable)
+    // owner_ata (writable)
+    // > Only needed if the candy machine has mint receipts, or the NFT was minted with one
+    // mint_receipt (writable)
+    // > Only needed if the NFT is a verified item of a sized collection
+    // collection_metadata (writable)
+}
+
+pub fn handle_refund_nft<'info>(ctx: Context<'_, '_, '_, 'info, RefundNFT<'info>>) -> Result<()> {
+    let freeze_pda = &mut ctx.accounts.freeze_pda;
+    let candy_machine = &mut ctx.accounts.candy_machine;
+    let refund_record = &ctx.accounts.refund_record;
+    let token_account = &ctx.accounts.token_account;
+    let owner = &ctx.accounts.owner;
+    let mint = &ctx.accounts.mint;
+    let edition = &ctx.accounts.edition;
+    let token_program = &ctx.accounts.token_program;
+    let token_metadata_program = &ctx.accounts.token_metadata_program;
+
+    let settings =
+        CandyMachineSettings::load(&candy_machine.to_account_info(), &candy_machine.data)?;
+    if !settings.freeze_refund {
+        return err!(CandyError::FreezeRefundNotEnabled);
+    }
+    // a sold out candy machine is thaw eligible, so a refund can never reopen it and
+    // push `thaw_eligible` back for the NFTs still frozen
+    if freeze_pda.thaw_eligible(Clock::get()?.unix_timestamp, candy_machine) {
+        return err!(CandyError::RefundWindowClosed);
+    }
+    if !token_account.is_frozen() || token_account.delegate != COption::Some(freeze_pda.key()) {
+        return err!(CandyError::NftNotFrozen);
+    }
+
+    let candy_key = candy_machine.key();
+    let freeze_seeds = [
+        FreezePDA::PREFIX.as_bytes(),
+        candy_key.as_ref(),
+        &[*ctx.bumps.get("freeze_pda").unwrap()],
+    ];
+    invoke_signed(
+        &thaw_delegated_account(
+            mpl_token_metadata::ID,
+            freeze_pda.key(),
+            token_account.key(),
+            edition.key(),
+            mint.key(),
+        ),
+        &[
+            freeze_pda.to_account_info(),
+            token_account.to_account_info(),
+            edition.to_account_info(),
+            mint.to_account_info(),
+            token_program.to_account_info(),
+            token_metadata_program.to_account_info(),
+        ],
+        &[&freeze_seeds],
+    )?;
+
+    let (token_accounts, collection_accounts) = if candy_machine.token_mint.is_some() {
+        if ctx.remaining_accounts.len() < 2 {
+            return err!(CandyError::MissingRemoveFreezeTokenAccounts);
+        }
+        ctx.remaining_accounts.split_at(2)
+    } else {
+        (&[][..], ctx.remaining_accounts)
+    };
+    let (mint_receipt_key, _) = Pubkey::find_program_address(
+        &[
+            MintReceipt::PREFIX.as_bytes(),
+            candy_key.as_ref(),
+            mint.key().as_ref(),
+        ],
+        &crate::id(),
+    );
+    let (mint_receipt, collection_accounts) = match collection_accounts.first() {
+        Some(info) if cmp_pubkeys(info.key, &mint_receipt_key) => {
+            (Some(info), &collection_accounts[1..])
+        }
+        _ if settings.mint_receipts => return err!(CandyError::MissingRemainingAccount),
+        _ => (None, collection_accounts),
+    };
+    let collection_metadata = collection_accounts.first();
+    let mut burn_infos = vec![
+        ctx.accounts.metadata.to_account_info(),
+        owner.to_account_info(),
+        mint.to_account_info(),
+        token_account.to_account_info(),
+        edition.to_account_info(),
+        token_program.to_account_info(),
+        token_metadata_program.to_account_info(),
+    ];
+    if let Some(collection_metadata) = collection_metadata {
+        burn_infos.push(collection_metadata.clone());
+    }
+    invoke(
+        &burn_nft(
+            mpl_token_metadata::ID,
+            ctx.accounts.metadata.key(),
+            owner.key(),
+            mint.key(),
+            token_account.key(),
+            edition.key(),
+            token_program.key(),
+            collection_metadata.map(|info| info.key()),
+        ),
+        &burn_infos,
+    )?;
+
+    if let Some(token_mint) = candy_machine.token_mint {
+        let freeze_ata = &token_accounts[0];
+        let owner_ata = &token_accounts[1];
+        assert_is_ata(freeze_ata, &freeze_pda.key(), &token_mint)?;
+        assert_is_ata(owner_ata, &owner.key(), &token_mint)?;
+        spl_token_transfer(TokenTransferParams {
+            source: freeze_ata.clone(),
+            destination: owner_ata.clone(),
+            authority: freeze_pda.to_account_info(),
+            authority_signer_seeds: &freeze_seeds,
+            token_program: token_program.to_account_info(),
+            amount: refund_record.amount,
+        })?;
+    } else {
+        // the freeze PDA is owned by the program, lamports are moved directly
+        let freeze_pda_info = freeze_pda.to_account_info();
+        let rent_exempt = Rent::get()?.minimum_balance(freeze_pda_info.data_len());
+        if freeze_pda_info.lamports() < rent_exempt + refund_record.amount {
+            return err!(CandyError::NotEnoughSOL);
+        }
+        **freeze_pda_info.try_borrow_mut_lamports()? -= refund_record.amount;
+        **owner.to_account_info().try_borrow_mut_lamports()? += refund_record.amount;
+    }
+
+    // the receipt of the burned NFT is closed, the NFT stayed frozen in the account of
+    // the payer so its rent goes back to the owner
+    if let Some(mint_receipt_info) = mint_receipt {
+        if !mint_receipt_info.data_is_empty() {
+            let mint_receipt: Account<MintReceipt> = Account::try_from(mint_receipt_info)?;
+            mint_receipt.close(owner.to_account_info())?;
+        }
+    }
+
+    // the burned item can be minted again
+    freeze_pda.frozen_count = freeze_pda
+        .frozen_count
+        .checked_sub(1)
+        .ok_or(CandyError::NumericalOverflowError)?;
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut data = candy_machine_info.data.borrow_mut();
+    let layout = ConfigLinesLayout::load(&candy_machine.data, &data)?;
+    release_minted_index(
+        &mut data,
+        &layout,
+        candy_machine.data.items_available as usize,
+        refund_record.line_index as usize,
+    )?;
+    drop(data);
+    candy_machine.items_redeemed = candy_machine
+        .items_redeemed
+        .checked_sub(1)
+        .ok_or(CandyError::NumericalOverflowError)?;
+    Ok(())
+}
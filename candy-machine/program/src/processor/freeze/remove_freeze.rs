@@ -1,9 +1,6 @@
 use anchor_lang::{prelude::*, AccountsClose};
 
-use crate::{
-    constants::{FREEZE_FEATURE_INDEX, FREEZE_LOCK_FEATURE_INDEX},
-    remove_feature_flag, CandyError, CandyMachine, FreezePDA,
-};
+use crate::{CandyError, CandyMachine, CandyMachineFeatures, Feature, FreezePDA};
 
 /// Removes the freeze flag from candy machine without closing the freeze pda unless no NFTs have been minted
 #[derive(Accounts)]
@@ -20,7 +17,9 @@ pub fn handle_remove_freeze(ctx: Context<RemoveFreeze>) -> Result<()> {
     let candy_machine = &mut ctx.accounts.candy_machine;
     let freeze_pda = &mut ctx.accounts.freeze_pda;
     freeze_pda.allow_thaw = true;
-    remove_feature_flag(&mut candy_machine.data.uuid, FREEZE_FEATURE_INDEX);
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut features = CandyMachineFeatures::load(&candy_machine_info, &candy_machine.data)?;
+    features.remove(Feature::Freeze);
 
     // Closes the account to enable editing if minting hasn't started
     if candy_machine
@@ -28,7 +27,7 @@ pub fn handle_remove_freeze(ctx: Context<RemoveFreeze>) -> Result<()> {
         .is_ok()
     {
         freeze_pda.close(ctx.accounts.authority.to_account_info())?;
-        remove_feature_flag(&mut candy_machine.data.uuid, FREEZE_LOCK_FEATURE_INDEX);
+        features.remove(Feature::FreezeLock);
     }
-    Ok(())
+    features.save(&candy_machine_info)
 }
@@ -1,9 +1,8 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    assert_is_ata,
-    constants::{FREEZE_FEATURE_INDEX, FREEZE_LOCK_FEATURE_INDEX, MAX_FREEZE_TIME},
-    set_feature_flag, CandyError, CandyMachine, FreezePDA,
+    assert_is_ata, constants::MAX_FREEZE_TIME, CandyError, CandyMachine, CandyMachineFeatures,
+    CandyMachineSettings, Feature, FreezePDA,
 };
 
 /// Set the Freeze PDA for the candy machine
@@ -28,6 +27,11 @@ pub fn handle_set_freeze(ctx: Context<SetFreeze>, freeze_time: i64) -> Result<()
     if freeze_time > MAX_FREEZE_TIME {
         return err!(CandyError::EnteredFreezeIsMoreThanMaxFreeze);
     }
+    let settings =
+        CandyMachineSettings::load(&candy_machine.to_account_info(), &candy_machine.data)?;
+    if !settings.payment_options.is_empty() {
+        return err!(CandyError::NoPaymentOptionsWithFreeze);
+    }
     freeze_pda.init(candy_machine.key(), None, freeze_time);
 
     if let Some(mint_pubkey) = candy_machine.token_mint {
@@ -38,7 +42,9 @@ pub fn handle_set_freeze(ctx: Context<SetFreeze>, freeze_time: i64) -> Result<()
         assert_is_ata(freeze_ata, freeze_pda.to_account_info().key, &mint_pubkey)
             .map_err(|_| CandyError::IncorrectFreezeAta)?;
     }
-    set_feature_flag(&mut candy_machine.data.uuid, FREEZE_FEATURE_INDEX);
-    set_feature_flag(&mut candy_machine.data.uuid, FREEZE_LOCK_FEATURE_INDEX);
-    Ok(())
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut features = CandyMachineFeatures::load(&candy_machine_info, &candy_machine.data)?;
+    features.set(Feature::Freeze);
+    features.set(Feature::FreezeLock);
+    features.save(&candy_machine_info)
 }
@@ -3,7 +3,13 @@ use anchor_lang::{
     system_program::{transfer, Transfer},
 };
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use mpl_token_metadata::instruction::thaw_delegated_account;
+use mpl_token_metadata::{
+    instruction::{
+        builders::{RevokeBuilder, UnlockBuilder},
+        thaw_delegated_account, InstructionBuilder, RevokeArgs, UnlockArgs,
+    },
+    state::{TokenMetadataAccount, TokenRecord, TokenState},
+};
 use solana_program::program::{invoke, invoke_signed};
 use spl_token::instruction::revoke;
 
@@ -31,6 +37,12 @@ pub struct ThawNFT<'info> {
     #[account(address = mpl_token_metadata::id())]
     token_metadata_program: UncheckedAccount<'info>,
     system_program: Program<'info, System>,
+    // > Only needed for programmable NFTs
+    // metadata (writable)
+    // token_record (writable)
+    // sysvar_instructions
+    // authorization_rules_program // Only needed if the NFT has a rule set
+    // authorization_rules // Only needed if the NFT has a rule set
 }
 
 pub fn handle_thaw_nft(ctx: Context<ThawNFT>) -> Result<()> {
@@ -61,6 +73,102 @@ pub fn handle_thaw_nft(ctx: Context<ThawNFT>) -> Result<()> {
         candy_machine.key.as_ref(),
         &[*ctx.bumps.get("freeze_pda").unwrap()],
     ];
+    if let [metadata, token_record, sysvar_instructions, rule_set_accounts @ ..] =
+        ctx.remaining_accounts
+    {
+        // programmable NFTs stay frozen, they are unlocked and their utility delegate revoked
+        let token_record_data = TokenRecord::from_account_info(token_record)?;
+        let mut thaw_infos = vec![
+            freeze_pda.to_account_info(),
+            payer.to_account_info(),
+            owner.to_account_info(),
+            token_account.to_account_info(),
+            token_record.clone(),
+            metadata.clone(),
+            edition.to_account_info(),
+            mint.to_account_info(),
+            token_metadata_program.to_account_info(),
+            token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            sysvar_instructions.clone(),
+        ];
+        thaw_infos.extend(rule_set_accounts.iter().cloned());
+        let rule_set_accounts = match rule_set_accounts {
+            [authorization_rules_program, authorization_rules] => {
+                Some((authorization_rules_program.key(), authorization_rules.key()))
+            }
+            _ => None,
+        };
+
+        if token_record_data.state == TokenState::Locked {
+            msg!("Token record is locked! Now attempting to unlock!");
+            let mut unlock_builder = UnlockBuilder::new();
+            unlock_builder
+                .authority(freeze_pda.key())
+                .token_owner(owner.key())
+                .token(token_account.key())
+                .mint(mint.key())
+                .metadata(metadata.key())
+                .edition(edition.key())
+                .token_record(token_record.key())
+                .payer(payer.key())
+                .system_program(ctx.accounts.system_program.key())
+                .sysvar_instructions(sysvar_instructions.key())
+                .spl_token_program(token_program.key());
+            if let Some((authorization_rules_program, authorization_rules)) = rule_set_accounts {
+                unlock_builder
+                    .authorization_rules_program(authorization_rules_program)
+                    .authorization_rules(authorization_rules);
+            }
+            invoke_signed(
+                &unlock_builder
+                    .build(UnlockArgs::V1 {
+                        authorization_data: None,
+                    })
+                    .unwrap()
+                    .instruction(),
+                &thaw_infos,
+                &[&freeze_seeds],
+            )?;
+            pay_thaw_fee(freeze_pda, payer, &ctx.accounts.system_program)?;
+        } else {
+            msg!("Token record is not locked!");
+        }
+        if cmp_pubkeys(&payer.key(), &owner.key())
+            && token_record_data.delegate == Some(freeze_pda.key())
+        {
+            msg!("Revoking authority");
+            let mut revoke_builder = RevokeBuilder::new();
+            revoke_builder
+                .delegate(freeze_pda.key())
+                .metadata(metadata.key())
+                .master_edition(edition.key())
+                .token_record(token_record.key())
+                .mint(mint.key())
+                .token(token_account.key())
+                .authority(payer.key())
+                .payer(payer.key())
+                .system_program(ctx.accounts.system_program.key())
+                .sysvar_instructions(sysvar_instructions.key())
+                .spl_token_program(token_program.key());
+            if let Some((authorization_rules_program, authorization_rules)) = rule_set_accounts {
+                revoke_builder
+                    .authorization_rules_program(authorization_rules_program)
+                    .authorization_rules(authorization_rules);
+            }
+            invoke(
+                &revoke_builder
+                    .build(RevokeArgs::UtilityV1)
+                    .unwrap()
+                    .instruction(),
+                &thaw_infos,
+            )?;
+        } else {
+            msg!("Cannot revoke delegate authority: token account owner is not signer. Re-run as owner to revoke.");
+        }
+        return Ok(());
+    }
+
     if token_account.is_frozen() {
         msg!("Token account is frozen! Now attempting to thaw!");
         invoke_signed(
@@ -81,22 +189,7 @@ pub fn handle_thaw_nft(ctx: Context<ThawNFT>) -> Result<()> {
             ],
             &[&freeze_seeds],
         )?;
-        if freeze_pda.freeze_fee > 0 && freeze_pda.frozen_count > 0 {
-            transfer(
-                CpiContext::new(
-                    ctx.accounts.system_program.to_account_info(),
-                    Transfer {
-                        from: freeze_pda.to_account_info(),
-                        to: payer.to_account_info(),
-                    },
-                ),
-                freeze_pda.freeze_fee,
-            )?;
-        }
-        // if everything is correct, this saturating sub shouldn't be needed.
-        // Just an extra precaution to allow unfreezing if something unexpected were to
-        // happen to the freeze count to allow everyone to still unfreeze
-        freeze_pda.frozen_count = freeze_pda.frozen_count.saturating_sub(1);
+        pay_thaw_fee(freeze_pda, payer, &ctx.accounts.system_program)?;
     } else {
         msg!("Token account is not frozen!");
     }
@@ -111,3 +204,27 @@ pub fn handle_thaw_nft(ctx: Context<ThawNFT>) -> Result<()> {
     }
     Ok(())
 }
+
+fn pay_thaw_fee<'info>(
+    freeze_pda: &mut Account<'info, FreezePDA>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if freeze_pda.freeze_fee > 0 && freeze_pda.frozen_count > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: freeze_pda.to_account_info(),
+                    to: payer.to_account_info(),
+                },
+            ),
+            freeze_pda.freeze_fee,
+        )?;
+    }
+    // if everything is correct, this saturating sub shouldn't be needed.
+    // Just an extra precaution to allow unfreezing if something unexpected were to
+    // happen to the freeze count to allow everyone to still unfreeze
+    freeze_pda.frozen_count = freeze_pda.frozen_count.saturating_sub(1);
+    Ok(())
+}
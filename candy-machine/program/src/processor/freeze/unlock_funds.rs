@@ -2,10 +2,8 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{close_account, CloseAccount, Token};
 
 use crate::{
-    assert_is_ata,
-    constants::{FREEZE_FEATURE_INDEX, FREEZE_LOCK_FEATURE_INDEX},
-    remove_feature_flag, spl_token_transfer, CandyError, CandyMachine, FreezePDA,
-    TokenTransferParams,
+    assert_is_ata, spl_token_transfer, CandyError, CandyMachine, CandyMachineFeatures, Feature,
+    FreezePDA, TokenTransferParams,
 };
 
 /// Unlocks the funds from mint stuck in the FreezePDA
@@ -85,7 +83,9 @@ pub fn handle_unlock_funds<'info>(
         ))?;
     }
 
-    remove_feature_flag(&mut candy_machine.data.uuid, FREEZE_FEATURE_INDEX);
-    remove_feature_flag(&mut candy_machine.data.uuid, FREEZE_LOCK_FEATURE_INDEX);
-    Ok(())
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut features = CandyMachineFeatures::load(&candy_machine_info, &candy_machine.data)?;
+    features.remove(Feature::Freeze);
+    features.remove(Feature::FreezeLock);
+    features.save(&candy_machine_info)
 }
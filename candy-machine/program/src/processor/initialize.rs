@@ -4,8 +4,9 @@ use spl_token::state::Mint;
 
 use crate::{
     assert_initialized, assert_owned_by, cmp_pubkeys,
-    constants::{CONFIG_ARRAY_START, CONFIG_LINE_SIZE},
-    CandyError, CandyMachine, CandyMachineData,
+    constants::{CONFIG_ARRAY_START, FEATURES_START},
+    CandyError, CandyMachine, CandyMachineData, CandyMachineFeatures, ConfigLineSettings,
+    ConfigLinesLayout, Feature,
 };
 
 /// Create a new candy machine.
@@ -13,7 +14,23 @@ use crate::{
 #[instruction(data: CandyMachineData)]
 pub struct InitializeCandyMachine<'info> {
     /// CHECK: account constraints checked in account trait
-    #[account(zero, rent_exempt = skip, constraint = candy_machine.to_account_info().owner == program_id && candy_machine.to_account_info().data_len() >= get_space_for_candy(data)?)]
+    #[account(zero, rent_exempt = skip, constraint = candy_machine.to_account_info().owner == program_id && candy_machine.to_account_info().data_len() >= get_space_for_candy(&data, &ConfigLinesLayout::new(None))?)]
+    candy_machine: UncheckedAccount<'info>,
+    /// CHECK: wallet can be any account and is not written to or read
+    wallet: UncheckedAccount<'info>,
+    /// CHECK: authority can be any account and is not written to or read
+    authority: UncheckedAccount<'info>,
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+    rent: Sysvar<'info, Rent>,
+}
+
+/// Create a new candy machine storing config line suffixes only.
+#[derive(Accounts)]
+#[instruction(data: CandyMachineData, config_line_settings: ConfigLineSettings)]
+pub struct InitializeCompressedCandyMachine<'info> {
+    /// CHECK: account constraints checked in account trait
+    #[account(zero, rent_exempt = skip, constraint = candy_machine.to_account_info().owner == program_id && candy_machine.to_account_info().data_len() >= get_space_for_candy(&data, &ConfigLinesLayout::new(Some(config_line_settings.clone())))?)]
     candy_machine: UncheckedAccount<'info>,
     /// CHECK: wallet can be any account and is not written to or read
     wallet: UncheckedAccount<'info>,
@@ -28,29 +45,70 @@ pub fn handle_initialize_candy_machine(
     ctx: Context<InitializeCandyMachine>,
     data: CandyMachineData,
 ) -> Result<()> {
-    let candy_machine_account = &mut ctx.accounts.candy_machine;
+    initialize_candy_machine(
+        &ctx.accounts.candy_machine,
+        &ctx.accounts.wallet,
+        &ctx.accounts.authority,
+        ctx.remaining_accounts,
+        data,
+        None,
+    )
+}
+
+pub fn handle_initialize_compressed_candy_machine(
+    ctx: Context<InitializeCompressedCandyMachine>,
+    data: CandyMachineData,
+    config_line_settings: ConfigLineSettings,
+) -> Result<()> {
+    if data.hidden_settings.is_some() {
+        return err!(CandyError::HiddenSettingsConfigsDoNotHaveConfigLines);
+    }
+    if !config_line_settings.is_valid() {
+        return err!(CandyError::InvalidConfigLineSettings);
+    }
+    initialize_candy_machine(
+        &ctx.accounts.candy_machine,
+        &ctx.accounts.wallet,
+        &ctx.accounts.authority,
+        ctx.remaining_accounts,
+        data,
+        Some(config_line_settings),
+    )
+}
 
+fn initialize_candy_machine(
+    candy_machine_account: &AccountInfo,
+    wallet: &AccountInfo,
+    authority: &AccountInfo,
+    remaining_accounts: &[AccountInfo],
+    data: CandyMachineData,
+    config_line_settings: Option<ConfigLineSettings>,
+) -> Result<()> {
     if data.uuid.len() != 6 {
         return err!(CandyError::UuidMustBeExactly6Length);
     }
 
     let mut candy_machine = CandyMachine {
         data,
-        authority: ctx.accounts.authority.key(),
-        wallet: ctx.accounts.wallet.key(),
+        authority: authority.key(),
+        wallet: wallet.key(),
         token_mint: None,
         items_redeemed: 0,
     };
 
     candy_machine.data.uuid = "000000".to_string();
+    let mut features = CandyMachineFeatures::default();
+    if config_line_settings.is_some() {
+        features.set(Feature::ConfigLineSettings);
+    }
 
-    if !ctx.remaining_accounts.is_empty() {
-        let token_mint_info = &ctx.remaining_accounts[0];
+    if !remaining_accounts.is_empty() {
+        let token_mint_info = &remaining_accounts[0];
         let _token_mint: Mint = assert_initialized(token_mint_info)?;
-        let token_account: spl_token::state::Account = assert_initialized(&ctx.accounts.wallet)?;
+        let token_account: spl_token::state::Account = assert_initialized(wallet)?;
 
         assert_owned_by(token_mint_info, &spl_token::id())?;
-        assert_owned_by(&ctx.accounts.wallet, &spl_token::id())?;
+        assert_owned_by(wallet, &spl_token::id())?;
 
         if !cmp_pubkeys(&token_account.mint, &token_mint_info.key()) {
             return err!(CandyError::MintMismatch);
@@ -74,18 +132,28 @@ pub fn handle_initialize_candy_machine(
 
     let mut new_data = CandyMachine::discriminator().try_to_vec().unwrap();
     new_data.append(&mut candy_machine.try_to_vec().unwrap());
+    if new_data.len() > FEATURES_START {
+        return err!(CandyError::CandyMachineDataTooLarge);
+    }
     let mut data = candy_machine_account.data.borrow_mut();
     // god forgive me couldnt think of better way to deal with this
     for i in 0..new_data.len() {
         data[i] = new_data[i];
     }
 
+    if let Some(config_line_settings) = &config_line_settings {
+        let settings_data = config_line_settings
+            .try_to_vec()
+            .map_err(|_| CandyError::InvalidConfigLineSettings)?;
+        data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + settings_data.len()]
+            .copy_from_slice(&settings_data);
+    }
+
     // only if we are not using hidden settings we will have space for
     // the config lines
     if candy_machine.data.hidden_settings.is_none() {
-        let vec_start = CONFIG_ARRAY_START
-            + 4
-            + (candy_machine.data.items_available as usize) * CONFIG_LINE_SIZE;
+        let vec_start = ConfigLinesLayout::new(config_line_settings)
+            .line_position(candy_machine.data.items_available as usize);
         let as_bytes = (candy_machine
             .data
             .items_available
@@ -96,23 +164,17 @@ pub fn handle_initialize_candy_machine(
             data[vec_start + i] = as_bytes[i]
         }
     }
+    drop(data);
 
-    Ok(())
+    features.save(candy_machine_account)
 }
 
-fn get_space_for_candy(data: CandyMachineData) -> Result<usize> {
+/// Size of the candy machine data and config lines, mint settings are stored after it.
+pub fn get_space_for_candy(data: &CandyMachineData, layout: &ConfigLinesLayout) -> Result<usize> {
     let num = if data.hidden_settings.is_some() {
         CONFIG_ARRAY_START
     } else {
-        CONFIG_ARRAY_START
-            + 4
-            + (data.items_available as usize) * CONFIG_LINE_SIZE
-            + 8
-            + 2 * ((data
-                .items_available
-                .checked_div(8)
-                .ok_or(CandyError::NumericalOverflowError)?
-                + 1) as usize)
+        layout.end(data.items_available as usize)
     };
 
     Ok(num)
@@ -1,14 +1,17 @@
-use std::{cell::RefMut, ops::Deref};
+use std::ops::Deref;
 
 use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 use arrayref::array_ref;
 use mpl_token_metadata::{
     instruction::{
+        builders::{CreateBuilder, DelegateBuilder, LockBuilder, MintBuilder, UpdateBuilder},
         create_master_edition_v3, create_metadata_accounts_v3, freeze_delegated_account,
-        update_metadata_accounts_v2,
+        update_metadata_accounts_v2, CollectionDetailsToggle, CollectionToggle, CreateArgs,
+        DelegateArgs, InstructionBuilder, LockArgs, MintArgs, RuleSetToggle, UpdateArgs,
+        UsesToggle,
     },
-    state::{MAX_NAME_LENGTH, MAX_URI_LENGTH},
+    state::{AssetData, PrintSupply, TokenStandard},
 };
 use solana_gateway::{
     state::{GatewayTokenAccess, InPlaceGatewayToken},
@@ -16,21 +19,24 @@ use solana_gateway::{
 };
 use solana_program::{
     clock::Clock,
+    keccak,
     program::{invoke, invoke_signed},
     serialize_utils::{read_pubkey, read_u16},
     system_instruction, sysvar,
     sysvar::{instructions::get_instruction_relative, SysvarId},
 };
+use spl_associated_token_account::get_associated_token_address;
 use spl_token::instruction::approve;
 
 use crate::{
     constants::{
-        A_TOKEN, BLOCK_HASHES, BOT_FEE, COLLECTIONS_FEATURE_INDEX, COMPUTE_BUDGET,
-        CONFIG_ARRAY_START, CONFIG_LINE_SIZE, CUPCAKE_ID, EXPIRE_OFFSET, FREEZE_FEATURE_INDEX,
-        GUMDROP_ID, PREFIX,
+        A_TOKEN, BLOCK_HASHES, COMPUTE_BUDGET, CUPCAKE_ID, EXPIRE_OFFSET, GUMDROP_ID,
+        MAX_MINTS_PER_TRANSACTION, PREFIX,
     },
     utils::*,
-    CandyError, CandyMachine, CandyMachineData, ConfigLine, EndSettingType, FreezePDA,
+    CandyError, CandyMachine, CandyMachineData, CandyMachineFeatures, CandyMachineSettings,
+    ConfigLine, ConfigLinesLayout, EndSettingType, Feature, FreezePDA, MintCommitment, MintCounter,
+    MintEvent, MintPhase, MintReceipt, Payment, PaymentKind, PaymentOption, RefundRecord,
     WhitelistMintMode, WhitelistMintSettings,
 };
 
@@ -77,6 +83,16 @@ pub struct MintNFT<'info> {
     /// CHECK: account constraints checked in account trait
     #[account(address = sysvar::instructions::id())]
     instruction_sysvar_account: UncheckedAccount<'info>,
+    // > Only needed if the active mint phase has a gate mint
+    // gate_token_account
+    // > Only needed if the active mint phase has an allowlist
+    // allowlist_proof
+    // > Only needed if the active mint phase has a mint limit or an allowlist
+    // mint_counter (writable)
+    // > Only needed if candy machine has a mint limit
+    // wallet_mint_counter (writable)
+    // > Only needed if candy machine has commit-reveal enabled
+    // mint_commitment (writable)
     // > Only needed if candy machine has a gatekeeper
     // gateway_token
     // > Only needed if candy machine has a gatekeeper and it has expire_on_use set to true:
@@ -87,18 +103,74 @@ pub struct MintNFT<'info> {
     // > Only needed if candy machine has whitelist_mint_settings and mode is BurnEveryTime
     // whitelist_token_mint
     // whitelist_burn_authority
-    // > Only needed if candy machine has token mint
+    // > Only needed if candy machine has payment options, for each payment of the selected option
+    // Sol: destination (writable, only if the payment has a destination)
+    // Token: token_account_info (writable), destination_token_account (writable)
+    // BurnToken: token_account_info (writable), token_mint (writable)
+    // CollectionNft: nft_token_account (writable), nft_metadata, destination_token_account (writable)
+    // > Only needed if candy machine has token mint and no payment options
     // token_account_info
     // transfer_authority_info
     // > Only needed if freeze token until after mint is set to true
     // freeze_pda (writable)
     // nft_token_account (writable)
     // freeze_ata (writable) // Only needed if spl token mint is enabled
+    // refund_record (writable) // Only needed if freeze refunds are enabled
+    // > Only needed if candy machine mints programmable NFTs
+    // nft_token_account (writable)
+    // token_record (writable)
+    // associated_token_program
+    // authorization_rules_program // Only needed if a rule set is enabled
+    // authorization_rules // Only needed if a rule set is enabled
+    // > Only needed if candy machine has mint receipts
+    // mint_receipt (writable)
+    // > Only needed when minting several NFTs, for each NFT after the first one
+    // metadata (writable)
+    // mint (writable)
+    // master_edition (writable)
+    // nft_token_account (writable) // Only needed if freeze is set
+    // refund_record (writable) // Only needed if freeze refunds are enabled
+    // nft_token_account (writable) // Only needed for programmable NFTs
+    // token_record (writable) // Only needed for programmable NFTs
+    // mint_receipt (writable) // Only needed if candy machine has mint receipts
+}
+
+/// Accounts of one of the NFTs minted by the instruction.
+struct MintItem<'info> {
+    metadata: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    master_edition: AccountInfo<'info>,
+    /// nft_token_account and refund_record, only if freeze is set
+    freeze_accounts: Option<(AccountInfo<'info>, Option<AccountInfo<'info>>)>,
+    /// nft_token_account, token_record and the programmable accounts shared by the NFTs
+    programmable_accounts: Option<Vec<AccountInfo<'info>>>,
+    /// only if mint receipts are enabled
+    mint_receipt: Option<AccountInfo<'info>>,
 }
 
 pub fn handle_mint_nft<'info>(
     ctx: Context<'_, '_, '_, 'info, MintNFT<'info>>,
     creator_bump: u8,
+) -> Result<()> {
+    handle_mint_nfts(ctx, creator_bump, 0, 1)
+}
+
+/// Mint paying with the `payment_option` of the candy machine payment options.
+pub fn handle_mint_nft_with_payment<'info>(
+    ctx: Context<'_, '_, '_, 'info, MintNFT<'info>>,
+    creator_bump: u8,
+    payment_option: u8,
+) -> Result<()> {
+    handle_mint_nfts(ctx, creator_bump, payment_option, 1)
+}
+
+/// Mint `count` NFTs, the checks, bot tax, mint limits and payment apply once to the
+/// whole batch and the price is paid for each NFT.
+pub fn handle_mint_nfts<'info>(
+    ctx: Context<'_, '_, '_, 'info, MintNFT<'info>>,
+    creator_bump: u8,
+    payment_option: u8,
+    count: u8,
 ) -> Result<()> {
     let candy_machine = &mut ctx.accounts.candy_machine;
     let candy_machine_creator = &ctx.accounts.candy_machine_creator;
@@ -118,24 +190,92 @@ pub fn handle_mint_nft<'info>(
         return err!(CandyError::MetadataAccountMustBeEmpty);
     }
 
-    if get_expected_remaining_accounts_count(candy_machine) > ctx.remaining_accounts.len() {
+    let features =
+        CandyMachineFeatures::load(&candy_machine.to_account_info(), &candy_machine.data)?;
+    let settings =
+        CandyMachineSettings::load(&candy_machine.to_account_info(), &candy_machine.data)?;
+    let active_phase = settings.active_phase(clock.unix_timestamp);
+    let payment_option_index = payment_option;
+    let payment_option = if settings.payment_options.is_empty() {
+        if payment_option != 0 {
+            return err!(CandyError::InvalidPaymentOption);
+        }
+        None
+    } else {
+        Some(
+            settings
+                .payment_options
+                .get(payment_option as usize)
+                .ok_or(CandyError::InvalidPaymentOption)?,
+        )
+    };
+
+    if count == 0 || count > MAX_MINTS_PER_TRANSACTION {
+        return err!(CandyError::InvalidMintCount);
+    }
+    // commitments, collection verification and NFT payments are made for a single NFT
+    if count > 1
+        && (settings.commit_reveal
+            || features.is_active(Feature::Collections)
+            || payment_option.map_or(false, |option| {
+                option
+                    .payments
+                    .iter()
+                    .any(|payment| matches!(payment.kind, PaymentKind::CollectionNft(_)))
+            }))
+    {
+        return err!(CandyError::BatchMintNotSupported);
+    }
+
+    let bot_tax = settings.bot_tax(active_phase.map(|(_, phase)| phase));
+    let bot_tax_destination = if bot_tax.pay_to_wallet && candy_machine.token_mint.is_none() {
+        wallet.to_account_info()
+    } else {
+        ctx.accounts.candy_machine.to_account_info()
+    };
+
+    // the accounts of the NFTs after the first one follow the other remaining accounts
+    let items_accounts_start = get_expected_remaining_accounts_count(
+        candy_machine,
+        &features,
+        &settings,
+        active_phase.map(|(_, phase)| phase),
+        payment_option,
+    );
+    let item_accounts_count = get_item_accounts_count(&features, &settings);
+    if items_accounts_start + (count as usize - 1) * item_accounts_count
+        > ctx.remaining_accounts.len()
+    {
         punish_bots(
             CandyError::IncorrectRemainingAccountsLen,
             payer.to_account_info(),
-            ctx.accounts.candy_machine.to_account_info(),
+            bot_tax_destination.clone(),
             ctx.accounts.system_program.to_account_info(),
-            BOT_FEE,
+            &bot_tax,
         )?;
         return Ok(());
     }
 
-    if candy_machine.items_redeemed >= candy_machine.data.items_available {
+    let items_accounts = ctx
+        .remaining_accounts
+        .get(items_accounts_start..)
+        .ok_or(CandyError::MissingRemainingAccount)?;
+    for index in 1..count as usize {
+        let metadata = items_accounts
+            .get((index - 1) * item_accounts_count)
+            .ok_or(CandyError::MissingRemainingAccount)?;
+        if !metadata.data_is_empty() {
+            return err!(CandyError::MetadataAccountMustBeEmpty);
+        }
+    }
+
+    if candy_machine.items_redeemed + count as u64 > candy_machine.data.items_available {
         punish_bots(
             CandyError::CandyMachineEmpty,
             payer.to_account_info(),
-            ctx.accounts.candy_machine.to_account_info(),
+            bot_tax_destination.clone(),
             ctx.accounts.system_program.to_account_info(),
-            BOT_FEE,
+            &bot_tax,
         )?;
         return Ok(());
     }
@@ -156,9 +296,9 @@ pub fn handle_mint_nft<'info>(
         punish_bots(
             CandyError::SuspiciousTransaction,
             payer.to_account_info(),
-            ctx.accounts.candy_machine.to_account_info(),
+            bot_tax_destination.clone(),
             ctx.accounts.system_program.to_account_info(),
-            BOT_FEE,
+            &bot_tax,
         )?;
         return Ok(());
     }
@@ -177,13 +317,13 @@ pub fn handle_mint_nft<'info>(
             }
         }
         Err(_) => {
-            if is_feature_active(&candy_machine.data.uuid, COLLECTIONS_FEATURE_INDEX) {
+            if features.is_active(Feature::Collections) {
                 punish_bots(
                     CandyError::MissingSetCollectionDuringMint,
                     payer.to_account_info(),
-                    ctx.accounts.candy_machine.to_account_info(),
+                    bot_tax_destination.clone(),
                     ctx.accounts.system_program.to_account_info(),
-                    BOT_FEE,
+                    &bot_tax,
                 )?;
                 return Ok(());
             }
@@ -212,9 +352,9 @@ pub fn handle_mint_nft<'info>(
             punish_bots(
                 CandyError::SuspiciousTransaction,
                 payer.to_account_info(),
-                ctx.accounts.candy_machine.to_account_info(),
+                bot_tax_destination.clone(),
                 ctx.accounts.system_program.to_account_info(),
-                BOT_FEE,
+                &bot_tax,
             )?;
             return Ok(());
         }
@@ -230,22 +370,22 @@ pub fn handle_mint_nft<'info>(
                     punish_bots(
                         CandyError::CandyMachineNotLive,
                         payer.to_account_info(),
-                        ctx.accounts.candy_machine.to_account_info(),
+                        bot_tax_destination.clone(),
                         ctx.accounts.system_program.to_account_info(),
-                        BOT_FEE,
+                        &bot_tax,
                     )?;
                     return Ok(());
                 }
             }
             EndSettingType::Amount => {
-                if candy_machine.items_redeemed >= es.number {
+                if candy_machine.items_redeemed + count as u64 > es.number {
                     if !cmp_pubkeys(&ctx.accounts.payer.key(), &candy_machine.authority) {
                         punish_bots(
                             CandyError::CandyMachineEmpty,
                             payer.to_account_info(),
-                            ctx.accounts.candy_machine.to_account_info(),
+                            bot_tax_destination.clone(),
                             ctx.accounts.system_program.to_account_info(),
-                            BOT_FEE,
+                            &bot_tax,
                         )?;
                         return Ok(());
                     }
@@ -254,9 +394,144 @@ pub fn handle_mint_nft<'info>(
             }
         }
     }
+    let candy_key = candy_machine.key();
+    let payer_key = payer.key();
     let mut remaining_accounts_counter: usize = 0;
+    // the mint counter is only incremented once all the bot checks have passed
+    let mut phase_mint_counter: Option<(&AccountInfo, usize, u16)> = None;
+    if !settings.phases.is_empty() {
+        match active_phase {
+            Some((phase_index, phase)) => {
+                price = phase.price;
+                if let Some(gate_mint) = &phase.gate_mint {
+                    let gate_token_account = ctx
+                        .remaining_accounts
+                        .get(remaining_accounts_counter)
+                        .ok_or(CandyError::MissingRemainingAccount)?;
+                    remaining_accounts_counter += 1;
+                    let holds_gate_token =
+                        match assert_is_ata(gate_token_account, &payer.key(), gate_mint) {
+                            Ok(gta) => gta.amount > 0,
+                            Err(_) => false,
+                        };
+                    if !holds_gate_token {
+                        punish_bots(
+                            CandyError::NoWhitelistToken,
+                            payer.to_account_info(),
+                            bot_tax_destination.clone(),
+                            ctx.accounts.system_program.to_account_info(),
+                            &bot_tax,
+                        )?;
+                        return Ok(());
+                    }
+                }
+                let mut phase_mint_limit = phase.mint_limit;
+                if let Some(allowlist_root) = phase.allowlist_root {
+                    let allowlist_proof_info = ctx
+                        .remaining_accounts
+                        .get(remaining_accounts_counter)
+                        .ok_or(CandyError::MissingRemainingAccount)?;
+                    remaining_accounts_counter += 1;
+                    match assert_allowlisted(
+                        allowlist_proof_info,
+                        &candy_key,
+                        &payer_key,
+                        phase_index as u8,
+                        allowlist_root,
+                    ) {
+                        Ok(entry) => {
+                            if let Some(entry_price) = entry.price {
+                                price = entry_price;
+                            }
+                            if let Some(quantity) = entry.quantity {
+                                phase_mint_limit =
+                                    Some(phase_mint_limit.map_or(quantity, |l| l.min(quantity)));
+                            }
+                        }
+                        Err(_) => {
+                            punish_bots(
+                                CandyError::NotAllowlisted,
+                                payer.to_account_info(),
+                                bot_tax_destination.clone(),
+                                ctx.accounts.system_program.to_account_info(),
+                                &bot_tax,
+                            )?;
+                            return Ok(());
+                        }
+                    }
+                }
+                if phase.mint_limit.is_some() || phase.allowlist_root.is_some() {
+                    let mint_counter_info = ctx
+                        .remaining_accounts
+                        .get(remaining_accounts_counter)
+                        .ok_or(CandyError::MissingRemainingAccount)?;
+                    remaining_accounts_counter += 1;
+                    phase_mint_counter = Some((
+                        mint_counter_info,
+                        phase_index,
+                        phase_mint_limit.unwrap_or(u16::MAX),
+                    ));
+                }
+            }
+            None => {
+                // between or outside of the phases only the authority can mint
+                if !cmp_pubkeys(&payer.key(), &candy_machine.authority) {
+                    punish_bots(
+                        CandyError::CandyMachineNotLive,
+                        payer.to_account_info(),
+                        bot_tax_destination.clone(),
+                        ctx.accounts.system_program.to_account_info(),
+                        &bot_tax,
+                    )?;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    let wallet_mint_counter = match &settings.mint_limit {
+        Some(mint_limit) => {
+            let mint_counter_info = ctx
+                .remaining_accounts
+                .get(remaining_accounts_counter)
+                .ok_or(CandyError::MissingRemainingAccount)?;
+            remaining_accounts_counter += 1;
+            Some((mint_counter_info, mint_limit))
+        }
+        None => None,
+    };
+
+    let mint_commitment = if settings.commit_reveal {
+        let mint_commitment_info = ctx
+            .remaining_accounts
+            .get(remaining_accounts_counter)
+            .ok_or(CandyError::MissingRemainingAccount)?;
+        remaining_accounts_counter += 1;
+        let (expected_key, _) = Pubkey::find_program_address(
+            &[
+                MintCommitment::PREFIX.as_bytes(),
+                candy_key.as_ref(),
+                payer_key.as_ref(),
+            ],
+            &crate::id(),
+        );
+        assert_keys_equal(&expected_key, mint_commitment_info.key)?;
+        if !cmp_pubkeys(&recent_slothashes.key(), &SlotHashes::id()) {
+            return err!(CandyError::IncorrectSlotHashesPubkey);
+        }
+        let mint_commitment: Account<MintCommitment> = Account::try_from(mint_commitment_info)?;
+        let revealed_slot_hash =
+            get_revealed_slot_hash(&recent_slothashes.data.borrow(), mint_commitment.slot)?;
+        Some((mint_commitment, revealed_slot_hash))
+    } else {
+        None
+    };
+
     if let Some(gatekeeper) = &candy_machine.data.gatekeeper {
-        let gateway_token_info = &ctx.remaining_accounts[remaining_accounts_counter];
+        let gateway_token_info = ctx
+            .remaining_accounts
+            .get(remaining_accounts_counter)
+            .ok_or(CandyError::MissingRemainingAccount)?;
 
         remaining_accounts_counter += 1;
 
@@ -282,9 +557,15 @@ pub fn handle_mint_nft<'info>(
             };
 
         if gatekeeper.expire_on_use {
-            let gateway_app = &ctx.remaining_accounts[remaining_accounts_counter];
+            let gateway_app = ctx
+                .remaining_accounts
+                .get(remaining_accounts_counter)
+                .ok_or(CandyError::MissingRemainingAccount)?;
             remaining_accounts_counter += 1;
-            let network_expire_feature = &ctx.remaining_accounts[remaining_accounts_counter];
+            let network_expire_feature = ctx
+                .remaining_accounts
+                .get(remaining_accounts_counter)
+                .ok_or(CandyError::MissingRemainingAccount)?;
             remaining_accounts_counter += 1;
 
             if Gateway::verify_and_expire_token_with_eval(
@@ -300,9 +581,9 @@ pub fn handle_mint_nft<'info>(
                 punish_bots(
                     CandyError::GatewayProgramError,
                     payer.to_account_info(),
-                    ctx.accounts.candy_machine.to_account_info(),
+                    bot_tax_destination.clone(),
                     ctx.accounts.system_program.to_account_info(),
-                    BOT_FEE,
+                    &bot_tax,
                 )?;
                 return Ok(());
             }
@@ -318,16 +599,19 @@ pub fn handle_mint_nft<'info>(
             punish_bots(
                 CandyError::GatewayProgramError,
                 payer.to_account_info(),
-                ctx.accounts.candy_machine.to_account_info(),
+                bot_tax_destination.clone(),
                 ctx.accounts.system_program.to_account_info(),
-                BOT_FEE,
+                &bot_tax,
             )?;
             return Ok(());
         }
     }
 
     if let Some(ws) = &candy_machine.data.whitelist_mint_settings {
-        let whitelist_token_account = &ctx.remaining_accounts[remaining_accounts_counter];
+        let whitelist_token_account = ctx
+            .remaining_accounts
+            .get(remaining_accounts_counter)
+            .ok_or(CandyError::MissingRemainingAccount)?;
         remaining_accounts_counter += 1;
         // If the user has not actually made this account,
         // this explodes and we just check normal dates.
@@ -344,9 +628,9 @@ pub fn handle_mint_nft<'info>(
                                 punish_bots(
                                     CandyError::CandyMachineNotLive,
                                     payer.to_account_info(),
-                                    ctx.accounts.candy_machine.to_account_info(),
+                                    bot_tax_destination.clone(),
                                     ctx.accounts.system_program.to_account_info(),
-                                    BOT_FEE,
+                                    &bot_tax,
                                 )?;
                                 return Ok(());
                             }
@@ -359,9 +643,9 @@ pub fn handle_mint_nft<'info>(
                                 punish_bots(
                                     CandyError::CandyMachineNotLive,
                                     payer.to_account_info(),
-                                    ctx.accounts.candy_machine.to_account_info(),
+                                    bot_tax_destination.clone(),
                                     ctx.accounts.system_program.to_account_info(),
-                                    BOT_FEE,
+                                    &bot_tax,
                                 )?;
                                 return Ok(());
                             }
@@ -369,12 +653,16 @@ pub fn handle_mint_nft<'info>(
                     }
 
                     if ws.mode == WhitelistMintMode::BurnEveryTime {
-                        let whitelist_token_mint =
-                            &ctx.remaining_accounts[remaining_accounts_counter];
+                        let whitelist_token_mint = ctx
+                            .remaining_accounts
+                            .get(remaining_accounts_counter)
+                            .ok_or(CandyError::MissingRemainingAccount)?;
                         remaining_accounts_counter += 1;
 
-                        let whitelist_burn_authority =
-                            &ctx.remaining_accounts[remaining_accounts_counter];
+                        let whitelist_burn_authority = ctx
+                            .remaining_accounts
+                            .get(remaining_accounts_counter)
+                            .ok_or(CandyError::MissingRemainingAccount)?;
                         remaining_accounts_counter += 1;
 
                         let key_check = assert_keys_equal(&whitelist_token_mint.key(), &ws.mint);
@@ -383,9 +671,9 @@ pub fn handle_mint_nft<'info>(
                             punish_bots(
                                 CandyError::IncorrectOwner,
                                 payer.to_account_info(),
-                                ctx.accounts.candy_machine.to_account_info(),
+                                bot_tax_destination.clone(),
                                 ctx.accounts.system_program.to_account_info(),
-                                BOT_FEE,
+                                &bot_tax,
                             )?;
                             return Ok(());
                         }
@@ -393,7 +681,7 @@ pub fn handle_mint_nft<'info>(
                         spl_token_burn(TokenBurnParams {
                             mint: whitelist_token_mint.clone(),
                             source: whitelist_token_account.clone(),
-                            amount: 1,
+                            amount: count as u64,
                             authority: whitelist_burn_authority.clone(),
                             authority_signer_seeds: None,
                             token_program: token_program.to_account_info(),
@@ -411,9 +699,9 @@ pub fn handle_mint_nft<'info>(
                         punish_bots(
                             CandyError::NoWhitelistToken,
                             payer.to_account_info(),
-                            ctx.accounts.candy_machine.to_account_info(),
+                            bot_tax_destination.clone(),
                             ctx.accounts.system_program.to_account_info(),
-                            BOT_FEE,
+                            &bot_tax,
                         )?;
                         return Ok(());
                     }
@@ -422,9 +710,9 @@ pub fn handle_mint_nft<'info>(
                         punish_bots(
                             CandyError::CandyMachineNotLive,
                             payer.to_account_info(),
-                            ctx.accounts.candy_machine.to_account_info(),
+                            bot_tax_destination.clone(),
                             ctx.accounts.system_program.to_account_info(),
-                            BOT_FEE,
+                            &bot_tax,
                         )?;
                         return Ok(());
                     }
@@ -441,9 +729,9 @@ pub fn handle_mint_nft<'info>(
                     punish_bots(
                         CandyError::NoWhitelistToken,
                         payer.to_account_info(),
-                        ctx.accounts.candy_machine.to_account_info(),
+                        bot_tax_destination.clone(),
                         ctx.accounts.system_program.to_account_info(),
-                        BOT_FEE,
+                        &bot_tax,
                     )?;
                     return Ok(());
                 }
@@ -455,34 +743,86 @@ pub fn handle_mint_nft<'info>(
                     punish_bots(
                         CandyError::CandyMachineNotLive,
                         payer.to_account_info(),
-                        ctx.accounts.candy_machine.to_account_info(),
+                        bot_tax_destination.clone(),
                         ctx.accounts.system_program.to_account_info(),
-                        BOT_FEE,
+                        &bot_tax,
                     )?;
                     return Ok(());
                 }
             }
         }
-    } else {
-        // no whitelist means normal datecheck
+    } else if settings.phases.is_empty() {
+        // no whitelist or phases means normal datecheck
         let go_live = assert_valid_go_live(payer, &clock, candy_machine);
         if go_live.is_err() {
             punish_bots(
                 CandyError::CandyMachineNotLive,
                 payer.to_account_info(),
-                ctx.accounts.candy_machine.to_account_info(),
+                bot_tax_destination.clone(),
                 ctx.accounts.system_program.to_account_info(),
-                BOT_FEE,
+                &bot_tax,
             )?;
             return Ok(());
         }
     }
 
-    let (wallet_to_use, freeze_pda): (&AccountInfo, Option<Account<FreezePDA>>) =
-        if is_feature_active(&candy_machine.data.uuid, FREEZE_FEATURE_INDEX) {
+    let wallet_mint_counter_seeds = [
+        MintCounter::PREFIX.as_bytes(),
+        candy_key.as_ref(),
+        payer_key.as_ref(),
+    ];
+    if let Some((mint_counter_info, mint_limit)) = wallet_mint_counter {
+        if mint_limit.bot_tax
+            && get_mint_count(mint_counter_info, &wallet_mint_counter_seeds)?
+                .saturating_add(count as u16)
+                > mint_limit.limit
+        {
+            punish_bots(
+                CandyError::MintLimitReached,
+                payer.to_account_info(),
+                bot_tax_destination.clone(),
+                ctx.accounts.system_program.to_account_info(),
+                &bot_tax,
+            )?;
+            return Ok(());
+        }
+        increment_mint_counter(
+            mint_counter_info,
+            &wallet_mint_counter_seeds,
+            &payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            mint_limit.limit,
+            count as u16,
+        )?;
+    }
+
+    if let Some((mint_counter_info, phase_index, mint_limit)) = phase_mint_counter {
+        increment_mint_counter(
+            mint_counter_info,
+            &[
+                MintCounter::PREFIX.as_bytes(),
+                candy_key.as_ref(),
+                payer_key.as_ref(),
+                &[phase_index as u8],
+            ],
+            &payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            mint_limit,
+            count as u16,
+        )?;
+    }
+
+    let (wallet_to_use, mut freeze_pda): (&AccountInfo, Option<Account<FreezePDA>>) =
+        if features.is_active(Feature::Freeze) {
             if let Some(mint) = candy_machine.token_mint {
-                let freeze_pda_info = &ctx.remaining_accounts[remaining_accounts_counter + 2];
-                let freeze_ata = &ctx.remaining_accounts[remaining_accounts_counter + 2 + 2];
+                let freeze_pda_info = ctx
+                    .remaining_accounts
+                    .get(remaining_accounts_counter + 2)
+                    .ok_or(CandyError::MissingRemainingAccount)?;
+                let freeze_ata = ctx
+                    .remaining_accounts
+                    .get(remaining_accounts_counter + 2 + 2)
+                    .ok_or(CandyError::MissingRemainingAccount)?;
                 assert_is_ata(freeze_ata, freeze_pda_info.key, &mint)?;
                 let freeze_pda: Account<FreezePDA> = Account::try_from(freeze_pda_info)?;
                 if freeze_pda.thaw_eligible(clock.unix_timestamp, candy_machine) {
@@ -491,7 +831,10 @@ pub fn handle_mint_nft<'info>(
                     (freeze_ata, Some(freeze_pda))
                 }
             } else {
-                let freeze_pda_info = &ctx.remaining_accounts[remaining_accounts_counter];
+                let freeze_pda_info = ctx
+                    .remaining_accounts
+                    .get(remaining_accounts_counter)
+                    .ok_or(CandyError::MissingRemainingAccount)?;
                 let freeze_pda: Account<FreezePDA> = Account::try_from(freeze_pda_info)?;
                 if freeze_pda.thaw_eligible(clock.unix_timestamp, candy_machine) {
                     (wallet, None)
@@ -503,15 +846,35 @@ pub fn handle_mint_nft<'info>(
             (wallet, None)
         };
 
-    if let Some(mint) = candy_machine.token_mint {
-        let token_account_info = &ctx.remaining_accounts[remaining_accounts_counter];
+    let total_price = price
+        .checked_mul(count as u64)
+        .ok_or(CandyError::NumericalOverflowError)?;
+    if let Some(payment_option) = payment_option {
+        pay_with_option(
+            payment_option,
+            count as u64,
+            ctx.remaining_accounts,
+            &mut remaining_accounts_counter,
+            &payer.to_account_info(),
+            &wallet.to_account_info(),
+            &token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+    } else if let Some(mint) = candy_machine.token_mint {
+        let token_account_info = ctx
+            .remaining_accounts
+            .get(remaining_accounts_counter)
+            .ok_or(CandyError::MissingRemainingAccount)?;
         remaining_accounts_counter += 1;
-        let transfer_authority_info = &ctx.remaining_accounts[remaining_accounts_counter];
+        let transfer_authority_info = ctx
+            .remaining_accounts
+            .get(remaining_accounts_counter)
+            .ok_or(CandyError::MissingRemainingAccount)?;
         remaining_accounts_counter += 1;
 
         let token_account = assert_is_ata(token_account_info, &payer.key(), &mint)?;
 
-        if token_account.amount < price {
+        if token_account.amount < total_price {
             return err!(CandyError::NotEnoughTokens);
         }
 
@@ -521,14 +884,18 @@ pub fn handle_mint_nft<'info>(
             authority: transfer_authority_info.clone(),
             authority_signer_seeds: &[],
             token_program: token_program.to_account_info(),
-            amount: price,
+            amount: total_price,
         })?;
     } else {
-        if ctx.accounts.payer.lamports() < price {
+        if ctx.accounts.payer.lamports() < total_price {
             return err!(CandyError::NotEnoughSOL);
         }
         invoke(
-            &system_instruction::transfer(&ctx.accounts.payer.key(), &wallet_to_use.key(), price),
+            &system_instruction::transfer(
+                &ctx.accounts.payer.key(),
+                &wallet_to_use.key(),
+                total_price,
+            ),
             &[
                 ctx.accounts.payer.to_account_info(),
                 wallet_to_use.to_account_info(),
@@ -537,21 +904,123 @@ pub fn handle_mint_nft<'info>(
         )?;
     }
 
-    let data = recent_slothashes.data.borrow();
-    let most_recent = array_ref![data, 12, 8];
-
-    let index = u64::from_le_bytes(*most_recent);
-    let modded: usize = index
-        .checked_rem(candy_machine.data.items_available)
-        .ok_or(CandyError::NumericalOverflowError)? as usize;
-
-    let config_line = get_config_line(candy_machine, modded, candy_machine.items_redeemed)?;
-
-    candy_machine.items_redeemed = candy_machine
-        .items_redeemed
-        .checked_add(1)
-        .ok_or(CandyError::NumericalOverflowError)?;
+    // the freeze accounts come next, then the programmable NFT accounts and the mint receipt
+    let freeze_accounts_start = remaining_accounts_counter;
+    let programmable_accounts_start =
+        freeze_accounts_start + get_freeze_accounts_count(candy_machine, &features, &settings);
+    let programmable_accounts = match &settings.programmable {
+        Some(programmable) => Some(
+            ctx.remaining_accounts
+                .get(
+                    programmable_accounts_start
+                        ..programmable_accounts_start + programmable.accounts_len(),
+                )
+                .ok_or(CandyError::MissingRemainingAccount)?,
+        ),
+        None => None,
+    };
+    let mint_receipt_index = programmable_accounts_start
+        + settings
+            .programmable
+            .as_ref()
+            .map_or(0, |programmable| programmable.accounts_len());
+    let mut items = vec![MintItem {
+        metadata: ctx.accounts.metadata.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        master_edition: ctx.accounts.master_edition.to_account_info(),
+        freeze_accounts: if features.is_active(Feature::Freeze) {
+            // after the freeze pda, and the freeze ata if spl token mint is enabled
+            let refund_record_offset = if candy_machine.token_mint.is_some() {
+                3
+            } else {
+                2
+            };
+            let refund_record = if settings.freeze_refund {
+                Some(
+                    ctx.remaining_accounts
+                        .get(freeze_accounts_start + refund_record_offset)
+                        .ok_or(CandyError::MissingRemainingAccount)?,
+                )
+            } else {
+                None
+            };
+            Some((
+                ctx.remaining_accounts
+                    .get(freeze_accounts_start + 1)
+                    .ok_or(CandyError::MissingRemainingAccount)?
+                    .clone(),
+                refund_record.cloned(),
+            ))
+        } else {
+            None
+        },
+        programmable_accounts: programmable_accounts.map(|accounts| accounts.to_vec()),
+        mint_receipt: if settings.mint_receipts {
+            Some(
+                ctx.remaining_accounts
+                    .get(mint_receipt_index)
+                    .ok_or(CandyError::MissingRemainingAccount)?
+                    .clone(),
+            )
+        } else {
+            None
+        },
+    }];
+    for group in items_accounts
+        .chunks_exact(item_accounts_count)
+        .take(count as usize - 1)
+    {
+        let mut position = 3;
+        let freeze_accounts = if features.is_active(Feature::Freeze) {
+            let nft_token_account = group[position].clone();
+            position += 1;
+            let refund_record = if settings.freeze_refund {
+                position += 1;
+                Some(group[position - 1].clone())
+            } else {
+                None
+            };
+            Some((nft_token_account, refund_record))
+        } else {
+            None
+        };
+        // the associated token and rule set accounts are shared by the NFTs
+        let programmable_accounts = programmable_accounts.map(|shared| {
+            let mut accounts = group[position..position + 2].to_vec();
+            accounts.extend(shared[2..].iter().cloned());
+            accounts
+        });
+        items.push(MintItem {
+            metadata: group[0].clone(),
+            mint: group[1].clone(),
+            master_edition: group[2].clone(),
+            freeze_accounts,
+            programmable_accounts,
+            mint_receipt: settings
+                .mint_receipts
+                .then(|| group[item_accounts_count - 1].clone()),
+        });
+    }
 
+    // the position picked is bound to the items left at commit time, so it doesn't change
+    // with the number of items minted while the minter waits to mint
+    let committed_items_remaining = mint_commitment.as_ref().map(|(mint_commitment, _)| {
+        candy_machine
+            .data
+            .items_available
+            .saturating_sub(mint_commitment.items_redeemed)
+    });
+    let seed = if let Some((mint_commitment, revealed_slot_hash)) = mint_commitment {
+        // closing returns the deposit along with the rent
+        mint_commitment.close(payer.to_account_info())?;
+        // mixed with the minter so commitments of the same slot get different items
+        let seed = keccak::hashv(&[&revealed_slot_hash, payer_key.as_ref(), candy_key.as_ref()]).0;
+        u64::from_le_bytes(*array_ref![seed, 0, 8])
+    } else {
+        let data = recent_slothashes.data.borrow();
+        let most_recent = array_ref![data, 12, 8];
+        u64::from_le_bytes(*most_recent)
+    };
     let cm_key = candy_machine.key();
     let authority_seeds = [PREFIX.as_bytes(), cm_key.as_ref(), &[creator_bump]];
 
@@ -570,289 +1039,700 @@ pub fn handle_mint_nft<'info>(
         });
     }
 
-    let metadata_infos = vec![
-        ctx.accounts.metadata.to_account_info(),
-        ctx.accounts.mint.to_account_info(),
-        ctx.accounts.mint_authority.to_account_info(),
-        ctx.accounts.payer.to_account_info(),
-        ctx.accounts.token_metadata_program.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        ctx.accounts.system_program.to_account_info(),
-        ctx.accounts.rent.to_account_info(),
-        candy_machine_creator.to_account_info(),
-    ];
-
-    let master_edition_infos = vec![
-        ctx.accounts.master_edition.to_account_info(),
-        ctx.accounts.mint.to_account_info(),
-        ctx.accounts.mint_authority.to_account_info(),
-        ctx.accounts.payer.to_account_info(),
-        ctx.accounts.metadata.to_account_info(),
-        ctx.accounts.token_metadata_program.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        ctx.accounts.system_program.to_account_info(),
-        ctx.accounts.rent.to_account_info(),
-        candy_machine_creator.to_account_info(),
-    ];
-
-    invoke_signed(
-        &create_metadata_accounts_v3(
-            ctx.accounts.token_metadata_program.key(),
-            ctx.accounts.metadata.key(),
-            ctx.accounts.mint.key(),
-            ctx.accounts.mint_authority.key(),
-            ctx.accounts.payer.key(),
-            candy_machine_creator.key(),
-            config_line.name,
-            candy_machine.data.symbol.clone(),
-            config_line.uri,
-            Some(creators),
-            candy_machine.data.seller_fee_basis_points,
-            true,
-            candy_machine.data.is_mutable,
-            None,
-            None,
-            None,
-        ),
-        metadata_infos.as_slice(),
-        &[&authority_seeds],
-    )?;
-    invoke_signed(
-        &create_master_edition_v3(
-            ctx.accounts.token_metadata_program.key(),
-            ctx.accounts.master_edition.key(),
-            ctx.accounts.mint.key(),
-            candy_machine_creator.key(),
-            ctx.accounts.mint_authority.key(),
-            ctx.accounts.metadata.key(),
-            ctx.accounts.payer.key(),
-            Some(candy_machine.data.max_supply),
-        ),
-        master_edition_infos.as_slice(),
-        &[&authority_seeds],
-    )?;
-
     let mut new_update_authority = Some(candy_machine.authority);
 
-    if !candy_machine.data.retain_authority {
+    if settings.on_chain_reveal {
+        // handed over when the NFT is revealed
+        new_update_authority = None;
+    } else if !candy_machine.data.retain_authority {
         new_update_authority = Some(ctx.accounts.update_authority.key());
     }
-    invoke_signed(
-        &update_metadata_accounts_v2(
-            ctx.accounts.token_metadata_program.key(),
-            ctx.accounts.metadata.key(),
-            candy_machine_creator.key(),
-            new_update_authority,
-            None,
-            Some(true),
-            if !candy_machine.data.is_mutable {
-                Some(false)
-            } else {
-                None
-            },
-        ),
-        &[
-            ctx.accounts.token_metadata_program.to_account_info(),
-            ctx.accounts.metadata.to_account_info(),
-            candy_machine_creator.to_account_info(),
-        ],
-        &[&authority_seeds],
-    )?;
+    let (paid_price, payment_mint) =
+        match payment_option.and_then(|payment_option| payment_option.payments.first()) {
+            Some(payment) => payment.price(),
+            None => (price, candy_machine.token_mint),
+        };
+    for (item_number, item) in items.iter().enumerate() {
+        // the other NFTs of a batch get their own pick from the seed
+        let index = if item_number == 0 {
+            seed
+        } else {
+            let item_seed = keccak::hashv(&[&seed.to_le_bytes(), &[item_number as u8]]).0;
+            u64::from_le_bytes(*array_ref![item_seed, 0, 8])
+        };
+        let items_remaining = candy_machine
+            .data
+            .items_available
+            .checked_sub(candy_machine.items_redeemed)
+            .ok_or(CandyError::NumericalOverflowError)?;
+        let modded: usize = index
+            .checked_rem(committed_items_remaining.unwrap_or(items_remaining))
+            .and_then(|position| position.checked_rem(items_remaining))
+            .ok_or(CandyError::NumericalOverflowError)? as usize;
 
-    if let Some(mut freeze_pda) = freeze_pda {
-        msg!("About to freeze nft");
-        let mint_pubkey = ctx.accounts.mint.key();
-        let candy_pubkey = ctx.accounts.candy_machine.key();
-        // counter incremented here since we sorta incremented it in our hearts during the wallet_to_use block.
-        remaining_accounts_counter += 1;
-        let nft_token_account_info = &ctx.remaining_accounts[remaining_accounts_counter];
-        // If we add more extra accounts later on we need to uncomment the following line out.
-        // remaining_accounts_counter += 1;
-
-        assert_is_ata(nft_token_account_info, &payer.key(), &mint_pubkey)?;
-        let seeds: &[&[u8]] = &[FreezePDA::PREFIX.as_bytes(), candy_pubkey.as_ref()];
-        let (expected_freeze_key, freeze_bump) = Pubkey::find_program_address(seeds, &crate::id());
-        assert_keys_equal(&expected_freeze_key, &freeze_pda.key())?;
-        // redundant check
-        freeze_pda.assert_from_candy(&candy_pubkey)?;
-
-        freeze_pda.frozen_count += 1;
-
-        if freeze_pda.freeze_fee > 0 {
-            invoke(
-                &system_instruction::transfer(
-                    &ctx.accounts.payer.key(),
-                    &freeze_pda.key(),
-                    freeze_pda.freeze_fee,
+        let (config_line, line_index) =
+            get_config_line(candy_machine, modded, candy_machine.items_redeemed)?;
+        // hidden settings NFTs are identified by their mint number
+        let item_index = line_index.map_or(candy_machine.items_redeemed, |index| index as u64);
+
+        candy_machine.items_redeemed = candy_machine
+            .items_redeemed
+            .checked_add(1)
+            .ok_or(CandyError::NumericalOverflowError)?;
+
+        if let Some(programmable_accounts) = &item.programmable_accounts {
+            let programmable = settings.programmable.as_ref().unwrap();
+            let nft_token_account_info = &programmable_accounts[0];
+            let token_record_info = &programmable_accounts[1];
+            let associated_token_program = &programmable_accounts[2];
+            let rule_set_accounts = &programmable_accounts[3..];
+            assert_keys_equal(
+                &get_associated_token_address(&payer.key(), &item.mint.key()),
+                nft_token_account_info.key,
+            )?;
+            assert_keys_equal(
+                &spl_associated_token_account::id(),
+                associated_token_program.key,
+            )?;
+
+            let mut programmable_infos = vec![
+                item.metadata.to_account_info(),
+                item.master_edition.to_account_info(),
+                item.mint.to_account_info(),
+                ctx.accounts.mint_authority.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                candy_machine_creator.to_account_info(),
+                nft_token_account_info.clone(),
+                token_record_info.clone(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                associated_token_program.clone(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.instruction_sysvar_account.to_account_info(),
+            ];
+            programmable_infos.extend(rule_set_accounts.iter().cloned());
+
+            let create_ix = CreateBuilder::new()
+                .metadata(item.metadata.key())
+                .master_edition(item.master_edition.key())
+                .mint(item.mint.key())
+                .authority(ctx.accounts.mint_authority.key())
+                .payer(ctx.accounts.payer.key())
+                .update_authority(candy_machine_creator.key())
+                .system_program(ctx.accounts.system_program.key())
+                .sysvar_instructions(ctx.accounts.instruction_sysvar_account.key())
+                .spl_token_program(ctx.accounts.token_program.key())
+                .initialize_mint(false)
+                .update_authority_as_signer(true)
+                .build(CreateArgs::V1 {
+                    asset_data: AssetData {
+                        name: config_line.name,
+                        symbol: candy_machine.data.symbol.clone(),
+                        uri: config_line.uri,
+                        seller_fee_basis_points: candy_machine.data.seller_fee_basis_points,
+                        creators: Some(creators.clone()),
+                        primary_sale_happened: true,
+                        // made immutable once the update authority is handed over
+                        is_mutable: true,
+                        token_standard: TokenStandard::ProgrammableNonFungible,
+                        collection: None,
+                        uses: None,
+                        collection_details: None,
+                        rule_set: programmable.rule_set,
+                    },
+                    decimals: Some(0),
+                    print_supply: Some(if candy_machine.data.max_supply == 0 {
+                        PrintSupply::Zero
+                    } else {
+                        PrintSupply::Limited(candy_machine.data.max_supply)
+                    }),
+                })
+                .unwrap()
+                .instruction();
+            invoke_signed(&create_ix, &programmable_infos, &[&authority_seeds])?;
+
+            // programmable NFTs can only be minted by their update authority
+            let mut mint_builder = MintBuilder::new();
+            mint_builder
+                .token(nft_token_account_info.key())
+                .token_owner(payer.key())
+                .metadata(item.metadata.key())
+                .master_edition(item.master_edition.key())
+                .token_record(token_record_info.key())
+                .mint(item.mint.key())
+                .authority(candy_machine_creator.key())
+                .payer(ctx.accounts.payer.key())
+                .system_program(ctx.accounts.system_program.key())
+                .sysvar_instructions(ctx.accounts.instruction_sysvar_account.key())
+                .spl_token_program(ctx.accounts.token_program.key())
+                .spl_ata_program(associated_token_program.key());
+            if let [authorization_rules_program, authorization_rules] = rule_set_accounts {
+                mint_builder
+                    .authorization_rules_program(authorization_rules_program.key())
+                    .authorization_rules(authorization_rules.key());
+            }
+            let mint_ix = mint_builder
+                .build(MintArgs::V1 {
+                    amount: 1,
+                    authorization_data: None,
+                })
+                .unwrap()
+                .instruction();
+            invoke_signed(&mint_ix, &programmable_infos, &[&authority_seeds])?;
+
+            let update_ix = UpdateBuilder::new()
+                .authority(candy_machine_creator.key())
+                .metadata(item.metadata.key())
+                .mint(item.mint.key())
+                .edition(item.master_edition.key())
+                .payer(ctx.accounts.payer.key())
+                .system_program(ctx.accounts.system_program.key())
+                .sysvar_instructions(ctx.accounts.instruction_sysvar_account.key())
+                .build(UpdateArgs::V1 {
+                    new_update_authority,
+                    data: None,
+                    primary_sale_happened: None,
+                    // on-chain reveals update the metadata after the mint
+                    is_mutable: if !candy_machine.data.is_mutable && !settings.on_chain_reveal {
+                        Some(false)
+                    } else {
+                        None
+                    },
+                    collection: CollectionToggle::None,
+                    collection_details: CollectionDetailsToggle::None,
+                    uses: UsesToggle::None,
+                    rule_set: RuleSetToggle::None,
+                    authorization_data: None,
+                })
+                .unwrap()
+                .instruction();
+            invoke_signed(&update_ix, &programmable_infos, &[&authority_seeds])?;
+        } else {
+            let metadata_infos = vec![
+                item.metadata.to_account_info(),
+                item.mint.to_account_info(),
+                ctx.accounts.mint_authority.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.rent.to_account_info(),
+                candy_machine_creator.to_account_info(),
+            ];
+
+            let master_edition_infos = vec![
+                item.master_edition.to_account_info(),
+                item.mint.to_account_info(),
+                ctx.accounts.mint_authority.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                item.metadata.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.rent.to_account_info(),
+                candy_machine_creator.to_account_info(),
+            ];
+
+            invoke_signed(
+                &create_metadata_accounts_v3(
+                    ctx.accounts.token_metadata_program.key(),
+                    item.metadata.key(),
+                    item.mint.key(),
+                    ctx.accounts.mint_authority.key(),
+                    ctx.accounts.payer.key(),
+                    candy_machine_creator.key(),
+                    config_line.name,
+                    candy_machine.data.symbol.clone(),
+                    config_line.uri,
+                    Some(creators.clone()),
+                    candy_machine.data.seller_fee_basis_points,
+                    true,
+                    // on-chain reveals update the metadata after the mint
+                    candy_machine.data.is_mutable || settings.on_chain_reveal,
+                    None,
+                    None,
+                    None,
+                ),
+                metadata_infos.as_slice(),
+                &[&authority_seeds],
+            )?;
+            invoke_signed(
+                &create_master_edition_v3(
+                    ctx.accounts.token_metadata_program.key(),
+                    item.master_edition.key(),
+                    item.mint.key(),
+                    candy_machine_creator.key(),
+                    ctx.accounts.mint_authority.key(),
+                    item.metadata.key(),
+                    ctx.accounts.payer.key(),
+                    Some(candy_machine.data.max_supply),
+                ),
+                master_edition_infos.as_slice(),
+                &[&authority_seeds],
+            )?;
+
+            invoke_signed(
+                &update_metadata_accounts_v2(
+                    ctx.accounts.token_metadata_program.key(),
+                    item.metadata.key(),
+                    candy_machine_creator.key(),
+                    new_update_authority,
+                    None,
+                    Some(true),
+                    if !candy_machine.data.is_mutable && !settings.on_chain_reveal {
+                        Some(false)
+                    } else {
+                        None
+                    },
                 ),
                 &[
-                    ctx.accounts.payer.to_account_info(),
-                    freeze_pda.to_account_info(),
-                    ctx.accounts.system_program.to_account_info(),
+                    ctx.accounts.token_metadata_program.to_account_info(),
+                    item.metadata.to_account_info(),
+                    candy_machine_creator.to_account_info(),
                 ],
+                &[&authority_seeds],
             )?;
         }
 
-        if freeze_pda.mint_start.is_none() {
-            freeze_pda.mint_start = Some(clock.unix_timestamp);
-        }
+        if let Some(freeze_pda) = freeze_pda.as_mut() {
+            msg!("About to freeze nft");
+            let mint_pubkey = item.mint.key();
+            let candy_pubkey = candy_machine.key();
+            let (nft_token_account_info, refund_record_info) = item
+                .freeze_accounts
+                .as_ref()
+                .ok_or(CandyError::IncorrectRemainingAccountsLen)?;
 
-        let freeze_seeds = [
-            FreezePDA::PREFIX.as_bytes(),
-            candy_pubkey.as_ref(),
-            &[freeze_bump],
-        ];
-        let mut freeze_ix = freeze_delegated_account(
-            mpl_token_metadata::ID,
-            freeze_pda.key(),
-            nft_token_account_info.key(),
-            ctx.accounts.master_edition.key(),
-            ctx.accounts.mint.key(),
-        );
-        // token metadata ix is sorta bad, so this line fixes it to enable freeze without marking signer as mutable
-        freeze_ix.accounts[0] = AccountMeta::new_readonly(freeze_pda.key(), true);
+            assert_is_ata(nft_token_account_info, &payer.key(), &mint_pubkey)?;
+            let seeds: &[&[u8]] = &[FreezePDA::PREFIX.as_bytes(), candy_pubkey.as_ref()];
+            let (expected_freeze_key, freeze_bump) =
+                Pubkey::find_program_address(seeds, &crate::id());
+            assert_keys_equal(&expected_freeze_key, &freeze_pda.key())?;
+            // redundant check
+            freeze_pda.assert_from_candy(&candy_pubkey)?;
 
-        invoke(
-            &approve(
-                &spl_token::ID,
-                &nft_token_account_info.key(),
-                &freeze_pda.key(),
-                &payer.key(),
-                &[],
-                1,
-            )?,
-            &[
-                nft_token_account_info.to_account_info(),
-                freeze_pda.to_account_info(),
-                payer.to_account_info(),
-            ],
-        )?;
-        invoke_signed(
-            &freeze_ix,
-            &[
-                freeze_pda.to_account_info(),
-                nft_token_account_info.to_account_info(),
-                ctx.accounts.master_edition.to_account_info(),
-                ctx.accounts.mint.to_account_info(),
-            ],
-            &[&freeze_seeds],
-        )?;
+            freeze_pda.frozen_count += 1;
+
+            if freeze_pda.freeze_fee > 0 {
+                invoke(
+                    &system_instruction::transfer(
+                        &ctx.accounts.payer.key(),
+                        &freeze_pda.key(),
+                        freeze_pda.freeze_fee,
+                    ),
+                    &[
+                        ctx.accounts.payer.to_account_info(),
+                        freeze_pda.to_account_info(),
+                        ctx.accounts.system_program.to_account_info(),
+                    ],
+                )?;
+            }
+
+            if freeze_pda.mint_start.is_none() {
+                freeze_pda.mint_start = Some(clock.unix_timestamp);
+            }
+
+            if let Some(refund_record_info) = refund_record_info {
+                let refund_record_seeds = [
+                    RefundRecord::PREFIX.as_bytes(),
+                    candy_pubkey.as_ref(),
+                    mint_pubkey.as_ref(),
+                ];
+                let (expected_key, bump) =
+                    Pubkey::find_program_address(&refund_record_seeds, &crate::id());
+                assert_keys_equal(&expected_key, refund_record_info.key)?;
+                create_pda_account(
+                    &payer.to_account_info(),
+                    refund_record_info,
+                    RefundRecord::SIZE,
+                    &ctx.accounts.system_program.to_account_info(),
+                    &[
+                        RefundRecord::PREFIX.as_bytes(),
+                        candy_pubkey.as_ref(),
+                        mint_pubkey.as_ref(),
+                        &[bump],
+                    ],
+                )?;
+                let mut refund_record: Account<RefundRecord> =
+                    Account::try_from_unchecked(refund_record_info)?;
+                refund_record.amount = price;
+                refund_record.line_index =
+                    line_index.ok_or(CandyError::InvalidFreezeRefund)? as u64;
+                refund_record.exit(&crate::id())?;
+            }
+
+            let freeze_seeds = [
+                FreezePDA::PREFIX.as_bytes(),
+                candy_pubkey.as_ref(),
+                &[freeze_bump],
+            ];
+            if let Some(programmable_accounts) = &item.programmable_accounts {
+                // programmable NFTs are always frozen, they are locked by a utility delegate instead
+                let token_record_info = &programmable_accounts[1];
+                let rule_set_accounts = &programmable_accounts[3..];
+                let mut lock_infos = vec![
+                    freeze_pda.to_account_info(),
+                    payer.to_account_info(),
+                    nft_token_account_info.clone(),
+                    token_record_info.clone(),
+                    item.metadata.to_account_info(),
+                    item.master_edition.to_account_info(),
+                    item.mint.to_account_info(),
+                    ctx.accounts.token_metadata_program.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    ctx.accounts.instruction_sysvar_account.to_account_info(),
+                ];
+                lock_infos.extend(rule_set_accounts.iter().cloned());
+
+                let mut delegate_builder = DelegateBuilder::new();
+                delegate_builder
+                    .delegate(freeze_pda.key())
+                    .metadata(item.metadata.key())
+                    .master_edition(item.master_edition.key())
+                    .token_record(token_record_info.key())
+                    .mint(item.mint.key())
+                    .token(nft_token_account_info.key())
+                    .authority(payer.key())
+                    .payer(payer.key())
+                    .system_program(ctx.accounts.system_program.key())
+                    .sysvar_instructions(ctx.accounts.instruction_sysvar_account.key())
+                    .spl_token_program(ctx.accounts.token_program.key());
+                let mut lock_builder = LockBuilder::new();
+                lock_builder
+                    .authority(freeze_pda.key())
+                    .token_owner(payer.key())
+                    .token(nft_token_account_info.key())
+                    .mint(item.mint.key())
+                    .metadata(item.metadata.key())
+                    .edition(item.master_edition.key())
+                    .token_record(token_record_info.key())
+                    .payer(payer.key())
+                    .system_program(ctx.accounts.system_program.key())
+                    .sysvar_instructions(ctx.accounts.instruction_sysvar_account.key())
+                    .spl_token_program(ctx.accounts.token_program.key());
+                if let [authorization_rules_program, authorization_rules] = rule_set_accounts {
+                    delegate_builder
+                        .authorization_rules_program(authorization_rules_program.key())
+                        .authorization_rules(authorization_rules.key());
+                    lock_builder
+                        .authorization_rules_program(authorization_rules_program.key())
+                        .authorization_rules(authorization_rules.key());
+                }
+
+                invoke(
+                    &delegate_builder
+                        .build(DelegateArgs::UtilityV1 {
+                            amount: 1,
+                            authorization_data: None,
+                        })
+                        .unwrap()
+                        .instruction(),
+                    &lock_infos,
+                )?;
+                invoke_signed(
+                    &lock_builder
+                        .build(LockArgs::V1 {
+                            authorization_data: None,
+                        })
+                        .unwrap()
+                        .instruction(),
+                    &lock_infos,
+                    &[&freeze_seeds],
+                )?;
+            } else {
+                let mut freeze_ix = freeze_delegated_account(
+                    mpl_token_metadata::ID,
+                    freeze_pda.key(),
+                    nft_token_account_info.key(),
+                    item.master_edition.key(),
+                    item.mint.key(),
+                );
+                // token metadata ix is sorta bad, so this line fixes it to enable freeze without marking signer as mutable
+                freeze_ix.accounts[0] = AccountMeta::new_readonly(freeze_pda.key(), true);
+
+                invoke(
+                    &approve(
+                        &spl_token::ID,
+                        &nft_token_account_info.key(),
+                        &freeze_pda.key(),
+                        &payer.key(),
+                        &[],
+                        1,
+                    )?,
+                    &[
+                        nft_token_account_info.to_account_info(),
+                        freeze_pda.to_account_info(),
+                        payer.to_account_info(),
+                    ],
+                )?;
+                invoke_signed(
+                    &freeze_ix,
+                    &[
+                        freeze_pda.to_account_info(),
+                        nft_token_account_info.to_account_info(),
+                        item.master_edition.to_account_info(),
+                        item.mint.to_account_info(),
+                    ],
+                    &[&freeze_seeds],
+                )?;
+            }
+        }
+
+        let event = MintEvent {
+            candy_machine: candy_machine.key(),
+            mint: item.mint.key(),
+            payer: payer.key(),
+            index: item_index,
+            price: paid_price,
+            payment_mint,
+            payment_option: payment_option.map(|_| payment_option_index),
+            phase: active_phase.map(|(phase_index, _)| phase_index as u8),
+        };
+        if let Some(mint_receipt_info) = &item.mint_receipt {
+            create_mint_receipt(
+                mint_receipt_info,
+                &event,
+                clock.unix_timestamp,
+                &payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+            )?;
+        }
+        emit!(event);
+    }
+    if let Some(freeze_pda) = freeze_pda {
         freeze_pda.exit(&crate::id())?;
     }
 
     Ok(())
 }
 
-pub fn get_good_index(
-    arr: &mut RefMut<&mut [u8]>,
-    items_available: usize,
-    index: usize,
-    pos: bool,
-) -> Result<(usize, bool)> {
-    let mut index_to_use = index;
-    let mut taken = 1;
-    let mut found = false;
-    let bit_mask_vec_start = CONFIG_ARRAY_START
-        + 4
-        + (items_available) * CONFIG_LINE_SIZE
-        + 4
-        + items_available
-            .checked_div(8)
-            .ok_or(CandyError::NumericalOverflowError)?
-        + 4;
-
-    while taken > 0 && index_to_use < items_available {
-        let my_position_in_vec = bit_mask_vec_start
-            + index_to_use
-                .checked_div(8)
-                .ok_or(CandyError::NumericalOverflowError)?;
-        if arr[my_position_in_vec] == 255 {
-            let eight_remainder = 8 - index_to_use
-                .checked_rem(8)
-                .ok_or(CandyError::NumericalOverflowError)?;
-            let reversed = 8 - eight_remainder + 1;
-            if (eight_remainder != 0 && pos) || (reversed != 0 && !pos) {
-                if pos {
-                    index_to_use += eight_remainder;
-                } else {
-                    if index_to_use < 8 {
-                        break;
-                    }
-                    index_to_use -= reversed;
-                }
-            } else if pos {
-                index_to_use += 8;
-            } else {
-                index_to_use -= 8;
-            }
-        } else {
-            let position_from_right = 7 - index_to_use
-                .checked_rem(8)
-                .ok_or(CandyError::NumericalOverflowError)?;
-            let mask = u8::pow(2, position_from_right as u32);
+/// Creates the [`MintReceipt`] PDA of the NFT minted in `event`.
+fn create_mint_receipt<'info>(
+    mint_receipt_info: &AccountInfo<'info>,
+    event: &MintEvent,
+    minted_at: i64,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let seeds = [
+        MintReceipt::PREFIX.as_bytes(),
+        event.candy_machine.as_ref(),
+        event.mint.as_ref(),
+    ];
+    let (expected_key, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    if !cmp_pubkeys(&expected_key, mint_receipt_info.key) {
+        return err!(CandyError::InvalidMintReceipt);
+    }
+    create_pda_account(
+        payer,
+        mint_receipt_info,
+        MintReceipt::SIZE,
+        system_program,
+        &[
+            MintReceipt::PREFIX.as_bytes(),
+            event.candy_machine.as_ref(),
+            event.mint.as_ref(),
+            &[bump],
+        ],
+    )?;
+    let mut mint_receipt: Account<MintReceipt> = Account::try_from_unchecked(mint_receipt_info)?;
+    mint_receipt.candy_machine = event.candy_machine;
+    mint_receipt.mint = event.mint;
+    mint_receipt.payer = event.payer;
+    mint_receipt.index = event.index;
+    mint_receipt.price = event.price;
+    mint_receipt.payment_mint = event.payment_mint;
+    mint_receipt.payment_option = event.payment_option;
+    mint_receipt.phase = event.phase;
+    mint_receipt.minted_at = minted_at;
+    mint_receipt.exit(&crate::id())
+}
 
-            taken = mask & arr[my_position_in_vec];
+/// Pays each payment of `payment_option`, consuming its remaining accounts.
+fn pay_with_option<'info>(
+    payment_option: &PaymentOption,
+    quantity: u64,
+    remaining_accounts: &[AccountInfo<'info>],
+    remaining_accounts_counter: &mut usize,
+    payer: &AccountInfo<'info>,
+    wallet: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    for payment in &payment_option.payments {
+        let accounts = remaining_accounts
+            .get(*remaining_accounts_counter..*remaining_accounts_counter + payment.accounts_len())
+            .ok_or(CandyError::MissingRemainingAccount)?;
+        *remaining_accounts_counter += payment.accounts_len();
+        let destination_wallet = payment.destination.unwrap_or_else(|| wallet.key());
+        let amount = payment
+            .amount
+            .checked_mul(quantity)
+            .ok_or(CandyError::NumericalOverflowError)?;
 
-            match taken {
-                x if x > 0 => {
-                    if pos {
-                        index_to_use += 1;
-                    } else {
-                        if index_to_use == 0 {
-                            break;
-                        }
-                        index_to_use -= 1;
-                    }
+        match &payment.kind {
+            PaymentKind::Sol => {
+                let destination = accounts.first().unwrap_or(wallet);
+                assert_keys_equal(destination.key, &destination_wallet)?;
+                if payer.lamports() < amount {
+                    return err!(CandyError::NotEnoughSOL);
                 }
-                0 => {
-                    found = true;
-                    arr[my_position_in_vec] |= mask;
+                invoke(
+                    &system_instruction::transfer(payer.key, destination.key, amount),
+                    &[payer.clone(), destination.clone(), system_program.clone()],
+                )?;
+            }
+            PaymentKind::Token(mint) => {
+                let token_account = assert_is_ata(&accounts[0], payer.key, mint)?;
+                assert_is_ata(&accounts[1], &destination_wallet, mint)?;
+                if token_account.amount < amount {
+                    return err!(CandyError::NotEnoughTokens);
+                }
+                spl_token_transfer(TokenTransferParams {
+                    source: accounts[0].clone(),
+                    destination: accounts[1].clone(),
+                    authority: payer.clone(),
+                    authority_signer_seeds: &[],
+                    token_program: token_program.clone(),
+                    amount: amount,
+                })?;
+            }
+            PaymentKind::BurnToken(mint) => {
+                let token_account = assert_is_ata(&accounts[0], payer.key, mint)?;
+                assert_keys_equal(accounts[1].key, mint)?;
+                if token_account.amount < amount {
+                    return err!(CandyError::NotEnoughTokens);
+                }
+                spl_token_burn(TokenBurnParams {
+                    mint: accounts[1].clone(),
+                    source: accounts[0].clone(),
+                    amount: amount,
+                    authority: payer.clone(),
+                    authority_signer_seeds: None,
+                    token_program: token_program.clone(),
+                })?;
+            }
+            PaymentKind::CollectionNft(collection) => {
+                assert_owned_by(&accounts[0], &spl_token::id())?;
+                let nft_account: spl_token::state::Account = assert_initialized(&accounts[0])?;
+                assert_keys_equal(&nft_account.owner, payer.key)?;
+                if nft_account.amount < 1 {
+                    return err!(CandyError::NotEnoughTokens);
                 }
-                _ => (),
+                assert_collection_nft(&accounts[1], &nft_account.mint, collection)?;
+                assert_is_ata(&accounts[2], &destination_wallet, &nft_account.mint)?;
+                spl_token_transfer(TokenTransferParams {
+                    source: accounts[0].clone(),
+                    destination: accounts[2].clone(),
+                    authority: payer.clone(),
+                    authority_signer_seeds: &[],
+                    token_program: token_program.clone(),
+                    amount: 1,
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the index of the `n`-th item that hasn't been minted yet and marks it as minted.
+/// Each remaining item is the pick of exactly one `n`, so they are all equally likely.
+pub fn get_nth_available_index(
+    arr: &mut [u8],
+    layout: &ConfigLinesLayout,
+    items_available: usize,
+    n: usize,
+) -> Result<usize> {
+    let bit_mask_vec_start = layout.minted_bit_mask_start(items_available);
+
+    let mut remaining = n;
+    for byte_index in 0..(items_available + 7) / 8 {
+        let position_in_vec = bit_mask_vec_start + byte_index;
+        let items_in_byte = (items_available - byte_index * 8).min(8);
+        // bits are taken from the left, set bits are minted items
+        let available = !arr[position_in_vec] & (u8::MAX << (8 - items_in_byte));
+        let available_count = available.count_ones() as usize;
+        if remaining >= available_count {
+            remaining -= available_count;
+            continue;
+        }
+        for bit in 0..items_in_byte {
+            let mask = 0b1000_0000 >> bit;
+            if available & mask == 0 {
+                continue;
             }
+            if remaining == 0 {
+                arr[position_in_vec] |= mask;
+                return Ok(byte_index * 8 + bit);
+            }
+            remaining -= 1;
         }
     }
-    Ok((index_to_use, found))
+    err!(CandyError::CannotFindUsableConfigLine)
 }
 
+/// Marks the config line at `index` as not minted so it can be minted again.
+pub fn release_minted_index(
+    arr: &mut [u8],
+    layout: &ConfigLinesLayout,
+    items_available: usize,
+    index: usize,
+) -> Result<()> {
+    if index >= items_available {
+        return err!(CandyError::IndexGreaterThanLength);
+    }
+    let position_in_vec = layout.minted_bit_mask_start(items_available) + index / 8;
+    let mask = 0b1000_0000 >> (index % 8);
+    if arr[position_in_vec] & mask == 0 {
+        return err!(CandyError::CannotFindUsableConfigLine);
+    }
+    arr[position_in_vec] &= !mask;
+    Ok(())
+}
+
+/// Returns the config line picked by `index` among the available ones, with its
+/// position for config lines candy machines.
 pub fn get_config_line(
     a: &Account<'_, CandyMachine>,
     index: usize,
     mint_number: u64,
-) -> Result<ConfigLine> {
+) -> Result<(ConfigLine, Option<usize>)> {
     if let Some(hs) = &a.data.hidden_settings {
-        return Ok(ConfigLine {
-            name: hs.name.clone() + "#" + &(mint_number + 1).to_string(),
-            uri: hs.uri.clone(),
-        });
+        return Ok((
+            ConfigLine {
+                name: hs.name.clone() + "#" + &(mint_number + 1).to_string(),
+                uri: hs.uri.clone(),
+            },
+            None,
+        ));
     }
     let a_info = a.to_account_info();
 
     let mut arr = a_info.data.borrow_mut();
 
-    let (mut index_to_use, good) =
-        get_good_index(&mut arr, a.data.items_available as usize, index, true)?;
-    if !good {
-        let (index_to_use_new, good_new) =
-            get_good_index(&mut arr, a.data.items_available as usize, index, false)?;
-        index_to_use = index_to_use_new;
-        if !good_new {
-            return err!(CandyError::CannotFindUsableConfigLine);
-        }
-    }
+    let layout = ConfigLinesLayout::load(&a.data, &arr)?;
+    let index_to_use =
+        get_nth_available_index(&mut arr, &layout, a.data.items_available as usize, index)?;
 
-    if arr[CONFIG_ARRAY_START + 4 + index_to_use * (CONFIG_LINE_SIZE)] == 1 {
+    // lines added by a resize can't be minted until add_config_lines fills them
+    let added_mask = 0b1000_0000 >> (index_to_use % 8);
+    if arr[layout.bit_mask_start(a.data.items_available as usize) + index_to_use / 8] & added_mask
+        == 0
+        || arr[layout.line_position(index_to_use)] == 1
+    {
         return err!(CandyError::CannotFindUsableConfigLine);
     }
 
-    let data_array = &mut arr[CONFIG_ARRAY_START + 4 + index_to_use * (CONFIG_LINE_SIZE)
-        ..CONFIG_ARRAY_START + 4 + (index_to_use + 1) * (CONFIG_LINE_SIZE)];
+    let data_array =
+        &mut arr[layout.line_position(index_to_use)..layout.line_position(index_to_use + 1)];
 
-    let mut name_vec = Vec::with_capacity(MAX_NAME_LENGTH);
-    let mut uri_vec = Vec::with_capacity(MAX_URI_LENGTH);
+    // compressed config lines only store the suffixes
+    let mut name_vec = layout.settings.prefix_name.as_bytes().to_vec();
+    let mut uri_vec = layout.settings.prefix_uri.as_bytes().to_vec();
 
     #[allow(clippy::needless_range_loop)]
-    for i in 4..4 + MAX_NAME_LENGTH {
+    for i in 4..4 + layout.name_length() {
         if data_array[i] == 0 {
             break;
         }
@@ -860,7 +1740,7 @@ pub fn get_config_line(
     }
 
     #[allow(clippy::needless_range_loop)]
-    for i in 8 + MAX_NAME_LENGTH..8 + MAX_NAME_LENGTH + MAX_URI_LENGTH {
+    for i in 8 + layout.name_length()..8 + layout.name_length() + layout.uri_length() {
         if data_array[i] == 0 {
             break;
         }
@@ -877,11 +1757,34 @@ pub fn get_config_line(
         },
     };
 
-    Ok(config_line)
+    Ok((config_line, Some(index_to_use)))
 }
 
-pub fn get_expected_remaining_accounts_count(candy: &CandyMachine) -> usize {
+pub fn get_expected_remaining_accounts_count(
+    candy: &CandyMachine,
+    features: &CandyMachineFeatures,
+    settings: &CandyMachineSettings,
+    active_phase: Option<&MintPhase>,
+    payment_option: Option<&PaymentOption>,
+) -> usize {
     let mut expected_count = 0;
+    if let Some(phase) = active_phase {
+        if phase.gate_mint.is_some() {
+            expected_count += 1;
+        }
+        if phase.allowlist_root.is_some() {
+            expected_count += 1;
+        }
+        if phase.mint_limit.is_some() || phase.allowlist_root.is_some() {
+            expected_count += 1;
+        }
+    }
+    if settings.mint_limit.is_some() {
+        expected_count += 1;
+    }
+    if settings.commit_reveal {
+        expected_count += 1;
+    }
     if let Some(gatekeeper) = &candy.data.gatekeeper {
         expected_count += 1;
         if gatekeeper.expire_on_use {
@@ -894,15 +1797,101 @@ pub fn get_expected_remaining_accounts_count(candy: &CandyMachine) -> usize {
             expected_count += 2;
         }
     }
-    if candy.token_mint.is_some() {
+    if let Some(payment_option) = payment_option {
+        expected_count += payment_option
+            .payments
+            .iter()
+            .map(Payment::accounts_len)
+            .sum::<usize>();
+    } else if candy.token_mint.is_some() {
         expected_count += 2;
     }
 
-    if is_feature_active(&candy.data.uuid, FREEZE_FEATURE_INDEX) {
-        expected_count += 2;
-        if candy.token_mint.is_some() {
-            expected_count += 1;
-        }
+    expected_count += get_freeze_accounts_count(candy, features, settings);
+    if let Some(programmable) = &settings.programmable {
+        expected_count += programmable.accounts_len();
+    }
+    if settings.mint_receipts {
+        expected_count += 1;
     }
     expected_count
 }
+
+fn get_freeze_accounts_count(
+    candy: &CandyMachine,
+    features: &CandyMachineFeatures,
+    settings: &CandyMachineSettings,
+) -> usize {
+    if !features.is_active(Feature::Freeze) {
+        return 0;
+    }
+    let mut count = if candy.token_mint.is_some() { 3 } else { 2 };
+    if settings.freeze_refund {
+        count += 1;
+    }
+    count
+}
+
+/// Number of remaining accounts of each NFT minted after the first one.
+fn get_item_accounts_count(
+    features: &CandyMachineFeatures,
+    settings: &CandyMachineSettings,
+) -> usize {
+    let mut count = 3;
+    if features.is_active(Feature::Freeze) {
+        count += 1;
+        if settings.freeze_refund {
+            count += 1;
+        }
+    }
+    if settings.programmable.is_some() {
+        count += 2;
+    }
+    if settings.mint_receipts {
+        count += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nth_available_index_equal_probability() {
+        let items_available = 21;
+        let layout = ConfigLinesLayout::new(None);
+        let bit_mask_vec_start = layout.minted_bit_mask_start(items_available);
+        let mut arr = vec![0; bit_mask_vec_start + items_available / 8 + 1];
+        let minted = [0, 3, 7, 8, 9, 15, 20];
+        for index in minted {
+            arr[bit_mask_vec_start + index / 8] |= 0b1000_0000 >> (index % 8);
+        }
+        let available: Vec<usize> = (0..items_available)
+            .filter(|index| !minted.contains(index))
+            .collect();
+
+        // every remaining item is picked by exactly one n, so a uniform n is a uniform pick
+        let picks: Vec<usize> = (0..available.len())
+            .map(|n| {
+                get_nth_available_index(&mut arr.clone(), &layout, items_available, n).unwrap()
+            })
+            .collect();
+        assert_eq!(picks, available);
+        assert!(get_nth_available_index(
+            &mut arr.clone(),
+            &layout,
+            items_available,
+            available.len()
+        )
+        .is_err());
+
+        // picking an item marks it as minted
+        let index = get_nth_available_index(&mut arr, &layout, items_available, 0).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(
+            get_nth_available_index(&mut arr, &layout, items_available, 0).unwrap(),
+            2
+        );
+    }
+}
@@ -1,15 +1,23 @@
 pub mod add_config_lines;
+pub mod allowlist;
 pub mod collection;
+pub mod commit_reveal;
 pub mod freeze;
 pub mod initialize;
 pub mod mint;
+pub mod resize;
+pub mod reveal;
 pub mod update;
 pub mod withdraw;
 
 pub use add_config_lines::*;
+pub use allowlist::*;
 pub use collection::*;
+pub use commit_reveal::*;
 pub use freeze::*;
 pub use initialize::*;
 pub use mint::*;
+pub use resize::*;
+pub use reveal::*;
 pub use update::*;
 pub use withdraw::*;
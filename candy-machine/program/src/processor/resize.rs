@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use solana_program::{
+    entrypoint::MAX_PERMITTED_DATA_INCREASE, program::invoke, system_instruction,
+};
+
+use crate::{
+    CandyError, CandyMachine, CandyMachineFeatures, CandyMachineSettings, ConfigLinesLayout,
+    Feature,
+};
+
+/// Reallocate the candy machine account to hold more items.
+#[derive(Accounts)]
+pub struct ResizeCandyMachine<'info> {
+    #[account(mut, has_one = authority)]
+    candy_machine: Account<'info, CandyMachine>,
+    #[account(mut)]
+    authority: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+// grows the config lines region to `items_available` lines, the new lines are added with
+// add_config_lines before they can be minted. Accounts grow by at most
+// MAX_PERMITTED_DATA_INCREASE bytes per instruction, larger resizes take several calls.
+pub fn handle_resize_candy_machine(
+    ctx: Context<ResizeCandyMachine>,
+    items_available: u64,
+) -> Result<()> {
+    let candy_machine = &mut ctx.accounts.candy_machine;
+    let authority = &ctx.accounts.authority;
+    if candy_machine.data.hidden_settings.is_some() {
+        return err!(CandyError::HiddenSettingsConfigsDoNotHaveConfigLines);
+    }
+    let old_items = candy_machine.data.items_available as usize;
+    let new_items = items_available as usize;
+    if new_items <= old_items {
+        return err!(CandyError::CannotShrinkCandyMachine);
+    }
+    let candy_machine_info = candy_machine.to_account_info();
+    // frozen NFTs of a sold out candy machine can already be thawed
+    if CandyMachineFeatures::load(&candy_machine_info, &candy_machine.data)?
+        .is_active(Feature::Freeze)
+        && candy_machine.items_redeemed >= candy_machine.data.items_available
+    {
+        return err!(CandyError::NoResizeAfterFrozenSellOut);
+    }
+
+    let settings = CandyMachineSettings::load(&candy_machine_info, &candy_machine.data)?;
+    let layout = ConfigLinesLayout::load(&candy_machine.data, &candy_machine_info.data.borrow())?;
+    let increase = layout.end(new_items) - layout.end(old_items);
+    if increase > MAX_PERMITTED_DATA_INCREASE {
+        return err!(CandyError::ResizeTooLarge);
+    }
+    let new_len = candy_machine_info
+        .data_len()
+        .checked_add(increase)
+        .ok_or(CandyError::NumericalOverflowError)?;
+
+    let rent_exempt = Rent::get()?.minimum_balance(new_len);
+    if rent_exempt > candy_machine_info.lamports() {
+        invoke(
+            &system_instruction::transfer(
+                &authority.key(),
+                &candy_machine_info.key(),
+                rent_exempt - candy_machine_info.lamports(),
+            ),
+            &[
+                authority.to_account_info(),
+                candy_machine_info.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+    candy_machine_info.realloc(new_len, false)?;
+
+    // the bitmasks follow the config lines, they are moved after the new lines
+    let mut data = candy_machine_info.data.borrow_mut();
+    let mask_len = old_items / 8 + 1;
+    let old_bit_mask_start = layout.bit_mask_start(old_items);
+    let added_mask = data[old_bit_mask_start..old_bit_mask_start + mask_len].to_vec();
+    let old_minted_start = layout.minted_bit_mask_start(old_items);
+    let minted_mask = data[old_minted_start..old_minted_start + mask_len].to_vec();
+    data[layout.line_position(old_items)..].fill(0);
+
+    let vec_start = layout.line_position(new_items);
+    data[vec_start..vec_start + 4].copy_from_slice(&((new_items / 8) as u32).to_le_bytes());
+    let bit_mask_start = layout.bit_mask_start(new_items);
+    data[bit_mask_start..bit_mask_start + mask_len].copy_from_slice(&added_mask);
+    let minted_start = layout.minted_bit_mask_start(new_items);
+    data[minted_start..minted_start + mask_len].copy_from_slice(&minted_mask);
+    drop(data);
+
+    candy_machine.data.items_available = items_available;
+    // the zeroed settings region already reads as the default settings
+    if settings == CandyMachineSettings::default() {
+        return Ok(());
+    }
+    settings.save(&candy_machine_info, &candy_machine.data)
+}
@@ -0,0 +1,226 @@
+use anchor_lang::prelude::*;
+use arrayref::array_ref;
+use mpl_token_metadata::{
+    instruction::update_metadata_accounts_v2,
+    state::{DataV2, Metadata, MAX_NAME_LENGTH, MAX_URI_LENGTH},
+};
+use solana_program::{keccak, program::invoke_signed};
+
+use crate::{
+    assert_owned_by, cmp_pubkeys,
+    constants::{CONFIG_LINE_SIZE, PREFIX},
+    CandyError, CandyMachine, CandyMachineSettings, ConfigLine, RevealLines,
+};
+
+/// Create the reveal lines account of a hidden settings candy machine.
+#[derive(Accounts)]
+pub struct InitializeRevealLines<'info> {
+    #[account(has_one = authority)]
+    candy_machine: Account<'info, CandyMachine>,
+    authority: Signer<'info>,
+    #[account(zero, rent_exempt = skip, constraint = reveal_lines.to_account_info().owner == program_id && reveal_lines.to_account_info().data_len() >= RevealLines::space(candy_machine.data.items_available).ok_or(CandyError::NumericalOverflowError)?)]
+    reveal_lines: Account<'info, RevealLines>,
+}
+
+/// Add the real config lines of a hidden settings candy machine, in mint order.
+#[derive(Accounts)]
+pub struct AddRevealLines<'info> {
+    #[account(has_one = authority)]
+    candy_machine: Account<'info, CandyMachine>,
+    authority: Signer<'info>,
+    #[account(mut, has_one = candy_machine)]
+    reveal_lines: Account<'info, RevealLines>,
+}
+
+/// Reveal a minted NFT of a hidden settings candy machine from its verified reveal lines.
+#[derive(Accounts)]
+pub struct RevealNFT<'info> {
+    candy_machine: Box<Account<'info, CandyMachine>>,
+    /// CHECK: account constraints checked in account trait
+    #[account(seeds=[PREFIX.as_bytes(), candy_machine.key().as_ref()], bump)]
+    candy_machine_creator: UncheckedAccount<'info>,
+    #[account(has_one = candy_machine)]
+    reveal_lines: Box<Account<'info, RevealLines>>,
+    /// CHECK: account checked in handler and CPI
+    #[account(mut)]
+    metadata: UncheckedAccount<'info>,
+    /// CHECK: account constraints checked in account trait
+    #[account(address = mpl_token_metadata::id())]
+    token_metadata_program: UncheckedAccount<'info>,
+}
+
+pub fn handle_initialize_reveal_lines(ctx: Context<InitializeRevealLines>) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    let settings =
+        CandyMachineSettings::load(&candy_machine.to_account_info(), &candy_machine.data)?;
+    if !settings.on_chain_reveal {
+        return err!(CandyError::OnChainRevealNotEnabled);
+    }
+    let reveal_lines = &mut ctx.accounts.reveal_lines;
+    reveal_lines.candy_machine = candy_machine.key();
+    reveal_lines.hash = [0; 32];
+    reveal_lines.lines_uploaded = 0;
+    reveal_lines.verified = false;
+    Ok(())
+}
+
+pub fn handle_add_reveal_lines(
+    ctx: Context<AddRevealLines>,
+    index: u64,
+    config_lines: Vec<ConfigLine>,
+) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    // lines uploaded before the end of the mint would tell minters what they get
+    if !candy_machine.is_mint_complete(Clock::get()?.unix_timestamp) {
+        return err!(CandyError::MintNotComplete);
+    }
+    let hidden_settings = candy_machine
+        .data
+        .hidden_settings
+        .as_ref()
+        .ok_or(CandyError::OnChainRevealNotEnabled)?;
+    let reveal_lines = &mut ctx.accounts.reveal_lines;
+    if reveal_lines.verified {
+        return err!(CandyError::RevealLinesAlreadyVerified);
+    }
+    // the running hash can't be rewound, uploading from index 0 starts over
+    if index == 0 {
+        reveal_lines.hash = [0; 32];
+        reveal_lines.lines_uploaded = 0;
+    } else if index != reveal_lines.lines_uploaded {
+        return err!(CandyError::RevealLinesOutOfOrder);
+    }
+    let total = index
+        .checked_add(config_lines.len() as u64)
+        .ok_or(CandyError::NumericalOverflowError)?;
+    if total > candy_machine.data.items_available {
+        return err!(CandyError::IndexGreaterThanLength);
+    }
+
+    let reveal_lines_info = reveal_lines.to_account_info();
+    let mut data = reveal_lines_info.data.borrow_mut();
+    for (i, line) in config_lines.iter().enumerate() {
+        if line.name.len() > MAX_NAME_LENGTH || line.uri.len() > MAX_URI_LENGTH {
+            return err!(CandyError::ConfigLineTooLong);
+        }
+        let serialized = line.try_to_vec()?;
+        reveal_lines.hash = keccak::hashv(&[&reveal_lines.hash, &serialized]).0;
+
+        let position = RevealLines::line_position(index as usize + i);
+        let line_data = &mut data[position..position + CONFIG_LINE_SIZE];
+        line_data.fill(0);
+        // padded like uncompressed config lines so each line has a fixed position
+        let name_end = 4 + line.name.len();
+        line_data[..name_end].copy_from_slice(&serialized[..name_end]);
+        let uri_start = 4 + MAX_NAME_LENGTH;
+        line_data[uri_start..uri_start + 4 + line.uri.len()]
+            .copy_from_slice(&serialized[name_end..]);
+    }
+    reveal_lines.lines_uploaded = total;
+
+    // the hash commits to every line, minted or not
+    if total == candy_machine.data.items_available {
+        if reveal_lines.hash != hidden_settings.hash {
+            return err!(CandyError::RevealHashMismatch);
+        }
+        reveal_lines.verified = true;
+    }
+    Ok(())
+}
+
+pub fn handle_reveal_nft(ctx: Context<RevealNFT>) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    let candy_machine_creator = &ctx.accounts.candy_machine_creator;
+    let reveal_lines = &ctx.accounts.reveal_lines;
+    if !reveal_lines.verified {
+        return err!(CandyError::RevealLinesNotVerified);
+    }
+    let hidden_settings = candy_machine
+        .data
+        .hidden_settings
+        .as_ref()
+        .ok_or(CandyError::OnChainRevealNotEnabled)?;
+
+    let metadata_info = ctx.accounts.metadata.to_account_info();
+    assert_owned_by(&metadata_info, &mpl_token_metadata::id())?;
+    let metadata = {
+        let data = metadata_info.data.borrow();
+        if data.is_empty() || data[0] != mpl_token_metadata::state::Key::MetadataV1 as u8 {
+            return err!(CandyError::InvalidMetadataAccount);
+        }
+        Metadata::deserialize(&mut data.as_ref())?
+    };
+    // only NFTs minted by this candy machine and not revealed yet
+    let minted_by_candy_machine = metadata.data.creators.as_ref().map_or(false, |creators| {
+        creators.first().map_or(false, |creator| {
+            creator.verified && cmp_pubkeys(&creator.address, &candy_machine_creator.key())
+        })
+    });
+    if !minted_by_candy_machine
+        || !cmp_pubkeys(&metadata.update_authority, &candy_machine_creator.key())
+    {
+        return err!(CandyError::NftNotRevealable);
+    }
+    // hidden settings NFTs are named after their mint number
+    let mint_number = metadata
+        .data
+        .name
+        .trim_end_matches(char::from(0))
+        .strip_prefix(&(hidden_settings.name.clone() + "#"))
+        .and_then(|number| number.parse::<u64>().ok())
+        .filter(|number| *number > 0 && *number <= reveal_lines.lines_uploaded)
+        .ok_or(CandyError::NftNotRevealable)?;
+    let config_line = get_reveal_line(&reveal_lines.to_account_info(), mint_number - 1)?;
+
+    let cm_key = candy_machine.key();
+    let creator_bump = *ctx.bumps.get("candy_machine_creator").unwrap();
+    let authority_seeds = [PREFIX.as_bytes(), cm_key.as_ref(), &[creator_bump]];
+    invoke_signed(
+        &update_metadata_accounts_v2(
+            ctx.accounts.token_metadata_program.key(),
+            metadata_info.key(),
+            candy_machine_creator.key(),
+            Some(candy_machine.authority),
+            Some(DataV2 {
+                name: config_line.name,
+                symbol: metadata
+                    .data
+                    .symbol
+                    .trim_end_matches(char::from(0))
+                    .to_string(),
+                uri: config_line.uri,
+                seller_fee_basis_points: metadata.data.seller_fee_basis_points,
+                creators: metadata.data.creators,
+                collection: metadata.collection,
+                uses: metadata.uses,
+            }),
+            None,
+            if !candy_machine.data.is_mutable {
+                Some(false)
+            } else {
+                None
+            },
+        ),
+        &[
+            ctx.accounts.token_metadata_program.to_account_info(),
+            metadata_info,
+            candy_machine_creator.to_account_info(),
+        ],
+        &[&authority_seeds],
+    )?;
+    Ok(())
+}
+
+fn get_reveal_line(reveal_lines: &AccountInfo, index: u64) -> Result<ConfigLine> {
+    let data = reveal_lines.data.borrow();
+    let position = RevealLines::line_position(index as usize);
+    let line_data = &data[position..position + CONFIG_LINE_SIZE];
+    let name_length = u32::from_le_bytes(*array_ref![line_data, 0, 4]) as usize;
+    let uri_start = 4 + MAX_NAME_LENGTH;
+    let uri_length = u32::from_le_bytes(*array_ref![line_data, uri_start, 4]) as usize;
+    let name = String::from_utf8(line_data[4..4 + name_length].to_vec())
+        .map_err(|_| CandyError::CannotFindUsableConfigLine)?;
+    let uri = String::from_utf8(line_data[uri_start + 4..uri_start + 4 + uri_length].to_vec())
+        .map_err(|_| CandyError::CannotFindUsableConfigLine)?;
+    Ok(ConfigLine { name, uri })
+}
@@ -1,8 +1,11 @@
 use anchor_lang::prelude::*;
+use solana_program::{program::invoke, system_instruction, system_program};
 
 use crate::{
-    constants::{COLLECTIONS_FEATURE_INDEX, FREEZE_FEATURE_INDEX},
-    is_feature_active, CandyError, CandyMachine, CandyMachineData,
+    assert_valid_mint_phases, assert_valid_payment_options, constants::FEATURES_START,
+    get_space_for_candy, BotTax, BotTaxCondition, CandyError, CandyMachine, CandyMachineData,
+    CandyMachineFeatures, CandyMachineSettings, ConfigLinesLayout, Feature, MintLimit, MintPhase,
+    PaymentOption, ProgrammableSettings,
 };
 
 /// Update the candy machine state.
@@ -17,7 +20,52 @@ pub struct UpdateCandyMachine<'info> {
     /// CHECK: wallet can be any account and is not written to or read
     wallet: UncheckedAccount<'info>,
     // Remaining accounts.
-    // token mint
+    // token mint // Only for update_candy_machine
+    // system_program // Only needed when the settings outgrow the account, the authority
+    //                   (writable) then pays the rent of the added space
+}
+
+// candy machines created without room for the settings are reallocated to fit them
+fn save_settings<'info>(
+    ctx: &Context<'_, '_, '_, 'info, UpdateCandyMachine<'info>>,
+    settings: &CandyMachineSettings,
+) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    let candy_machine_info = candy_machine.to_account_info();
+    let start = get_space_for_candy(
+        &candy_machine.data,
+        &ConfigLinesLayout::load(&candy_machine.data, &candy_machine_info.data.borrow())?,
+    )?;
+    let required_len = start
+        + settings
+            .try_to_vec()
+            .map_err(|_| CandyError::InvalidCandyMachineSettings)?
+            .len();
+    if candy_machine_info.data_len() < required_len {
+        let rent_exempt = Rent::get()?.minimum_balance(required_len);
+        if rent_exempt > candy_machine_info.lamports() {
+            let system_program = ctx
+                .remaining_accounts
+                .iter()
+                .find(|account| account.key == &system_program::ID)
+                .ok_or(CandyError::NotEnoughSpaceForSettings)?;
+            let authority = ctx.accounts.authority.to_account_info();
+            invoke(
+                &system_instruction::transfer(
+                    authority.key,
+                    candy_machine_info.key,
+                    rent_exempt - candy_machine_info.lamports(),
+                ),
+                &[
+                    authority,
+                    candy_machine_info.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+        candy_machine_info.realloc(required_len, false)?;
+    }
+    settings.save(&candy_machine_info, &candy_machine.data)
 }
 
 pub fn handle_update_authority(
@@ -28,7 +76,9 @@ pub fn handle_update_authority(
 
     // Do not allow changing update authority if collections is active
     if let Some(new_auth) = new_authority {
-        if is_feature_active(&candy_machine.data.uuid, COLLECTIONS_FEATURE_INDEX) {
+        if CandyMachineFeatures::load(&candy_machine.to_account_info(), &candy_machine.data)?
+            .is_active(Feature::Collections)
+        {
             return err!(CandyError::NoChangingAuthorityWithCollection);
         } else {
             candy_machine.authority = new_auth;
@@ -64,19 +114,213 @@ pub fn handle_update_candy_machine(
         return err!(CandyError::CannotSwitchFromHiddenSettings);
     }
 
-    let old_uuid = candy_machine.data.uuid.clone();
-    if is_feature_active(&old_uuid, FREEZE_FEATURE_INDEX) && candy_machine.token_mint != token_mint
+    if data.whitelist_mint_settings.is_some()
+        && !CandyMachineSettings::load(&candy_machine.to_account_info(), &candy_machine.data)?
+            .phases
+            .is_empty()
     {
+        return err!(CandyError::MintPhasesWithWhitelistSettings);
+    }
+
+    let features =
+        CandyMachineFeatures::load(&candy_machine.to_account_info(), &candy_machine.data)?;
+    if features.is_active(Feature::Freeze) && candy_machine.token_mint != token_mint {
         return err!(CandyError::NoChangingTokenWithFreeze);
     }
-    if is_feature_active(&old_uuid, COLLECTIONS_FEATURE_INDEX) && !data.retain_authority {
+    if features.is_active(Feature::Collections) && !data.retain_authority {
         return err!(CandyError::CandyCollectionRequiresRetainAuthority);
     }
+    if !data.retain_authority
+        && CandyMachineSettings::load(&candy_machine.to_account_info(), &candy_machine.data)?
+            .on_chain_reveal
+    {
+        return err!(CandyError::InvalidOnChainReveal);
+    }
 
+    let old_uuid = candy_machine.data.uuid.clone();
     candy_machine.wallet = ctx.accounts.wallet.key();
     candy_machine.data = data;
     candy_machine.data.uuid = old_uuid;
     candy_machine.token_mint = token_mint;
 
+    // the features header is stored right after the candy machine struct
+    if 8 + candy_machine.try_to_vec()?.len() > FEATURES_START {
+        return err!(CandyError::CandyMachineDataTooLarge);
+    }
     Ok(())
 }
+
+// replaces the mint phases, only allowed before the first item is minted
+pub fn handle_update_mint_phases(
+    ctx: Context<UpdateCandyMachine>,
+    phases: Vec<MintPhase>,
+) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    candy_machine.assert_not_minted(error!(CandyError::NoChangingPhasesDuringMint))?;
+    if !phases.is_empty() && candy_machine.data.whitelist_mint_settings.is_some() {
+        return err!(CandyError::MintPhasesWithWhitelistSettings);
+    }
+    assert_valid_mint_phases(&phases)?;
+
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut settings = CandyMachineSettings::load(&candy_machine_info, &candy_machine.data)?;
+    settings.phases = phases;
+    save_settings(&ctx, &settings)
+}
+
+pub fn handle_update_mint_limit(
+    ctx: Context<UpdateCandyMachine>,
+    mint_limit: Option<MintLimit>,
+) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    if let Some(MintLimit { limit: 0, .. }) = mint_limit {
+        return err!(CandyError::InvalidMintLimit);
+    }
+
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut settings = CandyMachineSettings::load(&candy_machine_info, &candy_machine.data)?;
+    settings.mint_limit = mint_limit;
+    save_settings(&ctx, &settings)
+}
+
+pub fn handle_update_payment_options(
+    ctx: Context<UpdateCandyMachine>,
+    payment_options: Vec<PaymentOption>,
+) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    // freeze escrows the proceeds of a single payment
+    if !payment_options.is_empty()
+        && CandyMachineFeatures::load(&candy_machine.to_account_info(), &candy_machine.data)?
+            .is_active(Feature::Freeze)
+    {
+        return err!(CandyError::NoPaymentOptionsWithFreeze);
+    }
+    assert_valid_payment_options(&payment_options)?;
+
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut settings = CandyMachineSettings::load(&candy_machine_info, &candy_machine.data)?;
+    settings.payment_options = payment_options;
+    save_settings(&ctx, &settings)
+}
+
+pub fn handle_update_commit_reveal(
+    ctx: Context<UpdateCandyMachine>,
+    commit_reveal: bool,
+) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut settings = CandyMachineSettings::load(&candy_machine_info, &candy_machine.data)?;
+    settings.commit_reveal = commit_reveal;
+    save_settings(&ctx, &settings)
+}
+
+// keeps the creator PDA as update authority of minted NFTs so they can be revealed on-chain
+pub fn handle_update_on_chain_reveal(
+    ctx: Context<UpdateCandyMachine>,
+    on_chain_reveal: bool,
+) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    candy_machine.assert_not_minted(error!(CandyError::NoChangingOnChainRevealDuringMint))?;
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut settings = CandyMachineSettings::load(&candy_machine_info, &candy_machine.data)?;
+    // verifying the collection during mint requires the NFT update authority
+    if on_chain_reveal
+        && (candy_machine.data.hidden_settings.is_none()
+            || !candy_machine.data.retain_authority
+            || CandyMachineFeatures::load(&candy_machine_info, &candy_machine.data)?
+                .is_active(Feature::Collections)
+            || settings.programmable.is_some())
+    {
+        return err!(CandyError::InvalidOnChainReveal);
+    }
+
+    settings.on_chain_reveal = on_chain_reveal;
+    save_settings(&ctx, &settings)
+}
+
+// switches between minting classic and programmable NFTs, only allowed before the first item is minted
+pub fn handle_update_programmable(
+    ctx: Context<UpdateCandyMachine>,
+    programmable: Option<ProgrammableSettings>,
+) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    candy_machine.assert_not_minted(error!(CandyError::NoChangingProgrammableDuringMint))?;
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut settings = CandyMachineSettings::load(&candy_machine_info, &candy_machine.data)?;
+    // on-chain reveals update the metadata with the legacy instruction
+    if programmable.is_some() && settings.on_chain_reveal {
+        return err!(CandyError::InvalidOnChainReveal);
+    }
+    // refunds burn with the legacy instruction
+    if programmable.is_some() && settings.freeze_refund {
+        return err!(CandyError::InvalidFreezeRefund);
+    }
+    settings.programmable = programmable;
+    save_settings(&ctx, &settings)
+}
+
+pub fn handle_update_bot_tax(
+    ctx: Context<UpdateCandyMachine>,
+    bot_tax: Option<BotTax>,
+) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    if let Some(bot_tax) = &bot_tax {
+        // the wallet of token candy machines is a token account
+        if bot_tax.conditions & !BotTaxCondition::ALL != 0
+            || (bot_tax.pay_to_wallet && candy_machine.token_mint.is_some())
+        {
+            return err!(CandyError::InvalidBotTax);
+        }
+    }
+
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut settings = CandyMachineSettings::load(&candy_machine_info, &candy_machine.data)?;
+    settings.bot_tax = bot_tax;
+    save_settings(&ctx, &settings)
+}
+
+// lets minters burn their frozen NFT for a refund, only allowed before the first item is minted
+pub fn handle_update_freeze_refund(
+    ctx: Context<UpdateCandyMachine>,
+    freeze_refund: bool,
+) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    candy_machine.assert_not_minted(error!(CandyError::NoChangingFreezeRefundDuringMint))?;
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut settings = CandyMachineSettings::load(&candy_machine_info, &candy_machine.data)?;
+    // refunded items are minted again from their config line
+    if freeze_refund
+        && (candy_machine.data.hidden_settings.is_some() || settings.programmable.is_some())
+    {
+        return err!(CandyError::InvalidFreezeRefund);
+    }
+
+    settings.freeze_refund = freeze_refund;
+    save_settings(&ctx, &settings)
+}
+
+// creates a MintReceipt for each NFT minted from now on
+pub fn handle_update_mint_receipts(
+    ctx: Context<UpdateCandyMachine>,
+    mint_receipts: bool,
+) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    let candy_machine_info = candy_machine.to_account_info();
+    let mut settings = CandyMachineSettings::load(&candy_machine_info, &candy_machine.data)?;
+    settings.mint_receipts = mint_receipts;
+    save_settings(&ctx, &settings)
+}
+
+// moves the feature flags of candy machines created before the features header out of the uuid
+pub fn handle_migrate_features(ctx: Context<UpdateCandyMachine>) -> Result<()> {
+    let candy_machine = &ctx.accounts.candy_machine;
+    let candy_machine_info = candy_machine.to_account_info();
+    let features = CandyMachineFeatures::load(&candy_machine_info, &candy_machine.data)?;
+    if features.version != 0 {
+        return err!(CandyError::FeaturesAlreadyMigrated);
+    }
+    if 8 + candy_machine.try_to_vec()?.len() > FEATURES_START {
+        return err!(CandyError::CandyMachineDataTooLarge);
+    }
+    features.save(&candy_machine_info)
+}
@@ -1,10 +1,6 @@
 use anchor_lang::{prelude::*, AccountsClose};
 
-use crate::{
-    cmp_pubkeys,
-    constants::{FREEZE_FEATURE_INDEX, FREEZE_LOCK_FEATURE_INDEX},
-    is_feature_active, CandyError, CandyMachine, CollectionPDA,
-};
+use crate::{cmp_pubkeys, CandyError, CandyMachine, CandyMachineFeatures, CollectionPDA, Feature};
 
 /// Withdraw SOL from candy machine account.
 #[derive(Accounts)]
@@ -22,10 +18,12 @@ pub fn handle_withdraw_funds<'info>(
 ) -> Result<()> {
     let authority = &ctx.accounts.authority;
     let candy_machine = &ctx.accounts.candy_machine;
-    if is_feature_active(&candy_machine.data.uuid, FREEZE_FEATURE_INDEX) {
+    let features =
+        CandyMachineFeatures::load(&candy_machine.to_account_info(), &candy_machine.data)?;
+    if features.is_active(Feature::Freeze) {
         return err!(CandyError::NoWithdrawWithFreeze);
     }
-    if is_feature_active(&candy_machine.data.uuid, FREEZE_LOCK_FEATURE_INDEX) {
+    if features.is_active(Feature::FreezeLock) {
         return err!(CandyError::NoWithdrawWithFrozenFunds);
     }
 
@@ -1,5 +1,13 @@
-use crate::{constants::FREEZE_FEE, CandyError};
+use crate::{
+    constants::{
+        BOT_FEE, COLLECTIONS_FEATURE_INDEX, CONFIG_ARRAY_START, CONFIG_LINE_SETTINGS_FEATURE_INDEX,
+        CONFIG_LINE_SETTINGS_SIZE, CONFIG_LINE_SIZE, FEATURES_START, FREEZE_FEATURE_INDEX,
+        FREEZE_FEE, FREEZE_LOCK_FEATURE_INDEX, MAX_ALLOWLIST_PROOF_LENGTH,
+    },
+    get_space_for_candy, is_feature_active, CandyError,
+};
 use anchor_lang::prelude::*;
+use mpl_token_metadata::state::{MAX_NAME_LENGTH, MAX_URI_LENGTH};
 
 /// Candy machine state and config data.
 #[account]
@@ -104,6 +112,523 @@ impl CandyMachine {
             Ok(())
         }
     }
+
+    /// Returns true once no more items can be minted, either sold out or ended.
+    pub fn is_mint_complete(&self, timestamp: i64) -> bool {
+        if self.items_redeemed >= self.data.items_available {
+            return true;
+        }
+        match &self.data.end_settings {
+            Some(EndSettings {
+                end_setting_type: EndSettingType::Date,
+                number,
+            }) => timestamp > *number as i64,
+            Some(EndSettings {
+                end_setting_type: EndSettingType::Amount,
+                number,
+            }) => self.items_redeemed >= *number,
+            None => false,
+        }
+    }
+}
+
+/// Settings stored in the candy machine account after the config lines region.
+/// Fields are only ever appended, a region that is too short (or missing) reads
+/// its trailing fields as zeroes.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct CandyMachineSettings {
+    /// Ordered mint phases, when empty `go_live_date` and `price` apply.
+    pub phases: Vec<MintPhase>,
+    /// If [`Some`] caps the number of items a wallet can mint across all phases
+    pub mint_limit: Option<MintLimit>,
+    /// Payment options minters choose from, when not empty they replace the
+    /// price and `token_mint` payment.
+    pub payment_options: Vec<PaymentOption>,
+    /// Minters must create a [`MintCommitment`] before minting, the item is then picked
+    /// from a slot hash that didn't exist at commit time. Commitments hold the bot tax
+    /// lamports, which are lost if the commitment expires.
+    pub commit_reveal: bool,
+    /// Minted NFTs keep the candy machine creator PDA as update authority until they
+    /// are revealed on-chain from [`RevealLines`], only for hidden settings.
+    pub on_chain_reveal: bool,
+    /// If [`Some`] items are minted as programmable NFTs
+    pub programmable: Option<ProgrammableSettings>,
+    /// If [`Some`] replaces the default bot tax policy
+    pub bot_tax: Option<BotTax>,
+    /// Minters can burn their frozen NFT for a refund while the mint funds are
+    /// locked in the [`FreezePDA`], only for config lines and classic NFTs.
+    pub freeze_refund: bool,
+    /// A [`MintReceipt`] is created for each minted NFT
+    pub mint_receipts: bool,
+}
+
+impl CandyMachineSettings {
+    pub fn load(candy_machine: &AccountInfo, data: &CandyMachineData) -> Result<Self> {
+        let account_data = candy_machine.data.borrow();
+        let start = get_space_for_candy(data, &ConfigLinesLayout::load(data, &account_data)?)?;
+        if account_data.len() <= start {
+            return Ok(Self::default());
+        }
+        let mut bytes = account_data[start..].to_vec();
+        let padding = Self::default()
+            .try_to_vec()
+            .map_err(|_| CandyError::InvalidCandyMachineSettings)?
+            .len();
+        bytes.resize(bytes.len() + padding, 0);
+        Self::deserialize(&mut bytes.as_slice())
+            .map_err(|_| error!(CandyError::InvalidCandyMachineSettings))
+    }
+
+    pub fn save(&self, candy_machine: &AccountInfo, data: &CandyMachineData) -> Result<()> {
+        let bytes = self
+            .try_to_vec()
+            .map_err(|_| CandyError::InvalidCandyMachineSettings)?;
+        let mut account_data = candy_machine.data.borrow_mut();
+        let start = get_space_for_candy(data, &ConfigLinesLayout::load(data, &account_data)?)?;
+        if account_data.len() < start + bytes.len() {
+            return err!(CandyError::NotEnoughSpaceForSettings);
+        }
+        account_data[start..start + bytes.len()].copy_from_slice(&bytes);
+        account_data[start + bytes.len()..].fill(0);
+        Ok(())
+    }
+
+    /// Returns the latest phase that started at `timestamp` and has not ended yet.
+    pub fn active_phase(&self, timestamp: i64) -> Option<(usize, &MintPhase)> {
+        self.phases
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, phase)| phase.start_date <= timestamp)
+            .filter(|(_, phase)| phase.end_date.map_or(true, |end| timestamp < end))
+    }
+
+    /// Returns the bot tax policy applying during `active_phase`.
+    pub fn bot_tax(&self, active_phase: Option<&MintPhase>) -> BotTax {
+        let mut bot_tax = self.bot_tax.clone().unwrap_or_default();
+        if bot_tax.skip_allowlist_phases
+            && active_phase.map_or(false, |phase| phase.allowlist_root.is_some())
+        {
+            bot_tax.conditions = 0;
+        }
+        bot_tax
+    }
+}
+
+/// Features enabled on the candy machine, stored at [`FEATURES_START`] between the
+/// candy machine struct and the config lines region. Candy machines created before
+/// the header existed (version 0) keep their flags in the uuid until migrated.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct CandyMachineFeatures {
+    pub version: u8,
+    /// Bitmask of [`Feature`]
+    pub flags: u16,
+}
+
+impl CandyMachineFeatures {
+    pub const VERSION: u8 = 1;
+
+    pub fn load(candy_machine: &AccountInfo, data: &CandyMachineData) -> Result<Self> {
+        Self::from_account_data(data, &candy_machine.data.borrow())
+    }
+
+    pub fn from_account_data(data: &CandyMachineData, account_data: &[u8]) -> Result<Self> {
+        let features = Self::deserialize(&mut &account_data[FEATURES_START..CONFIG_ARRAY_START])
+            .map_err(|_| error!(CandyError::InvalidFeatures))?;
+        match features.version {
+            0 => Ok(Self::from_uuid(&data.uuid)),
+            Self::VERSION => Ok(features),
+            _ => err!(CandyError::InvalidFeatures),
+        }
+    }
+
+    /// Reads the flags of a version 0 candy machine from its uuid.
+    pub fn from_uuid(uuid: &str) -> Self {
+        let flags = Feature::ALL
+            .iter()
+            .filter(|feature| is_feature_active(uuid, feature.uuid_index()))
+            .fold(0, |flags, feature| flags | *feature as u16);
+        Self { version: 0, flags }
+    }
+
+    /// Writes the flags with the current version, migrating version 0 candy machines.
+    pub fn save(&self, candy_machine: &AccountInfo) -> Result<()> {
+        let bytes = Self {
+            version: Self::VERSION,
+            flags: self.flags,
+        }
+        .try_to_vec()
+        .map_err(|_| CandyError::InvalidFeatures)?;
+        let mut account_data = candy_machine.data.borrow_mut();
+        account_data[FEATURES_START..CONFIG_ARRAY_START].fill(0);
+        account_data[FEATURES_START..FEATURES_START + bytes.len()].copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    pub fn is_active(&self, feature: Feature) -> bool {
+        self.flags & feature as u16 != 0
+    }
+
+    pub fn set(&mut self, feature: Feature) {
+        self.flags |= feature as u16;
+    }
+
+    pub fn remove(&mut self, feature: Feature) {
+        self.flags &= !(feature as u16);
+    }
+}
+
+/// Optional features of a candy machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u16)]
+pub enum Feature {
+    /// A collection is set, minted NFTs are verified items of it
+    Collections = 1 << 0,
+    /// Minted NFTs are frozen and the mint funds locked in the [`FreezePDA`]
+    Freeze = 1 << 1,
+    /// Mint funds are locked in the [`FreezePDA`]
+    FreezeLock = 1 << 2,
+    /// Config lines are stored compressed, see [`ConfigLineSettings`]
+    ConfigLineSettings = 1 << 3,
+}
+
+impl Feature {
+    pub const ALL: [Self; 4] = [
+        Self::Collections,
+        Self::Freeze,
+        Self::FreezeLock,
+        Self::ConfigLineSettings,
+    ];
+
+    /// Position of the flag in the uuid of version 0 candy machines.
+    pub fn uuid_index(&self) -> usize {
+        match self {
+            Self::Collections => COLLECTIONS_FEATURE_INDEX,
+            Self::Freeze => FREEZE_FEATURE_INDEX,
+            Self::FreezeLock => FREEZE_LOCK_FEATURE_INDEX,
+            Self::ConfigLineSettings => CONFIG_LINE_SETTINGS_FEATURE_INDEX,
+        }
+    }
+}
+
+/// A mint phase (e.g. OG, allowlist or public) with its own schedule, price and gate.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct MintPhase {
+    /// Label of the phase, at most `MAX_PHASE_LABEL_LENGTH` bytes
+    pub label: String,
+    pub start_date: i64,
+    /// If [`None`] the phase lasts until the next one starts
+    pub end_date: Option<i64>,
+    pub price: u64,
+    /// Maximum number of items a wallet can mint during the phase
+    pub mint_limit: Option<u16>,
+    /// If [`Some`] minters must hold a token of this mint
+    pub gate_mint: Option<Pubkey>,
+    /// If [`Some`] minters must have submitted an [`AllowlistProof`] for this merkle root
+    pub allowlist_root: Option<[u8; 32]>,
+}
+
+/// Allowlist leaf data of a wallet, overriding the phase settings when [`Some`].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct AllowlistEntry {
+    /// Number of items the wallet can mint during the phase
+    pub quantity: Option<u16>,
+    /// Price the wallet pays during the phase
+    pub price: Option<u64>,
+}
+
+/// Merkle proof of a wallet for a phase allowlist, submitted ahead of minting
+/// so long proofs don't need to fit in the mint transaction.
+#[account]
+#[derive(Default, Debug)]
+pub struct AllowlistProof {
+    pub entry: AllowlistEntry,
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl AllowlistProof {
+    pub const SIZE: usize = 8 + 3 + 9 + 4 + 32 * MAX_ALLOWLIST_PROOF_LENGTH;
+
+    pub const PREFIX: &'static str = "allowlist_proof";
+}
+
+/// A way to pay for a mint, made of one or more payments (e.g. 1 SOL + 100 DUST).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct PaymentOption {
+    pub payments: Vec<Payment>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Payment {
+    pub kind: PaymentKind,
+    /// Lamports or token amount, ignored for [`PaymentKind::CollectionNft`]
+    pub amount: u64,
+    /// If [`Some`] funds go to this wallet (or its ATA) instead of the candy machine wallet
+    pub destination: Option<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum PaymentKind {
+    Sol,
+    /// Transfer tokens of this mint
+    Token(Pubkey),
+    /// Burn tokens of this mint
+    BurnToken(Pubkey),
+    /// Transfer an NFT verified as part of this collection
+    CollectionNft(Pubkey),
+}
+
+impl Payment {
+    /// Number of remaining accounts the mint instruction expects for the payment.
+    pub fn accounts_len(&self) -> usize {
+        match self.kind {
+            PaymentKind::Sol => usize::from(self.destination.is_some()),
+            PaymentKind::Token(_) | PaymentKind::BurnToken(_) => 2,
+            PaymentKind::CollectionNft(_) => 3,
+        }
+    }
+
+    /// Amount paid for one NFT and its token mint, [`None`] for lamports.
+    pub fn price(&self) -> (u64, Option<Pubkey>) {
+        match self.kind {
+            PaymentKind::Sol => (self.amount, None),
+            PaymentKind::Token(mint) | PaymentKind::BurnToken(mint) => (self.amount, Some(mint)),
+            // a single NFT of the collection
+            PaymentKind::CollectionNft(collection) => (1, Some(collection)),
+        }
+    }
+}
+
+/// Per-wallet mint limit of the candy machine.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct MintLimit {
+    pub limit: u16,
+    /// Bot tax wallets minting over the limit instead of failing the transaction
+    pub bot_tax: bool,
+}
+
+/// Number of items a wallet has minted, seeded by candy machine and wallet
+/// (and the phase index for phase limits).
+#[account]
+#[derive(Default, Debug)]
+pub struct MintCounter {
+    pub count: u16,
+}
+
+impl MintCounter {
+    pub const SIZE: usize = 8 + 2;
+
+    pub const PREFIX: &'static str = "mint_counter";
+}
+
+/// Programmable NFT settings of the candy machine.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct ProgrammableSettings {
+    /// If [`Some`] transfers and delegates of minted NFTs are checked against this
+    /// token auth rule set
+    pub rule_set: Option<Pubkey>,
+}
+
+impl ProgrammableSettings {
+    /// Number of remaining accounts the mint instruction expects for programmable NFTs.
+    pub fn accounts_len(&self) -> usize {
+        if self.rule_set.is_some() {
+            5
+        } else {
+            3
+        }
+    }
+}
+
+/// Bot tax policy of the candy machine, failed mints matching one of its conditions
+/// are charged instead of failing the transaction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BotTax {
+    /// Lamports charged to a bot
+    pub lamports: u64,
+    /// Bitmask of the taxed [`BotTaxCondition`]s
+    pub conditions: u16,
+    /// Send the tax to the candy machine `wallet` instead of the candy machine account,
+    /// only for SOL candy machines
+    pub pay_to_wallet: bool,
+    /// Fail instead of taxing during phases with an allowlist
+    pub skip_allowlist_phases: bool,
+}
+
+impl Default for BotTax {
+    fn default() -> Self {
+        Self {
+            lamports: BOT_FEE,
+            conditions: BotTaxCondition::ALL,
+            pay_to_wallet: false,
+            skip_allowlist_phases: false,
+        }
+    }
+}
+
+impl BotTax {
+    pub fn taxes(&self, condition: BotTaxCondition) -> bool {
+        self.conditions & condition as u16 != 0
+    }
+}
+
+/// Mint failures that can be bot taxed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u16)]
+pub enum BotTaxCondition {
+    /// Missing remaining accounts
+    InvalidAccounts = 1 << 0,
+    /// No items left to mint
+    SoldOut = 1 << 1,
+    /// Unauthorized program or instruction in the transaction
+    SuspiciousTransaction = 1 << 2,
+    /// Missing set collection instruction after the mint
+    MissingCollection = 1 << 3,
+    /// Minting before the go live date or outside of the phases
+    NotLive = 1 << 4,
+    /// Missing whitelist token, gate token or allowlist proof
+    NotWhitelisted = 1 << 5,
+    /// Invalid gateway token
+    Gateway = 1 << 6,
+    /// Minting over the wallet mint limit, also requires [`MintLimit::bot_tax`]
+    MintLimit = 1 << 7,
+}
+
+impl BotTaxCondition {
+    pub const ALL: u16 = (1 << 8) - 1;
+
+    pub fn from_error(error: &CandyError) -> Option<Self> {
+        match error {
+            CandyError::IncorrectRemainingAccountsLen => Some(Self::InvalidAccounts),
+            CandyError::CandyMachineEmpty => Some(Self::SoldOut),
+            CandyError::SuspiciousTransaction => Some(Self::SuspiciousTransaction),
+            CandyError::MissingSetCollectionDuringMint => Some(Self::MissingCollection),
+            CandyError::CandyMachineNotLive => Some(Self::NotLive),
+            CandyError::NoWhitelistToken
+            | CandyError::NotAllowlisted
+            | CandyError::IncorrectOwner => Some(Self::NotWhitelisted),
+            CandyError::GatewayProgramError => Some(Self::Gateway),
+            CandyError::MintLimitReached => Some(Self::MintLimit),
+            _ => None,
+        }
+    }
+}
+
+/// Commitment of a wallet to mint from a commit-reveal candy machine, seeded by
+/// the candy machine and the wallet.
+#[account]
+#[derive(Default, Debug)]
+pub struct MintCommitment {
+    /// Slot of the commitment, the hash of the next slot picks the item
+    pub slot: u64,
+    /// Items redeemed at commit time, the item is picked among the ones left then
+    pub items_redeemed: u64,
+    /// Lamports held until the commitment is minted, forfeited to the candy machine
+    /// if it expires
+    pub deposit: u64,
+}
+
+impl MintCommitment {
+    pub const SIZE: usize = 8 + 8 + 8 + 8;
+
+    pub const PREFIX: &'static str = "mint_commitment";
+}
+
+/// Price paid for a frozen NFT of a freeze refund candy machine, seeded by the
+/// candy machine and the NFT mint.
+#[account]
+#[derive(Default, Debug)]
+pub struct RefundRecord {
+    /// Lamports, or tokens of the candy machine `token_mint`, refunded when the NFT is burned
+    pub amount: u64,
+    /// Config line of the NFT, released for minting again when it is refunded
+    pub line_index: u64,
+}
+
+impl RefundRecord {
+    pub const SIZE: usize = 8 + 8 + 8;
+
+    pub const PREFIX: &'static str = "refund_record";
+}
+
+/// Record of a minted NFT, seeded by the candy machine and the NFT mint, only created
+/// when the candy machine has `mint_receipts` enabled.
+#[account]
+#[derive(Default, Debug)]
+pub struct MintReceipt {
+    pub candy_machine: Pubkey,
+    pub mint: Pubkey,
+    pub payer: Pubkey,
+    /// Config line of the NFT, or its mint number for hidden settings
+    pub index: u64,
+    /// Lamports, or tokens of `payment_mint`, paid for the NFT, from the first payment
+    /// of the option when it was paid with a payment option
+    pub price: u64,
+    /// Token mint of the price, [`None`] for lamports, the collection for NFT payments
+    pub payment_mint: Option<Pubkey>,
+    pub payment_option: Option<u8>,
+    pub phase: Option<u8>,
+    pub minted_at: i64,
+}
+
+impl MintReceipt {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // candy machine
+        32 + // mint
+        32 + // payer
+        8 + // index
+        8 + // price
+        1 + 32 + // payment mint
+        1 + 1 + // payment option
+        1 + 1 + // phase
+        8; // minted at
+
+    pub const PREFIX: &'static str = "mint_receipt";
+}
+
+/// Emitted for each NFT minted by the candy machine.
+#[event]
+pub struct MintEvent {
+    pub candy_machine: Pubkey,
+    pub mint: Pubkey,
+    pub payer: Pubkey,
+    /// Config line of the NFT, or its mint number for hidden settings
+    pub index: u64,
+    /// Lamports, or tokens of `payment_mint`, paid for the NFT, from the first payment
+    /// of the option when it was paid with a payment option
+    pub price: u64,
+    pub payment_mint: Option<Pubkey>,
+    pub payment_option: Option<u8>,
+    pub phase: Option<u8>,
+}
+
+/// Real config lines of a hidden settings candy machine, uploaded by the authority
+/// once minting is complete and verified against the [`HiddenSettings`] hash.
+#[account]
+#[derive(Default, Debug)]
+pub struct RevealLines {
+    pub candy_machine: Pubkey,
+    /// Running hash of the uploaded lines
+    pub hash: [u8; 32],
+    pub lines_uploaded: u64,
+    /// Set once every line is uploaded and the hash matches the hidden settings
+    pub verified: bool,
+    // here there are items_available config lines of CONFIG_LINE_SIZE bytes, in mint
+    // order, that we explicitly never want deserialized.
+}
+
+impl RevealLines {
+    pub const LINES_START: usize = 8 + 32 + 32 + 8 + 1;
+
+    pub fn space(items_available: u64) -> Option<usize> {
+        (items_available as usize)
+            .checked_mul(CONFIG_LINE_SIZE)?
+            .checked_add(Self::LINES_START)
+    }
+
+    pub fn line_position(index: usize) -> usize {
+        Self::LINES_START + index * CONFIG_LINE_SIZE
+    }
 }
 
 /// Individual config line for storing NFT data pre-mint.
@@ -114,6 +639,98 @@ pub struct ConfigLine {
     pub uri: String,
 }
 
+/// Name and URI prefixes shared by the config lines of a compressed candy machine,
+/// each config line then only stores its suffixes (e.g. `#123` and `123.json`).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct ConfigLineSettings {
+    pub prefix_name: String,
+    /// Maximum length of the name suffixes
+    pub name_length: u32,
+    pub prefix_uri: String,
+    /// Maximum length of the URI suffixes
+    pub uri_length: u32,
+}
+
+impl ConfigLineSettings {
+    pub fn is_valid(&self) -> bool {
+        self.prefix_name.len() + self.name_length as usize <= MAX_NAME_LENGTH
+            && self.prefix_uri.len() + self.uri_length as usize <= MAX_URI_LENGTH
+    }
+}
+
+/// Byte layout of the config lines region, compressed when the candy machine has
+/// [`ConfigLineSettings`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigLinesLayout {
+    pub settings: ConfigLineSettings,
+    /// Position of the config lines count, followed by the config lines
+    pub start: usize,
+}
+
+impl ConfigLinesLayout {
+    pub fn new(config_line_settings: Option<ConfigLineSettings>) -> Self {
+        match config_line_settings {
+            Some(settings) => Self {
+                settings,
+                start: CONFIG_ARRAY_START + CONFIG_LINE_SETTINGS_SIZE,
+            },
+            None => Self {
+                settings: ConfigLineSettings {
+                    prefix_name: String::new(),
+                    name_length: MAX_NAME_LENGTH as u32,
+                    prefix_uri: String::new(),
+                    uri_length: MAX_URI_LENGTH as u32,
+                },
+                start: CONFIG_ARRAY_START,
+            },
+        }
+    }
+
+    pub fn load(data: &CandyMachineData, account_data: &[u8]) -> Result<Self> {
+        if !CandyMachineFeatures::from_account_data(data, account_data)?
+            .is_active(Feature::ConfigLineSettings)
+        {
+            return Ok(Self::new(None));
+        }
+        let settings = ConfigLineSettings::deserialize(
+            &mut &account_data[CONFIG_ARRAY_START..CONFIG_ARRAY_START + CONFIG_LINE_SETTINGS_SIZE],
+        )
+        .map_err(|_| error!(CandyError::InvalidConfigLineSettings))?;
+        Ok(Self::new(Some(settings)))
+    }
+
+    pub fn name_length(&self) -> usize {
+        self.settings.name_length as usize
+    }
+
+    pub fn uri_length(&self) -> usize {
+        self.settings.uri_length as usize
+    }
+
+    pub fn line_size(&self) -> usize {
+        4 + self.name_length() + 4 + self.uri_length()
+    }
+
+    pub fn line_position(&self, index: usize) -> usize {
+        self.start + 4 + index * self.line_size()
+    }
+
+    /// Position of the bitmask of added config lines.
+    pub fn bit_mask_start(&self, items_available: usize) -> usize {
+        self.line_position(items_available) + 4
+    }
+
+    /// Position of the bitmask of minted items.
+    pub fn minted_bit_mask_start(&self, items_available: usize) -> usize {
+        self.bit_mask_start(items_available) + items_available / 8 + 4
+    }
+
+    /// End of the config lines region.
+    pub fn end(&self, items_available: usize) -> usize {
+        self.line_position(items_available) + 8 + 2 * (items_available / 8 + 1)
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct EndSettings {
     pub end_setting_type: EndSettingType,
@@ -140,6 +757,8 @@ pub struct Creator {
 pub struct HiddenSettings {
     pub name: String,
     pub uri: String,
+    /// Hash of the real config lines, on-chain reveals expect the running keccak hash
+    /// `hash = keccak(hash || line)` of the borsh serialized lines, starting from zeroes
     pub hash: [u8; 32],
 }
 
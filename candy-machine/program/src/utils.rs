@@ -1,11 +1,13 @@
 use anchor_lang::prelude::*;
 use mpl_token_metadata::{
     error::MetadataError,
+    pda::find_metadata_account,
     state::{MasterEditionV2, Metadata, TokenStandard},
 };
 use solana_program::{
     account_info::AccountInfo,
     clock::Clock,
+    keccak,
     program::{invoke, invoke_signed},
     program_memory::sol_memcmp,
     program_pack::{IsInitialized, Pack},
@@ -14,7 +16,10 @@ use solana_program::{
 };
 use spl_associated_token_account::get_associated_token_address;
 
-use crate::{constants::*, CandyError, CandyMachine};
+use crate::{
+    constants::*, AllowlistEntry, AllowlistProof, BotTax, BotTaxCondition, CandyError,
+    CandyMachine, MintCounter, MintPhase, PaymentKind, PaymentOption,
+};
 
 pub fn assert_initialized<T: Pack + IsInitialized>(account_info: &AccountInfo) -> Result<T> {
     let account: T = T::unpack_unchecked(&account_info.data.borrow())?;
@@ -178,6 +183,8 @@ pub fn spl_token_burn(params: TokenBurnParams<'_, '_>) -> Result<()> {
     result.map_err(|_| CandyError::TokenBurnFailed.into())
 }
 
+/// Reads a feature flag from the uuid of a candy machine without a features header,
+/// see [`CandyMachineFeatures`](crate::CandyMachineFeatures).
 pub fn is_feature_active(uuid: &str, feature_index: usize) -> bool {
     let uuid_bytes = uuid.as_bytes();
     if feature_index == COLLECTIONS_FEATURE_INDEX && uuid_bytes[feature_index] == b'1' {
@@ -191,39 +198,19 @@ fn is_valid_uuid(uuid: &str) -> bool {
     !uuid.bytes().any(|b| b != b'1' && b != b'0' && b != b'#')
 }
 
-// string is 6 bytes long, can be any valid utf8 char coming in.
-// feature_index is between 0 and 5, inclusive.
-pub fn set_feature_flag(uuid: &mut str, feature_index: usize) {
-    if feature_index > 5 {
-        return;
-    }
-
-    // its safe because the char boundaries for the normalized string are all 1 byte. trust me bro
-    unsafe {
-        uuid.as_bytes_mut()[feature_index] = b'#';
-    }
-}
-
-// string is 6 bytes long, can be any valid utf8 char coming in.
-// feature_index is between 0 and 5, inclusive.
-pub fn remove_feature_flag(uuid: &mut str, feature_index: usize) {
-    if feature_index > 5 {
-        return;
-    }
-
-    // its safe because the char boundaries for the normalized string are all 1 byte. trust me bro
-    unsafe {
-        uuid.as_bytes_mut()[feature_index] = b'0';
-    }
-}
-
+/// Charges the bot tax for `error`, or returns `error` when the bot tax policy
+/// doesn't tax it.
 pub fn punish_bots<'a>(
     error: CandyError,
     bot_account: AccountInfo<'a>,
     payment_account: AccountInfo<'a>,
     system_program: AccountInfo<'a>,
-    fee: u64,
+    bot_tax: &BotTax,
 ) -> Result<()> {
+    if !BotTaxCondition::from_error(&error).map_or(false, |condition| bot_tax.taxes(condition)) {
+        return Err(error.into());
+    }
+    let fee = bot_tax.lamports;
     msg!(
         "{}, Candy Machine Botting is taxed at {:?} lamports",
         error.to_string(),
@@ -238,6 +225,226 @@ pub fn punish_bots<'a>(
     Ok(())
 }
 
+pub fn assert_valid_mint_phases(phases: &[MintPhase]) -> Result<()> {
+    if phases.len() > MAX_MINT_PHASES {
+        return err!(CandyError::InvalidMintPhases);
+    }
+    let mut previous: Option<&MintPhase> = None;
+    for phase in phases {
+        if phase.label.len() > MAX_PHASE_LABEL_LENGTH || phase.mint_limit == Some(0) {
+            return err!(CandyError::InvalidMintPhases);
+        }
+        if let Some(end_date) = phase.end_date {
+            if end_date <= phase.start_date {
+                return err!(CandyError::InvalidMintPhases);
+            }
+        }
+        if let Some(previous) = previous {
+            let previous_end = previous.end_date.unwrap_or(previous.start_date);
+            if phase.start_date <= previous.start_date || phase.start_date < previous_end {
+                return err!(CandyError::InvalidMintPhases);
+            }
+        }
+        previous = Some(phase);
+    }
+    Ok(())
+}
+
+pub fn allowlist_leaf(wallet: &Pubkey, entry: &AllowlistEntry) -> Result<[u8; 32]> {
+    let entry_bytes = entry
+        .try_to_vec()
+        .map_err(|_| CandyError::InvalidCandyMachineSettings)?;
+    Ok(keccak::hashv(&[&[0x00], wallet.as_ref(), &entry_bytes]).0)
+}
+
+/// Returns true if `leaf` can be proved to be part of the merkle tree defined by `root`,
+/// using the same sorted pair keccak proofs as gumdrop.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut computed_hash = leaf;
+    for proof_element in proof {
+        computed_hash = if computed_hash <= *proof_element {
+            keccak::hashv(&[&[0x01], &computed_hash, proof_element]).0
+        } else {
+            keccak::hashv(&[&[0x01], proof_element, &computed_hash]).0
+        };
+    }
+    computed_hash == root
+}
+
+/// Checks the allowlist proof PDA of `wallet` for the phase proves it against `root`.
+pub fn assert_allowlisted(
+    allowlist_proof_info: &AccountInfo,
+    candy_machine: &Pubkey,
+    wallet: &Pubkey,
+    phase_index: u8,
+    root: [u8; 32],
+) -> Result<AllowlistEntry> {
+    let (expected_key, _) = Pubkey::find_program_address(
+        &[
+            AllowlistProof::PREFIX.as_bytes(),
+            candy_machine.as_ref(),
+            wallet.as_ref(),
+            &[phase_index],
+        ],
+        &crate::id(),
+    );
+    assert_keys_equal(&expected_key, allowlist_proof_info.key)?;
+    let allowlist_proof: Account<AllowlistProof> = Account::try_from(allowlist_proof_info)?;
+    let leaf = allowlist_leaf(wallet, &allowlist_proof.entry)?;
+    if !verify_merkle_proof(&allowlist_proof.proof, root, leaf) {
+        return err!(CandyError::NotAllowlisted);
+    }
+    Ok(allowlist_proof.entry.clone())
+}
+
+pub fn assert_valid_payment_options(payment_options: &[PaymentOption]) -> Result<()> {
+    if payment_options.len() > MAX_PAYMENT_OPTIONS {
+        return err!(CandyError::InvalidPaymentOptions);
+    }
+    for payment_option in payment_options {
+        if payment_option.payments.is_empty()
+            || payment_option.payments.len() > MAX_PAYMENTS_PER_OPTION
+        {
+            return err!(CandyError::InvalidPaymentOptions);
+        }
+        for payment in &payment_option.payments {
+            let needs_amount = !matches!(payment.kind, PaymentKind::CollectionNft(_));
+            if needs_amount && payment.amount == 0 {
+                return err!(CandyError::InvalidPaymentOptions);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks `metadata_info` is the metadata of `mint` and is verified as part of `collection`.
+pub fn assert_collection_nft(
+    metadata_info: &AccountInfo,
+    mint: &Pubkey,
+    collection: &Pubkey,
+) -> Result<()> {
+    assert_owned_by(metadata_info, &mpl_token_metadata::id())?;
+    let (expected_key, _) = find_metadata_account(mint);
+    assert_keys_equal(&expected_key, metadata_info.key)?;
+    let metadata = {
+        let data = metadata_info.data.borrow();
+        if data.is_empty() || data[0] != mpl_token_metadata::state::Key::MetadataV1 as u8 {
+            return err!(CandyError::InvalidMetadataAccount);
+        }
+        Metadata::deserialize(&mut data.as_ref())?
+    };
+    match metadata.collection {
+        Some(c) if c.verified && cmp_pubkeys(&c.key, collection) => Ok(()),
+        _ => err!(CandyError::InvalidCollectionPayment),
+    }
+}
+
+/// Returns the hash of the first slot after `commit_slot` from the slot hashes sysvar data.
+/// That hash didn't exist when the commitment was made, so it can't have been known by then.
+pub fn get_revealed_slot_hash(slot_hashes: &[u8], commit_slot: u64) -> Result<[u8; 32]> {
+    let read_u64 = |offset: usize| -> Result<u64> {
+        let bytes = slot_hashes
+            .get(offset..offset + 8)
+            .ok_or(CandyError::IncorrectSlotHashesPubkey)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    };
+    // (slot, hash) entries, most recent first
+    let entries = read_u64(0)? as usize;
+    let mut revealed = None;
+    for offset in (0..entries).map(|i| 8 + i * (8 + 32)) {
+        if read_u64(offset)? <= commit_slot {
+            return revealed.ok_or_else(|| error!(CandyError::MintCommitmentNotRevealable));
+        }
+        revealed = Some(
+            slot_hashes[offset + 8..offset + 8 + 32]
+                .try_into()
+                .map_err(|_| CandyError::IncorrectSlotHashesPubkey)?,
+        );
+    }
+    // every slot hash left is after the one following the commitment
+    err!(CandyError::MintCommitmentExpired)
+}
+
+/// Creates a PDA owned by the candy machine program, topping up any lamports already in it.
+pub fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    new_account: &AccountInfo<'a>,
+    size: usize,
+    system_program: &AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let required_lamports = Rent::get()?
+        .minimum_balance(size)
+        .saturating_sub(new_account.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, new_account.key, required_lamports),
+            &[payer.clone(), new_account.clone(), system_program.clone()],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(new_account.key, size as u64),
+        &[new_account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(new_account.key, &crate::id()),
+        &[new_account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )?;
+    Ok(())
+}
+
+/// Returns the count of the mint counter PDA derived from `seeds`, zero before the wallet's first mint.
+pub fn get_mint_count(mint_counter_info: &AccountInfo, seeds: &[&[u8]]) -> Result<u16> {
+    let (expected_key, _) = Pubkey::find_program_address(seeds, &crate::id());
+    assert_keys_equal(&expected_key, mint_counter_info.key)?;
+    if mint_counter_info.data_is_empty() {
+        return Ok(0);
+    }
+    let mint_counter: Account<MintCounter> = Account::try_from(mint_counter_info)?;
+    Ok(mint_counter.count)
+}
+
+/// Increments the mint counter PDA derived from `seeds`, creating it on the wallet's first mint.
+pub fn increment_mint_counter<'a>(
+    mint_counter_info: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    mint_limit: u16,
+    amount: u16,
+) -> Result<()> {
+    let (expected_key, bump) = Pubkey::find_program_address(seeds, &crate::id());
+    assert_keys_equal(&expected_key, mint_counter_info.key)?;
+
+    let mut mint_counter: Account<MintCounter> = if mint_counter_info.data_is_empty() {
+        let bump_seed = [bump];
+        let mut signer_seeds = seeds.to_vec();
+        signer_seeds.push(&bump_seed);
+        create_pda_account(
+            payer,
+            mint_counter_info,
+            MintCounter::SIZE,
+            system_program,
+            &signer_seeds,
+        )?;
+        Account::try_from_unchecked(mint_counter_info)?
+    } else {
+        Account::try_from(mint_counter_info)?
+    };
+
+    let count = mint_counter
+        .count
+        .checked_add(amount)
+        .ok_or(CandyError::NumericalOverflowError)?;
+    if count > mint_limit {
+        return err!(CandyError::MintLimitReached);
+    }
+    mint_counter.count = count;
+    mint_counter.exit(&crate::id())
+}
+
 pub fn assert_master_edition(
     collection_data: &Metadata,
     edition_account_info: &AccountInfo,
@@ -262,53 +469,122 @@ pub fn assert_master_edition(
 
 #[cfg(test)]
 pub mod tests {
-    use std::{assert_eq, println};
+    use std::assert_eq;
 
-    use crate::constants::COLLECTIONS_FEATURE_INDEX;
+    use mpl_token_metadata::state::{
+        MAX_CREATOR_LIMIT, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH,
+    };
+
+    use crate::{
+        CandyMachineData, CandyMachineFeatures, CandyMachineSettings, Creator, EndSettingType,
+        EndSettings, Feature, GatekeeperConfig, HiddenSettings, WhitelistMintMode,
+        WhitelistMintSettings,
+    };
 
     use super::*;
 
+    fn phase(start_date: i64, end_date: Option<i64>) -> MintPhase {
+        MintPhase {
+            label: String::from("public"),
+            start_date,
+            end_date,
+            price: 1,
+            mint_limit: None,
+            gate_mint: None,
+            allowlist_root: None,
+        }
+    }
+
     #[test]
     fn feature_flag_working() {
-        let mut uuid = String::from("ABCDEF");
-        println!(
-            "Should be 65: {}",
-            uuid.as_bytes()[COLLECTIONS_FEATURE_INDEX]
-        );
-        remove_feature_flag(&mut uuid, COLLECTIONS_FEATURE_INDEX);
-        assert_eq!(uuid, "0BCDEF");
-
-        uuid = String::from("01H333");
-        assert!(!is_feature_active(&uuid, FREEZE_FEATURE_INDEX));
-        assert_eq!(uuid, "01H333");
-        set_feature_flag(&mut uuid, FREEZE_FEATURE_INDEX);
-        assert_eq!(uuid, "0#H333");
-        assert!(is_feature_active(&uuid, FREEZE_FEATURE_INDEX));
-
-        remove_feature_flag(&mut uuid, FREEZE_FEATURE_INDEX);
+        let uuid = String::from("ABCDEF");
+        assert!(!is_feature_active(&uuid, COLLECTIONS_FEATURE_INDEX));
         assert!(!is_feature_active(&uuid, FREEZE_FEATURE_INDEX));
-        println!("Should be 00H333: {}", uuid);
 
-        set_feature_flag(&mut uuid, FREEZE_LOCK_FEATURE_INDEX);
-        assert!(is_feature_active(&uuid, FREEZE_LOCK_FEATURE_INDEX));
-        assert_eq!(uuid, "00#333");
-
-        remove_feature_flag(&mut uuid, COLLECTIONS_FEATURE_INDEX);
-        assert_eq!(uuid, "00#333");
-        set_feature_flag(&mut uuid, FREEZE_FEATURE_INDEX);
+        let uuid = String::from("0#H333");
         assert!(is_feature_active(&uuid, FREEZE_FEATURE_INDEX));
-        assert_eq!(uuid, "0##333");
-        set_feature_flag(&mut uuid, COLLECTIONS_FEATURE_INDEX);
-        assert!(is_feature_active(&uuid, COLLECTIONS_FEATURE_INDEX));
-        assert_eq!(uuid, "###333");
+        assert!(!is_feature_active(&uuid, FREEZE_LOCK_FEATURE_INDEX));
 
-        uuid = String::from("1ABCDE");
+        let uuid = String::from("1ABCDE");
         assert!(!is_feature_active(&uuid, COLLECTIONS_FEATURE_INDEX));
-        uuid = String::from("100000");
+        let uuid = String::from("100000");
         assert!(is_feature_active(&uuid, COLLECTIONS_FEATURE_INDEX));
-
-        uuid = String::from("1##000");
+        let uuid = String::from("1##000");
         assert!(is_feature_active(&uuid, COLLECTIONS_FEATURE_INDEX));
+        assert!(is_feature_active(&uuid, FREEZE_LOCK_FEATURE_INDEX));
+    }
+
+    #[test]
+    fn features_header() {
+        let data = CandyMachineData {
+            uuid: String::from("1##000"),
+            ..CandyMachineData::default()
+        };
+        let mut account_data = vec![0; CONFIG_ARRAY_START];
+        let features = CandyMachineFeatures::from_account_data(&data, &account_data).unwrap();
+        assert_eq!(features.version, 0);
+        assert!(features.is_active(Feature::Collections));
+        assert!(features.is_active(Feature::Freeze));
+        assert!(features.is_active(Feature::FreezeLock));
+        assert!(!features.is_active(Feature::ConfigLineSettings));
+
+        let mut features = CandyMachineFeatures {
+            version: CandyMachineFeatures::VERSION,
+            flags: 0,
+        };
+        features.set(Feature::ConfigLineSettings);
+        features.set(Feature::Freeze);
+        features.remove(Feature::Freeze);
+        let bytes = features.try_to_vec().unwrap();
+        account_data[FEATURES_START..FEATURES_START + bytes.len()].copy_from_slice(&bytes);
+        // the uuid is ignored once the header is written
+        let features = CandyMachineFeatures::from_account_data(&data, &account_data).unwrap();
+        assert_eq!(features.flags, Feature::ConfigLineSettings as u16);
+
+        account_data[FEATURES_START] = CandyMachineFeatures::VERSION + 1;
+        assert!(CandyMachineFeatures::from_account_data(&data, &account_data).is_err());
+    }
+
+    #[test]
+    fn features_fit_after_candy_machine() {
+        let candy_machine = CandyMachine {
+            token_mint: Some(Pubkey::new_unique()),
+            data: CandyMachineData {
+                uuid: String::from("000000"),
+                symbol: String::from_utf8(vec![b'a'; MAX_SYMBOL_LENGTH]).unwrap(),
+                go_live_date: Some(0),
+                end_settings: Some(EndSettings {
+                    end_setting_type: EndSettingType::Date,
+                    number: 0,
+                }),
+                creators: vec![
+                    Creator {
+                        address: Pubkey::new_unique(),
+                        verified: false,
+                        share: 0,
+                    };
+                    MAX_CREATOR_LIMIT
+                ],
+                hidden_settings: Some(HiddenSettings {
+                    name: String::from_utf8(vec![b'a'; MAX_NAME_LENGTH]).unwrap(),
+                    uri: String::from_utf8(vec![b'a'; MAX_URI_LENGTH]).unwrap(),
+                    hash: [0; 32],
+                }),
+                whitelist_mint_settings: Some(WhitelistMintSettings {
+                    mode: WhitelistMintMode::BurnEveryTime,
+                    mint: Pubkey::new_unique(),
+                    presale: false,
+                    discount_price: Some(0),
+                }),
+                gatekeeper: Some(GatekeeperConfig {
+                    gatekeeper_network: Pubkey::new_unique(),
+                    expire_on_use: false,
+                }),
+                ..CandyMachineData::default()
+            },
+            ..CandyMachine::default()
+        };
+        assert!(8 + candy_machine.try_to_vec().unwrap().len() <= FEATURES_START);
     }
 
     #[test]
@@ -323,4 +599,145 @@ pub mod tests {
         let key2 = Pubkey::new_unique();
         assert!(!cmp_pubkeys(&key1, &key2));
     }
+
+    #[test]
+    fn mint_phases_validation() {
+        assert!(assert_valid_mint_phases(&[]).is_ok());
+        assert!(assert_valid_mint_phases(&[phase(10, Some(20)), phase(20, None)]).is_ok());
+        assert!(assert_valid_mint_phases(&[phase(10, None), phase(15, Some(30))]).is_ok());
+
+        // ends before it starts
+        assert!(assert_valid_mint_phases(&[phase(10, Some(10))]).is_err());
+        // out of order
+        assert!(assert_valid_mint_phases(&[phase(20, None), phase(10, None)]).is_err());
+        // overlapping
+        assert!(assert_valid_mint_phases(&[phase(10, Some(30)), phase(20, None)]).is_err());
+
+        let mut zero_limit = phase(10, None);
+        zero_limit.mint_limit = Some(0);
+        assert!(assert_valid_mint_phases(&[zero_limit]).is_err());
+
+        let mut long_label = phase(10, None);
+        long_label.label = "a".repeat(MAX_PHASE_LABEL_LENGTH + 1);
+        assert!(assert_valid_mint_phases(&[long_label]).is_err());
+
+        let too_many: Vec<MintPhase> = (0..=MAX_MINT_PHASES as i64)
+            .map(|i| phase(i * 10, None))
+            .collect();
+        assert!(assert_valid_mint_phases(&too_many).is_err());
+    }
+
+    #[test]
+    fn active_mint_phase() {
+        let settings = CandyMachineSettings {
+            phases: vec![phase(10, Some(20)), phase(30, Some(40)), phase(40, None)],
+        };
+        assert!(settings.active_phase(5).is_none());
+        assert_eq!(settings.active_phase(10).unwrap().0, 0);
+        assert_eq!(settings.active_phase(19).unwrap().0, 0);
+        // between phases
+        assert!(settings.active_phase(25).is_none());
+        assert_eq!(settings.active_phase(30).unwrap().0, 1);
+        assert_eq!(settings.active_phase(40).unwrap().0, 2);
+        assert_eq!(settings.active_phase(i64::MAX).unwrap().0, 2);
+
+        assert!(CandyMachineSettings::default().active_phase(0).is_none());
+    }
+
+    #[test]
+    fn allowlist_merkle_proof() {
+        let wallets: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let entry = AllowlistEntry {
+            quantity: Some(2),
+            price: None,
+        };
+        let leaves: Vec<[u8; 32]> = wallets
+            .iter()
+            .map(|wallet| allowlist_leaf(wallet, &entry).unwrap())
+            .collect();
+        let hash_pair = |a: [u8; 32], b: [u8; 32]| {
+            if a <= b {
+                keccak::hashv(&[&[0x01], &a, &b]).0
+            } else {
+                keccak::hashv(&[&[0x01], &b, &a]).0
+            }
+        };
+        let node = hash_pair(leaves[0], leaves[1]);
+        let root = hash_pair(node, leaves[2]);
+
+        assert!(verify_merkle_proof(
+            &[leaves[1], leaves[2]],
+            root,
+            leaves[0]
+        ));
+        assert!(verify_merkle_proof(
+            &[leaves[0], leaves[2]],
+            root,
+            leaves[1]
+        ));
+        assert!(verify_merkle_proof(&[node], root, leaves[2]));
+
+        // the entry is part of the leaf
+        let other_entry = AllowlistEntry {
+            quantity: Some(5),
+            price: None,
+        };
+        let forged_leaf = allowlist_leaf(&wallets[0], &other_entry).unwrap();
+        assert!(!verify_merkle_proof(
+            &[leaves[1], leaves[2]],
+            root,
+            forged_leaf
+        ));
+        assert!(!verify_merkle_proof(&[leaves[2]], root, leaves[0]));
+    }
+
+    #[test]
+    fn revealed_slot_hash() {
+        let slot_hashes = |slots: &[u64]| {
+            let mut data = (slots.len() as u64).to_le_bytes().to_vec();
+            for slot in slots {
+                data.extend_from_slice(&slot.to_le_bytes());
+                data.extend_from_slice(&[*slot as u8; 32]);
+            }
+            data
+        };
+
+        // slot 11 was skipped, the first hash after the commitment is the one of slot 12
+        let data = slot_hashes(&[14, 13, 12, 10, 9]);
+        assert_eq!(get_revealed_slot_hash(&data, 10).unwrap(), [12; 32]);
+        assert_eq!(get_revealed_slot_hash(&data, 12).unwrap(), [13; 32]);
+        assert!(get_revealed_slot_hash(&data, 14).is_err());
+        // the slot hash following the commitment fell out of the sysvar
+        assert!(get_revealed_slot_hash(&data, 5).is_err());
+    }
+
+    #[test]
+    fn payment_options_validation() {
+        let payment = |kind: PaymentKind, amount: u64| crate::Payment {
+            kind,
+            amount,
+            destination: None,
+        };
+        let sol_and_dust = PaymentOption {
+            payments: vec![
+                payment(PaymentKind::Sol, 1),
+                payment(PaymentKind::Token(Pubkey::new_unique()), 100),
+            ],
+        };
+        let collection_nft = PaymentOption {
+            payments: vec![payment(PaymentKind::CollectionNft(Pubkey::new_unique()), 0)],
+        };
+        assert!(assert_valid_payment_options(&[sol_and_dust.clone(), collection_nft]).is_ok());
+
+        let empty = PaymentOption { payments: vec![] };
+        assert!(assert_valid_payment_options(&[empty]).is_err());
+
+        let free_burn = PaymentOption {
+            payments: vec![payment(PaymentKind::BurnToken(Pubkey::new_unique()), 0)],
+        };
+        assert!(assert_valid_payment_options(&[free_burn]).is_err());
+
+        let too_many = vec![sol_and_dust; MAX_PAYMENT_OPTIONS + 1];
+        assert!(assert_valid_payment_options(&too_many).is_err());
+    }
 }
@@ -0,0 +1,140 @@
+#![cfg(feature = "test-bpf")]
+#![allow(dead_code)]
+
+use mpl_candy_machine::{allowlist_leaf, AllowlistEntry, CandyError, MintPhase};
+use solana_program::{instruction::InstructionError, keccak, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+
+use crate::{
+    core::helpers::{airdrop, get_balance},
+    utils::{
+        auto_config, candy_machine_program_test,
+        helpers::{sol, test_start},
+        CandyManager, PhaseInfo,
+    },
+};
+
+mod core;
+mod utils;
+
+fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    if a <= b {
+        keccak::hashv(&[&[0x01], &a, &b]).0
+    } else {
+        keccak::hashv(&[&[0x01], &b, &a]).0
+    }
+}
+
+fn allowlist_phase(allowlist_root: [u8; 32]) -> MintPhase {
+    MintPhase {
+        label: "allowlist".to_string(),
+        start_date: 0,
+        end_date: None,
+        price: sol(1.0),
+        mint_limit: None,
+        gate_mint: None,
+        allowlist_root: Some(allowlist_root),
+    }
+}
+
+async fn setup_allowlist_phase(
+    context: &mut ProgramTestContext,
+    allowlist_root: [u8; 32],
+) -> CandyManager {
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(3.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, None, true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager
+        .update_mint_phases(context, vec![allowlist_phase(allowlist_root)])
+        .await
+        .unwrap();
+
+    let candy_machine = candy_manager.candy_machine.pubkey();
+    let minter = candy_manager.minter.pubkey();
+    candy_manager.phase_info = PhaseInfo {
+        gate_token_account: None,
+        allowlist_proof: Some(PhaseInfo::find_allowlist_proof(&candy_machine, &minter, 0)),
+        mint_counter: Some(PhaseInfo::find_mint_counter(&candy_machine, &minter, 0)),
+    };
+    candy_manager
+}
+
+#[tokio::test]
+async fn mint_allowlisted_wallet() {
+    test_start("Mint Allowlisted Wallet");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+
+    let entry = AllowlistEntry {
+        quantity: Some(1),
+        price: Some(sol(0.1)),
+    };
+    let other_leaves = [
+        allowlist_leaf(&Pubkey::new_unique(), &entry).unwrap(),
+        allowlist_leaf(&Pubkey::new_unique(), &entry).unwrap(),
+    ];
+    // the minter keypair is only known after init, so build the tree from a placeholder first
+    let mut candy_manager = setup_allowlist_phase(context, [0; 32]).await;
+    let minter_leaf = allowlist_leaf(&candy_manager.minter.pubkey(), &entry).unwrap();
+    let root = hash_pair(hash_pair(minter_leaf, other_leaves[0]), other_leaves[1]);
+    candy_manager
+        .update_mint_phases(context, vec![allowlist_phase(root)])
+        .await
+        .unwrap();
+
+    // submit the proof in two parts
+    candy_manager
+        .submit_allowlist_proof(context, 0, entry.clone(), vec![other_leaves[0]])
+        .await
+        .unwrap();
+    candy_manager
+        .submit_allowlist_proof(context, 0, entry, vec![other_leaves[1]])
+        .await
+        .unwrap();
+
+    let start_wallet_balance = get_balance(context, &candy_manager.wallet).await;
+    candy_manager
+        .mint_and_assert_successful(context, None, true)
+        .await
+        .unwrap();
+    let end_wallet_balance = get_balance(context, &candy_manager.wallet).await;
+    assert_eq!(
+        end_wallet_balance - start_wallet_balance,
+        sol(0.1),
+        "Allowlist price wasn't used!"
+    );
+
+    let err = match candy_manager.mint_nft(context).await.unwrap_err().unwrap() {
+        TransactionError::InstructionError(0, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    };
+    let candy_error_num: u32 = CandyError::MintLimitReached.into();
+    assert_eq!(err, candy_error_num);
+}
+
+#[tokio::test]
+async fn mint_without_allowlist_proof_bot_tax() {
+    test_start("Mint Without Allowlist Proof Bot Tax");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+
+    let root = hash_pair(
+        allowlist_leaf(&Pubkey::new_unique(), &AllowlistEntry::default()).unwrap(),
+        allowlist_leaf(&Pubkey::new_unique(), &AllowlistEntry::default()).unwrap(),
+    );
+    let mut candy_manager = setup_allowlist_phase(context, root).await;
+
+    candy_manager
+        .mint_and_assert_bot_tax(context)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "test-bpf")]
+#![allow(dead_code)]
+
+use mpl_candy_machine::{BotTax, BotTaxCondition, CandyError};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+
+use crate::{
+    core::helpers::{airdrop, get_balance},
+    utils::{
+        auto_config, candy_machine_program_test,
+        helpers::{sol, test_start},
+        CandyManager,
+    },
+};
+
+mod core;
+mod utils;
+
+// without a go live date only the authority can mint
+async fn setup_not_live_candy_machine(context: &mut ProgramTestContext) -> CandyManager {
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(3.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, None, true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager
+}
+
+#[tokio::test]
+async fn bot_tax_paid_to_wallet() {
+    test_start("Bot Tax Paid To Wallet");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = setup_not_live_candy_machine(context).await;
+    candy_manager
+        .update_bot_tax(
+            context,
+            Some(BotTax {
+                lamports: sol(0.5),
+                conditions: BotTaxCondition::ALL,
+                pay_to_wallet: true,
+                skip_allowlist_phases: false,
+            }),
+        )
+        .await
+        .unwrap();
+
+    let start_balance = get_balance(context, &candy_manager.minter.pubkey()).await;
+    let start_wallet_balance = get_balance(context, &candy_manager.wallet).await;
+    candy_manager.mint_nft(context).await.unwrap();
+    let end_balance = get_balance(context, &candy_manager.minter.pubkey()).await;
+    let end_wallet_balance = get_balance(context, &candy_manager.wallet).await;
+
+    assert_eq!(start_balance - end_balance, sol(0.5) + 5000);
+    assert_eq!(end_wallet_balance - start_wallet_balance, sol(0.5));
+    assert_eq!(candy_manager.get_candy(context).await.items_redeemed, 0);
+}
+
+#[tokio::test]
+async fn untaxed_condition_fails() {
+    test_start("Untaxed Condition Fails");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = setup_not_live_candy_machine(context).await;
+    candy_manager
+        .update_bot_tax(
+            context,
+            Some(BotTax {
+                conditions: BotTaxCondition::ALL & !(BotTaxCondition::NotLive as u16),
+                ..BotTax::default()
+            }),
+        )
+        .await
+        .unwrap();
+
+    let err = match candy_manager.mint_nft(context).await.unwrap_err().unwrap() {
+        TransactionError::InstructionError(0, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    };
+    let candy_error_num: u32 = CandyError::CandyMachineNotLive.into();
+    assert_eq!(err, candy_error_num);
+}
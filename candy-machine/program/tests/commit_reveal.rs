@@ -0,0 +1,127 @@
+#![cfg(feature = "test-bpf")]
+#![allow(dead_code)]
+
+use mpl_candy_machine::{constants::BOT_FEE, CandyError};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+
+use crate::{
+    core::helpers::{airdrop, assert_account_empty, get_balance},
+    utils::{
+        auto_config, candy_machine_program_test, close_mint_commitment,
+        helpers::{sol, test_start},
+        CandyManager,
+    },
+};
+
+mod core;
+mod utils;
+
+fn candy_error(err: TransactionError) -> u32 {
+    match err {
+        TransactionError::InstructionError(0, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    }
+}
+
+async fn setup_commit_reveal(context: &mut ProgramTestContext) -> CandyManager {
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(3.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager
+        .update_commit_reveal(context, true)
+        .await
+        .unwrap();
+    candy_manager
+}
+
+#[tokio::test]
+async fn commit_then_reveal_mint() {
+    test_start("Commit Then Reveal Mint");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = setup_commit_reveal(context).await;
+
+    candy_manager.commit_mint(context).await.unwrap();
+    let mint_commitment = candy_manager.mint_commitment.unwrap();
+
+    // the slot hash following the commitment doesn't exist yet
+    let err = candy_manager.mint_nft(context).await.unwrap_err().unwrap();
+    let candy_error_num: u32 = CandyError::MintCommitmentNotRevealable.into();
+    assert_eq!(candy_error(err), candy_error_num);
+
+    // a commitment that can still be revealed can't be closed to pick another item
+    let err = close_mint_commitment(
+        context,
+        &candy_manager.candy_machine.pubkey(),
+        &candy_manager.minter,
+    )
+    .await
+    .unwrap_err()
+    .unwrap();
+    let candy_error_num: u32 = CandyError::MintCommitmentNotExpired.into();
+    assert_eq!(candy_error(err), candy_error_num);
+
+    candy_manager.mint_nft(context).await.unwrap();
+    assert_account_empty(context, &mint_commitment).await;
+    let candy_machine = candy_manager.get_candy(context).await;
+    assert_eq!(candy_machine.items_redeemed, 1);
+}
+
+#[tokio::test]
+async fn commit_without_commit_reveal_fails() {
+    test_start("Commit Without Commit Reveal Fails");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = setup_commit_reveal(context).await;
+    candy_manager
+        .update_commit_reveal(context, false)
+        .await
+        .unwrap();
+
+    let err = candy_manager
+        .commit_mint(context)
+        .await
+        .unwrap_err()
+        .unwrap();
+    let candy_error_num: u32 = CandyError::CommitRevealNotEnabled.into();
+    assert_eq!(candy_error(err), candy_error_num);
+}
+
+#[tokio::test]
+async fn close_expired_commitment_forfeits_deposit() {
+    test_start("Close Expired Commitment Forfeits Deposit");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = setup_commit_reveal(context).await;
+    let candy_machine = candy_manager.candy_machine.pubkey();
+
+    candy_manager.commit_mint(context).await.unwrap();
+    let mint_commitment = candy_manager.mint_commitment.unwrap();
+    let commitment = candy_manager.get_mint_commitment(context).await;
+    assert_eq!(commitment.items_redeemed, 0);
+    assert_eq!(commitment.deposit, BOT_FEE);
+
+    // the slot hash following the commitment falls out of the slot hashes sysvar
+    let slot = context.banks_client.get_root_slot().await.unwrap();
+    context.warp_to_slot(slot + 1000).unwrap();
+
+    let candy_balance = get_balance(context, &candy_machine).await;
+    close_mint_commitment(context, &candy_machine, &candy_manager.minter)
+        .await
+        .unwrap();
+    assert_account_empty(context, &mint_commitment).await;
+    assert_eq!(
+        get_balance(context, &candy_machine).await,
+        candy_balance + BOT_FEE
+    );
+}
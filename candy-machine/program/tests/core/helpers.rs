@@ -327,3 +327,22 @@ pub async fn prepare_nft(
     .unwrap();
     MasterEditionManager::new(&nft_info)
 }
+
+/// Creates the mint of a programmable NFT, its token is minted by token metadata.
+pub async fn prepare_programmable_nft(
+    context: &mut ProgramTestContext,
+    minter: &Keypair,
+) -> MasterEditionManager {
+    update_blockhash(context).await.expect("warp slot failed!");
+    let nft_info = metadata_manager::MetadataManager::new(minter);
+    create_mint(
+        context,
+        &minter.pubkey(),
+        Some(&minter.pubkey()),
+        0,
+        Some(clone_keypair(&nft_info.mint)),
+    )
+    .await
+    .unwrap();
+    MasterEditionManager::new(&nft_info)
+}
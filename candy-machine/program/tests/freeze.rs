@@ -1,24 +1,27 @@
 #![cfg(feature = "test-bpf")]
 #![allow(dead_code)]
 
-use solana_program::clock::Clock;
+use anchor_lang::{AccountDeserialize, AccountSerialize};
+use solana_program::{clock::Clock, instruction::InstructionError};
 use solana_program_test::*;
-use solana_sdk::{signature::Keypair, signer::Signer};
+use solana_sdk::{
+    account::AccountSharedData, signature::Keypair, signer::Signer, transaction::TransactionError,
+};
 
 use mpl_candy_machine::{
-    constants::{FREEZE_FEATURE_INDEX, FREEZE_FEE, FREEZE_LOCK_FEATURE_INDEX, MAX_FREEZE_TIME},
-    is_feature_active, CandyMachineData, FreezePDA,
+    constants::{FEATURES_START, FREEZE_FEE, MAX_FREEZE_TIME},
+    CandyError, CandyMachine, CandyMachineData, CandyMachineFeatures, Feature, FreezePDA,
     WhitelistMintMode::BurnEveryTime,
 };
 
 use crate::{
     core::helpers::{
-        airdrop, assert_account_empty, clone_keypair, get_balance, get_token_balance,
+        airdrop, assert_account_empty, clone_keypair, get_account, get_balance, get_token_balance,
         new_funded_keypair,
     },
     utils::{
         auto_config, candy_machine_program_test,
-        helpers::{sol, test_start},
+        helpers::{find_mint_receipt, find_refund_record, sol, test_start},
         CandyManager, FreezeConfig, WhitelistConfig,
     },
 };
@@ -182,16 +185,10 @@ async fn freeze_update() {
 
     candy_manager.remove_freeze(context).await.unwrap();
 
-    let candy_machine_account = candy_manager.get_candy(context).await;
+    let features = candy_manager.get_features(context).await;
     assert_account_empty(context, &candy_manager.freeze_info.pda).await;
-    assert!(!is_feature_active(
-        &candy_machine_account.data.uuid,
-        FREEZE_FEATURE_INDEX
-    ));
-    assert!(!is_feature_active(
-        &candy_machine_account.data.uuid,
-        FREEZE_LOCK_FEATURE_INDEX
-    ));
+    assert!(!features.is_active(Feature::Freeze));
+    assert!(!features.is_active(Feature::FreezeLock));
 
     candy_manager.set_freeze(context).await.unwrap();
     candy_manager
@@ -227,9 +224,9 @@ async fn freeze_update() {
     expected_freeze_pda.allow_thaw = true;
     let freeze_pda = candy_manager.get_freeze_pda(context).await;
     assert_eq!(freeze_pda, expected_freeze_pda);
-    let uuid = candy_manager.get_candy(context).await.data.uuid;
-    assert!(!is_feature_active(&uuid, FREEZE_FEATURE_INDEX));
-    assert!(is_feature_active(&uuid, FREEZE_LOCK_FEATURE_INDEX));
+    let features = candy_manager.get_features(context).await;
+    assert!(!features.is_active(Feature::Freeze));
+    assert!(features.is_active(Feature::FreezeLock));
 
     candy_manager
         .thaw_nft(context, &new_nft, &random_key)
@@ -555,3 +552,186 @@ async fn mint_out_unfreeze() {
     let post_balance = get_balance(context, &candy_manager.authority.pubkey()).await;
     assert!(post_balance - pre_balance >= sol(2.0));
 }
+
+#[tokio::test]
+async fn freeze_refund() {
+    test_start("Test Freeze Refund");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let freeze_time = 60 * 60;
+    let mut candy_manager = CandyManager::init(
+        context,
+        None,
+        false,
+        Some(FreezeConfig::new(true, freeze_time)),
+        None,
+        None,
+    )
+    .await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(20.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager.set_freeze(context).await.unwrap();
+    candy_manager
+        .update_freeze_refund(context, true)
+        .await
+        .unwrap();
+
+    let new_nft = candy_manager.mint_nft(context).await.unwrap();
+    candy_manager.assert_frozen(context, &new_nft).await;
+
+    let start_balance = get_balance(context, &candy_manager.minter.pubkey()).await;
+    candy_manager.refund_nft(context, &new_nft).await.unwrap();
+    let end_balance = get_balance(context, &candy_manager.minter.pubkey()).await;
+    // the price and the rent of the burned accounts, minus the transaction fee
+    assert!(end_balance - start_balance > candy_data.price);
+
+    assert_account_empty(context, &new_nft.metadata_pubkey).await;
+    assert_account_empty(
+        context,
+        &find_refund_record(
+            &candy_manager.candy_machine.pubkey(),
+            &new_nft.mint.pubkey(),
+        ),
+    )
+    .await;
+    assert_eq!(candy_manager.get_candy(context).await.items_redeemed, 0);
+    assert_eq!(candy_manager.get_freeze_pda(context).await.frozen_count, 0);
+
+    // the refunded item is available again
+    let mut last_nft = None;
+    for _ in 0..candy_data.items_available {
+        last_nft = Some(candy_manager.mint_nft(context).await.unwrap());
+    }
+    let candy_machine = candy_manager.get_candy(context).await;
+    assert_eq!(candy_machine.items_redeemed, candy_data.items_available);
+
+    // the funds are no longer locked once sold out
+    candy_manager
+        .refund_nft(context, &last_nft.unwrap())
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn freeze_refund_closes_mint_receipt() {
+    test_start("Test Freeze Refund Closes Mint Receipt");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = CandyManager::init(
+        context,
+        None,
+        false,
+        Some(FreezeConfig::new(true, 60 * 60)),
+        None,
+        None,
+    )
+    .await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(20.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager.create(context, candy_data).await.unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager.set_freeze(context).await.unwrap();
+    candy_manager
+        .update_freeze_refund(context, true)
+        .await
+        .unwrap();
+    candy_manager
+        .update_mint_receipts(context, true)
+        .await
+        .unwrap();
+
+    let new_nft = candy_manager.mint_nft(context).await.unwrap();
+    let mint_receipt = find_mint_receipt(
+        &candy_manager.candy_machine.pubkey(),
+        &new_nft.mint.pubkey(),
+    );
+    get_account(context, &mint_receipt).await;
+
+    // the receipt must be passed while the candy machine has mint receipts
+    candy_manager.mint_receipts = false;
+    candy_manager
+        .refund_nft(context, &new_nft)
+        .await
+        .unwrap_err();
+
+    candy_manager.mint_receipts = true;
+    candy_manager.refund_nft(context, &new_nft).await.unwrap();
+    assert_account_empty(context, &mint_receipt).await;
+}
+
+#[tokio::test]
+async fn migrate_legacy_freeze_features() {
+    test_start("Migrate Legacy Freeze Features");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = CandyManager::init(
+        context,
+        None,
+        false,
+        Some(FreezeConfig::new(true, 60 * 60)),
+        None,
+        None,
+    )
+    .await;
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager.set_freeze(context).await.unwrap();
+
+    // rewrite the account the way candy machines stored freeze before the features header
+    let candy_machine = candy_manager.candy_machine.pubkey();
+    let mut account = get_account(context, &candy_machine).await;
+    let mut legacy = CandyMachine::try_deserialize(&mut account.data.as_ref()).unwrap();
+    legacy.data.uuid = String::from("0##000");
+    legacy
+        .try_serialize(&mut account.data.as_mut_slice())
+        .unwrap();
+    account.data[FEATURES_START..FEATURES_START + 4].fill(0);
+    context.set_account(&candy_machine, &AccountSharedData::from(account));
+
+    let features = candy_manager.get_features(context).await;
+    assert_eq!(features.version, 0);
+    assert!(features.is_active(Feature::Freeze));
+    assert!(features.is_active(Feature::FreezeLock));
+    candy_manager.withdraw(context).await.unwrap_err();
+
+    candy_manager.migrate_features(context).await.unwrap();
+    let features = candy_manager.get_features(context).await;
+    assert_eq!(features.version, CandyMachineFeatures::VERSION);
+    assert_eq!(
+        features.flags,
+        Feature::Freeze as u16 | Feature::FreezeLock as u16
+    );
+
+    let err = candy_manager
+        .migrate_features(context)
+        .await
+        .unwrap_err()
+        .unwrap();
+    let candy_error_num: u32 = CandyError::FeaturesAlreadyMigrated.into();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::Custom(candy_error_num))
+    );
+
+    // the uuid is no longer read once migrated
+    candy_manager.remove_freeze(context).await.unwrap();
+    let features = candy_manager.get_features(context).await;
+    assert_eq!(features.flags, 0);
+    assert_eq!(candy_manager.get_candy(context).await.data.uuid, "0##000");
+}
@@ -13,24 +13,28 @@ use solana_gateway::{
         get_gatekeeper_address_with_seed, get_gateway_token_address_with_seed, GatewayTokenState,
     },
 };
-use solana_program::pubkey;
+use solana_program::{instruction::InstructionError, pubkey};
 use solana_program_test::*;
 use solana_sdk::{
     clock::UnixTimestamp,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 use mpl_candy_machine::{
-    CandyMachineData, GatekeeperConfig as GKConfig, WhitelistMintMode::BurnEveryTime,
+    CandyError, CandyMachineData, ConfigLine, ConfigLineSettings, GatekeeperConfig as GKConfig,
+    WhitelistMintMode::BurnEveryTime,
 };
 use utils::{custom_config, GatekeeperInfo};
 
 use crate::{
-    core::helpers::{airdrop, assert_account_empty, get_balance},
+    core::{
+        helpers::{airdrop, assert_account_empty, get_balance},
+        MetadataManager,
+    },
     utils::{
-        auto_config, candy_machine_program_test,
+        add_config_lines, auto_config, candy_machine_program_test,
         helpers::{sol, test_start},
         CandyManager, GatekeeperConfig, WhitelistConfig,
     },
@@ -92,3 +96,73 @@ async fn init_default_success() {
     assert_account_empty(context, &candy_manager.collection_info.pda).await;
     assert!(post_balance > pre_balance);
 }
+
+#[tokio::test]
+async fn init_compressed_config_lines() {
+    test_start("Init Compressed Config Lines");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(2.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    let items_available = candy_data.items_available;
+    candy_manager
+        .create_compressed(
+            context,
+            candy_data,
+            ConfigLineSettings {
+                prefix_name: "Item #".to_string(),
+                name_length: 2,
+                prefix_uri: "https://example.com/".to_string(),
+                uri_length: 7,
+            },
+        )
+        .await
+        .unwrap();
+
+    let err = add_config_lines(
+        context,
+        &candy_manager.candy_machine.pubkey(),
+        &candy_manager.authority,
+        0,
+        vec![ConfigLine {
+            name: "100".to_string(),
+            uri: "100.json".to_string(),
+        }],
+    )
+    .await
+    .unwrap_err()
+    .unwrap();
+    let err = match err {
+        TransactionError::InstructionError(0, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    };
+    let candy_error_num: u32 = CandyError::ConfigLineTooLong.into();
+    assert_eq!(err, candy_error_num);
+
+    let config_lines = (0..items_available)
+        .map(|i| ConfigLine {
+            name: i.to_string(),
+            uri: format!("{}.json", i),
+        })
+        .collect();
+    add_config_lines(
+        context,
+        &candy_manager.candy_machine.pubkey(),
+        &candy_manager.authority,
+        0,
+        config_lines,
+    )
+    .await
+    .unwrap();
+
+    let new_nft = candy_manager.mint_nft(context).await.unwrap();
+    let metadata = MetadataManager::get_data_from_account(context, &new_nft.metadata_pubkey).await;
+    let name = metadata.data.name.trim_matches(char::from(0));
+    let uri = metadata.data.uri.trim_matches(char::from(0));
+    let suffix = name.strip_prefix("Item #").unwrap();
+    assert_eq!(uri, format!("https://example.com/{}.json", suffix));
+}
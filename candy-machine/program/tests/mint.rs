@@ -2,7 +2,7 @@
 #![allow(dead_code)]
 
 use anchor_client::solana_sdk::transaction::Transaction;
-use mpl_candy_machine::WhitelistMintMode;
+use mpl_candy_machine::{BotTax, CandyError, MintLimit, WhitelistMintMode};
 use solana_program::{instruction::InstructionError, pubkey::Pubkey};
 use solana_program_test::*;
 use solana_sdk::{
@@ -13,11 +13,14 @@ use solana_sdk::{
 };
 
 use crate::{
-    core::helpers::{airdrop, prepare_nft, update_blockhash},
+    core::{
+        helpers::{airdrop, get_balance, get_token_balance, prepare_nft, update_blockhash},
+        MetadataManager,
+    },
     utils::{
         auto_config, candy_machine_program_test,
         helpers::{find_candy_creator, sol, test_start},
-        mint_nft, mint_nft_ix, CandyManager, WhitelistConfig, WhitelistInfo,
+        mint_nft, mint_nft_ix, mint_nfts_ix, CandyManager, WhitelistConfig, WhitelistInfo,
     },
 };
 
@@ -71,6 +74,12 @@ async fn fail_metadata_not_blank() {
         candy_manager.collection_info.clone(),
         candy_manager.gateway_info.clone(),
         candy_manager.freeze_info.clone(),
+        candy_manager.mint_limit_info.clone(),
+        candy_manager.phase_info.clone(),
+        candy_manager.payment_info.clone(),
+        candy_manager.mint_commitment,
+        candy_manager.programmable.clone(),
+        candy_manager.mint_receipts,
     )
     .await
     .unwrap_err()
@@ -141,6 +150,12 @@ async fn metadata_check_before_bot_tax() {
         candy_manager.collection_info.clone(),
         candy_manager.gateway_info.clone(),
         candy_manager.freeze_info.clone(),
+        candy_manager.mint_limit_info.clone(),
+        candy_manager.phase_info.clone(),
+        candy_manager.payment_info.clone(),
+        candy_manager.mint_commitment,
+        candy_manager.programmable.clone(),
+        candy_manager.mint_receipts,
     );
 
     ix[0].accounts.pop();
@@ -166,3 +181,315 @@ async fn metadata_check_before_bot_tax() {
     };
     assert_eq!(err, 6031)
 }
+
+#[tokio::test]
+async fn mint_limit_reached() {
+    test_start("Mint Limit Reached");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+
+    airdrop(context, &candy_manager.minter.pubkey(), sol(6.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager
+        .update_mint_limit(
+            context,
+            Some(MintLimit {
+                limit: 1,
+                bot_tax: false,
+            }),
+        )
+        .await
+        .unwrap();
+
+    candy_manager
+        .mint_and_assert_successful(context, None, true)
+        .await
+        .unwrap();
+
+    let err = match candy_manager.mint_nft(context).await.unwrap_err().unwrap() {
+        TransactionError::InstructionError(0, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    };
+    let candy_error_num: u32 = CandyError::MintLimitReached.into();
+    assert_eq!(err, candy_error_num);
+}
+
+#[tokio::test]
+async fn mint_limit_bot_tax() {
+    test_start("Mint Limit Bot Tax");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+
+    airdrop(context, &candy_manager.minter.pubkey(), sol(6.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager
+        .update_mint_limit(
+            context,
+            Some(MintLimit {
+                limit: 1,
+                bot_tax: true,
+            }),
+        )
+        .await
+        .unwrap();
+
+    candy_manager
+        .mint_and_assert_successful(context, None, true)
+        .await
+        .unwrap();
+    candy_manager
+        .mint_and_assert_bot_tax(context)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn mint_several_nfts() {
+    test_start("Mint Several NFTs");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+
+    airdrop(context, &candy_manager.minter.pubkey(), sol(6.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager
+        .update_mint_limit(
+            context,
+            Some(MintLimit {
+                limit: 4,
+                bot_tax: false,
+            }),
+        )
+        .await
+        .unwrap();
+
+    let start_wallet_balance = get_balance(context, &candy_manager.wallet).await;
+    let new_nfts = candy_manager.mint_nfts(context, 3).await.unwrap();
+    let end_wallet_balance = get_balance(context, &candy_manager.wallet).await;
+    for new_nft in &new_nfts {
+        assert_eq!(
+            get_token_balance(context, &new_nft.token_account).await,
+            1,
+            "Minter is not the owner"
+        );
+    }
+    assert_eq!(candy_manager.get_candy(context).await.items_redeemed, 3);
+    assert_eq!(
+        end_wallet_balance - start_wallet_balance,
+        3 * candy_data.price,
+        "Price wasn't paid for each NFT"
+    );
+
+    // the mint limit applies to the whole batch
+    let err = match candy_manager
+        .mint_nfts(context, 2)
+        .await
+        .unwrap_err()
+        .unwrap()
+    {
+        TransactionError::InstructionError(0, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    };
+    let candy_error_num: u32 = CandyError::MintLimitReached.into();
+    assert_eq!(err, candy_error_num);
+}
+
+#[tokio::test]
+async fn mint_receipts() {
+    test_start("Mint Receipts");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+
+    airdrop(context, &candy_manager.minter.pubkey(), sol(6.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager
+        .update_mint_receipts(context, true)
+        .await
+        .unwrap();
+
+    let mut new_nfts = vec![candy_manager
+        .mint_and_assert_successful(context, None, true)
+        .await
+        .unwrap()];
+    new_nfts.extend(candy_manager.mint_nfts(context, 2).await.unwrap());
+
+    let mut indices = Vec::new();
+    for new_nft in &new_nfts {
+        let mint = new_nft.mint.pubkey();
+        let receipt = candy_manager.get_mint_receipt(context, &mint).await;
+        assert_eq!(receipt.candy_machine, candy_manager.candy_machine.pubkey());
+        assert_eq!(receipt.mint, mint);
+        assert_eq!(receipt.payer, candy_manager.minter.pubkey());
+        assert_eq!(receipt.price, candy_data.price);
+        assert_eq!(receipt.payment_mint, None);
+        assert_eq!(receipt.payment_option, None);
+        assert_eq!(receipt.phase, None);
+        assert!(receipt.index < candy_data.items_available);
+        indices.push(receipt.index);
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    assert_eq!(indices.len(), 3, "Receipts share a config line");
+}
+
+#[tokio::test]
+async fn mint_remaining_items_of_partially_minted() {
+    test_start("Mint Remaining Items Of Partially Minted");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+
+    airdrop(context, &candy_manager.minter.pubkey(), sol(15.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+
+    // each pick comes from the items left, so every config line is minted exactly once
+    let mut names = Vec::new();
+    for _ in 0..candy_data.items_available {
+        let new_nft = candy_manager.mint_nft(context).await.unwrap();
+        let metadata =
+            MetadataManager::get_data_from_account(context, &new_nft.metadata_pubkey).await;
+        names.push(metadata.data.name.trim_matches(char::from(0)).to_string());
+    }
+    names.sort_unstable();
+    names.dedup();
+    assert_eq!(names.len() as u64, candy_data.items_available);
+    assert_eq!(
+        candy_manager.get_candy(context).await.items_redeemed,
+        candy_data.items_available
+    );
+}
+
+#[tokio::test]
+async fn mint_several_nfts_missing_accounts() {
+    test_start("Mint Several NFTs Missing Accounts");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+
+    airdrop(context, &candy_manager.minter.pubkey(), sol(6.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager
+        .update_mint_limit(
+            context,
+            Some(MintLimit {
+                limit: 4,
+                bot_tax: false,
+            }),
+        )
+        .await
+        .unwrap();
+    // fail instead of taxing so the error is returned
+    candy_manager
+        .update_bot_tax(
+            context,
+            Some(BotTax {
+                conditions: 0,
+                ..BotTax::default()
+            }),
+        )
+        .await
+        .unwrap();
+
+    let nft_infos = vec![
+        prepare_nft(context, &candy_manager.minter).await,
+        prepare_nft(context, &candy_manager.minter).await,
+    ];
+    let (candy_machine_creator, creator_bump) =
+        find_candy_creator(&candy_manager.candy_machine.pubkey());
+    let mut ix = mint_nfts_ix(
+        &candy_manager.candy_machine.pubkey(),
+        &candy_machine_creator,
+        creator_bump,
+        &candy_manager.wallet,
+        &candy_manager.authority.pubkey(),
+        &candy_manager.minter,
+        &nft_infos,
+        candy_manager.token_info.clone(),
+        candy_manager.whitelist_info.clone(),
+        candy_manager.collection_info.clone(),
+        candy_manager.gateway_info.clone(),
+        candy_manager.freeze_info.clone(),
+        candy_manager.mint_limit_info.clone(),
+        candy_manager.phase_info.clone(),
+        candy_manager.payment_info.clone(),
+        candy_manager.programmable.clone(),
+        candy_manager.mint_receipts,
+    );
+
+    // the master edition of the second NFT is missing
+    ix[0].accounts.pop();
+    update_blockhash(context).await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        ix.as_slice(),
+        Some(&candy_manager.minter.pubkey()),
+        &[&candy_manager.minter],
+        context.last_blockhash,
+    );
+
+    let err = match context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+    {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(err_num),
+        )) => err_num,
+        _ => 0,
+    };
+    let candy_error_num: u32 = CandyError::IncorrectRemainingAccountsLen.into();
+    assert_eq!(err, candy_error_num);
+    assert_eq!(candy_manager.get_candy(context).await.items_redeemed, 0);
+}
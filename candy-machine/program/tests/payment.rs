@@ -0,0 +1,211 @@
+#![cfg(feature = "test-bpf")]
+#![allow(dead_code)]
+
+use mpl_candy_machine::{CandyError, Payment, PaymentKind, PaymentOption};
+use solana_program::{
+    instruction::{AccountMeta, InstructionError},
+    pubkey::Pubkey,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+use crate::{
+    core::helpers::{
+        airdrop, create_associated_token_account, create_mint, get_balance, get_token_balance,
+        mint_to_wallets,
+    },
+    utils::{
+        auto_config, candy_machine_program_test,
+        helpers::{sol, test_start},
+        CandyManager, PaymentInfo,
+    },
+};
+
+mod core;
+mod utils;
+
+async fn setup_candy_machine(context: &mut ProgramTestContext) -> CandyManager {
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(3.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager
+}
+
+#[tokio::test]
+async fn mint_with_token_and_sol_payment() {
+    test_start("Mint With Token And Sol Payment");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = setup_candy_machine(context).await;
+
+    let mint_authority = Keypair::new();
+    let mint = create_mint(context, &mint_authority.pubkey(), None, 0, None)
+        .await
+        .unwrap()
+        .pubkey();
+    let minter_account = mint_to_wallets(
+        context,
+        &mint,
+        &mint_authority,
+        vec![(candy_manager.minter.pubkey(), 10)],
+    )
+    .await
+    .unwrap()[0];
+    let wallet_account = create_associated_token_account(context, &candy_manager.wallet, &mint)
+        .await
+        .unwrap();
+    let treasury = Pubkey::new_unique();
+    airdrop(context, &treasury, sol(0.01)).await.unwrap();
+
+    candy_manager
+        .update_payment_options(
+            context,
+            vec![
+                PaymentOption {
+                    payments: vec![Payment {
+                        kind: PaymentKind::Sol,
+                        amount: sol(1.0),
+                        destination: None,
+                    }],
+                },
+                PaymentOption {
+                    payments: vec![
+                        Payment {
+                            kind: PaymentKind::Token(mint),
+                            amount: 4,
+                            destination: None,
+                        },
+                        Payment {
+                            kind: PaymentKind::Sol,
+                            amount: sol(0.1),
+                            destination: Some(treasury),
+                        },
+                    ],
+                },
+            ],
+        )
+        .await
+        .unwrap();
+    candy_manager.payment_info = PaymentInfo {
+        payment_option: Some(1),
+        accounts: vec![
+            AccountMeta::new(minter_account, false),
+            AccountMeta::new(wallet_account, false),
+            AccountMeta::new(treasury, false),
+        ],
+    };
+
+    let start_treasury_balance = get_balance(context, &treasury).await;
+    candy_manager.mint_nft(context).await.unwrap();
+
+    assert_eq!(get_token_balance(context, &minter_account).await, 6);
+    assert_eq!(get_token_balance(context, &wallet_account).await, 4);
+    assert_eq!(
+        get_balance(context, &treasury).await - start_treasury_balance,
+        sol(0.1)
+    );
+}
+
+#[tokio::test]
+async fn mint_with_invalid_payment_option() {
+    test_start("Mint With Invalid Payment Option");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = setup_candy_machine(context).await;
+
+    candy_manager
+        .update_payment_options(
+            context,
+            vec![PaymentOption {
+                payments: vec![Payment {
+                    kind: PaymentKind::Sol,
+                    amount: sol(1.0),
+                    destination: None,
+                }],
+            }],
+        )
+        .await
+        .unwrap();
+    candy_manager.payment_info = PaymentInfo {
+        payment_option: Some(1),
+        accounts: vec![],
+    };
+
+    let err = match candy_manager.mint_nft(context).await.unwrap_err().unwrap() {
+        TransactionError::InstructionError(0, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    };
+    let candy_error_num: u32 = CandyError::InvalidPaymentOption.into();
+    assert_eq!(err, candy_error_num);
+}
+
+#[tokio::test]
+async fn mint_receipt_with_payment_option() {
+    test_start("Mint Receipt With Payment Option");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = setup_candy_machine(context).await;
+
+    let mint_authority = Keypair::new();
+    let mint = create_mint(context, &mint_authority.pubkey(), None, 0, None)
+        .await
+        .unwrap()
+        .pubkey();
+    let minter_account = mint_to_wallets(
+        context,
+        &mint,
+        &mint_authority,
+        vec![(candy_manager.minter.pubkey(), 10)],
+    )
+    .await
+    .unwrap()[0];
+    let wallet_account = create_associated_token_account(context, &candy_manager.wallet, &mint)
+        .await
+        .unwrap();
+
+    candy_manager
+        .update_payment_options(
+            context,
+            vec![PaymentOption {
+                payments: vec![Payment {
+                    kind: PaymentKind::Token(mint),
+                    amount: 3,
+                    destination: None,
+                }],
+            }],
+        )
+        .await
+        .unwrap();
+    candy_manager
+        .update_mint_receipts(context, true)
+        .await
+        .unwrap();
+    candy_manager.payment_info = PaymentInfo {
+        payment_option: Some(0),
+        accounts: vec![
+            AccountMeta::new(minter_account, false),
+            AccountMeta::new(wallet_account, false),
+        ],
+    };
+
+    let new_nft = candy_manager.mint_nft(context).await.unwrap();
+
+    let receipt = candy_manager
+        .get_mint_receipt(context, &new_nft.mint.pubkey())
+        .await;
+    assert_eq!(receipt.price, 3);
+    assert_eq!(receipt.payment_mint, Some(mint));
+    assert_eq!(receipt.payment_option, Some(0));
+    assert_eq!(get_token_balance(context, &wallet_account).await, 3);
+}
@@ -0,0 +1,166 @@
+#![cfg(feature = "test-bpf")]
+#![allow(dead_code)]
+
+use anchor_lang::AnchorDeserialize;
+use mpl_candy_machine::{CandyError, HiddenSettings, ProgrammableSettings};
+use mpl_token_metadata::{
+    pda::find_token_record_account,
+    state::{TokenRecord, TokenStandard, TokenState},
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+
+use crate::{
+    core::{
+        helpers::{airdrop, clone_keypair, get_account},
+        MetadataManager,
+    },
+    utils::{
+        auto_config, candy_machine_program_test,
+        helpers::{find_candy_creator, sol, test_start},
+        CandyManager, FreezeConfig,
+    },
+};
+
+mod core;
+mod utils;
+
+async fn setup_candy_machine(
+    context: &mut ProgramTestContext,
+    freeze_config: Option<FreezeConfig>,
+) -> CandyManager {
+    let mut candy_manager =
+        CandyManager::init(context, None, false, freeze_config, None, None).await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(3.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), false, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager
+}
+
+#[tokio::test]
+async fn mint_programmable_nft() {
+    test_start("Mint Programmable NFT");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = setup_candy_machine(context, None).await;
+    candy_manager
+        .update_programmable(context, Some(ProgrammableSettings::default()))
+        .await
+        .unwrap();
+
+    let nft = candy_manager.mint_nft(context).await.unwrap();
+
+    let metadata = MetadataManager::get_data_from_account(context, &nft.metadata_pubkey).await;
+    assert_eq!(
+        metadata.token_standard,
+        Some(TokenStandard::ProgrammableNonFungible)
+    );
+    assert_eq!(metadata.update_authority, candy_manager.authority.pubkey());
+    assert!(!metadata.is_mutable);
+    assert!(metadata.primary_sale_happened);
+
+    let err = candy_manager
+        .update_programmable(context, None)
+        .await
+        .unwrap_err()
+        .unwrap();
+    let candy_error_num: u32 = CandyError::NoChangingProgrammableDuringMint.into();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::Custom(candy_error_num))
+    );
+}
+
+#[tokio::test]
+async fn mint_frozen_programmable_nft() {
+    test_start("Mint Frozen Programmable NFT");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager =
+        setup_candy_machine(context, Some(FreezeConfig::new(true, 60 * 60))).await;
+    candy_manager.set_freeze(context).await.unwrap();
+    candy_manager
+        .update_programmable(context, Some(ProgrammableSettings::default()))
+        .await
+        .unwrap();
+
+    let nft = candy_manager.mint_nft(context).await.unwrap();
+
+    let token_record = find_token_record_account(&nft.mint.pubkey(), &nft.token_account).0;
+    let token_record = get_account(context, &token_record).await;
+    let token_record = TokenRecord::deserialize(&mut token_record.data.as_ref()).unwrap();
+    assert_eq!(token_record.state, TokenState::Locked);
+    assert_eq!(token_record.delegate, Some(candy_manager.freeze_info.pda));
+
+    candy_manager.remove_freeze(context).await.unwrap();
+    let minter = clone_keypair(&candy_manager.minter);
+    candy_manager
+        .thaw_nft(context, &nft, &minter)
+        .await
+        .unwrap();
+
+    let token_record = find_token_record_account(&nft.mint.pubkey(), &nft.token_account).0;
+    let token_record = get_account(context, &token_record).await;
+    let token_record = TokenRecord::deserialize(&mut token_record.data.as_ref()).unwrap();
+    assert_eq!(token_record.state, TokenState::Unlocked);
+    assert_eq!(token_record.delegate, None);
+}
+
+#[tokio::test]
+async fn mint_programmable_nft_for_on_chain_reveal() {
+    test_start("Mint Programmable NFT For On Chain Reveal");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(3.0))
+        .await
+        .unwrap();
+
+    let hidden_settings = HiddenSettings {
+        name: "Hidden".to_string(),
+        uri: "https://hidden.uri".to_string(),
+        hash: [0; 32],
+    };
+    let candy_data = auto_config(
+        &candy_manager,
+        Some(0),
+        false,
+        true,
+        None,
+        Some(hidden_settings),
+    );
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager
+        .update_on_chain_reveal(context, true)
+        .await
+        .unwrap();
+    candy_manager
+        .update_programmable(context, Some(ProgrammableSettings::default()))
+        .await
+        .unwrap();
+
+    let nft = candy_manager.mint_nft(context).await.unwrap();
+
+    // the candy machine keeps the NFT mutable until it is revealed
+    let metadata = MetadataManager::get_data_from_account(context, &nft.metadata_pubkey).await;
+    assert_eq!(
+        metadata.token_standard,
+        Some(TokenStandard::ProgrammableNonFungible)
+    );
+    assert_eq!(
+        metadata.update_authority,
+        find_candy_creator(&candy_manager.candy_machine.pubkey()).0
+    );
+    assert!(metadata.is_mutable);
+}
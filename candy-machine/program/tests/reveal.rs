@@ -0,0 +1,187 @@
+#![cfg(feature = "test-bpf")]
+#![allow(dead_code)]
+
+use anchor_lang::AnchorSerialize;
+use mpl_candy_machine::{CandyError, ConfigLine, HiddenSettings};
+use solana_program::{instruction::InstructionError, keccak};
+use solana_program_test::*;
+use solana_sdk::{signature::Signer, transaction::TransactionError};
+
+use crate::{
+    core::{helpers::airdrop, MetadataManager},
+    utils::{
+        add_reveal_lines, auto_config, candy_machine_program_test,
+        helpers::{make_config_lines, sol, test_start},
+        initialize_reveal_lines, reveal_nft, CandyManager,
+    },
+};
+
+mod core;
+mod utils;
+
+const HIDDEN_NAME: &str = "Hidden";
+
+fn candy_error(err: TransactionError) -> u32 {
+    match err {
+        TransactionError::InstructionError(0, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    }
+}
+
+fn reveal_hash(config_lines: &[ConfigLine]) -> [u8; 32] {
+    config_lines.iter().fold([0; 32], |hash, line| {
+        keccak::hashv(&[&hash, &line.try_to_vec().unwrap()]).0
+    })
+}
+
+async fn setup_hidden_candy_machine(
+    context: &mut ProgramTestContext,
+    config_lines: &[ConfigLine],
+) -> CandyManager {
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(3.0))
+        .await
+        .unwrap();
+
+    let hidden_settings = HiddenSettings {
+        name: HIDDEN_NAME.to_string(),
+        uri: "https://hidden.uri".to_string(),
+        hash: reveal_hash(config_lines),
+    };
+    let mut candy_data = auto_config(
+        &candy_manager,
+        Some(0),
+        false,
+        true,
+        None,
+        Some(hidden_settings),
+    );
+    candy_data.items_available = config_lines.len() as u64;
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager
+        .update_on_chain_reveal(context, true)
+        .await
+        .unwrap();
+    candy_manager
+}
+
+#[tokio::test]
+async fn reveal_hidden_settings_on_chain() {
+    test_start("Reveal Hidden Settings On Chain");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let config_lines = make_config_lines(0, 2);
+    let mut candy_manager = setup_hidden_candy_machine(context, &config_lines).await;
+    let candy_machine = candy_manager.candy_machine.pubkey();
+
+    let first_nft = candy_manager.mint_nft(context).await.unwrap();
+    let reveal_lines =
+        initialize_reveal_lines(context, &candy_machine, &candy_manager.authority, 2)
+            .await
+            .unwrap();
+
+    // lines can't be uploaded while items can still be minted
+    let err = add_reveal_lines(
+        context,
+        &candy_machine,
+        &candy_manager.authority,
+        &reveal_lines,
+        0,
+        make_config_lines(0, 2),
+    )
+    .await
+    .unwrap_err()
+    .unwrap();
+    let candy_error_num: u32 = CandyError::MintNotComplete.into();
+    assert_eq!(candy_error(err), candy_error_num);
+
+    let second_nft = candy_manager.mint_nft(context).await.unwrap();
+    add_reveal_lines(
+        context,
+        &candy_machine,
+        &candy_manager.authority,
+        &reveal_lines,
+        0,
+        make_config_lines(0, 2),
+    )
+    .await
+    .unwrap();
+
+    for (i, (nft, line)) in [first_nft, second_nft]
+        .iter()
+        .zip(config_lines.iter())
+        .enumerate()
+    {
+        let metadata = MetadataManager::get_data_from_account(context, &nft.metadata_pubkey).await;
+        assert_eq!(
+            metadata.data.name.trim_end_matches(char::from(0)),
+            format!("{}#{}", HIDDEN_NAME, i + 1)
+        );
+        assert!(metadata.is_mutable);
+
+        reveal_nft(
+            context,
+            &candy_machine,
+            &reveal_lines,
+            &nft.metadata_pubkey,
+            &candy_manager.minter,
+        )
+        .await
+        .unwrap();
+
+        let metadata = MetadataManager::get_data_from_account(context, &nft.metadata_pubkey).await;
+        assert_eq!(
+            metadata.data.name.trim_end_matches(char::from(0)),
+            line.name
+        );
+        assert_eq!(metadata.data.uri.trim_end_matches(char::from(0)), line.uri);
+        assert_eq!(metadata.update_authority, candy_manager.authority.pubkey());
+        assert!(!metadata.is_mutable);
+    }
+}
+
+#[tokio::test]
+async fn reveal_lines_must_match_hash() {
+    test_start("Reveal Lines Must Match Hash");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+    let mut candy_manager = setup_hidden_candy_machine(context, &make_config_lines(0, 2)).await;
+    let candy_machine = candy_manager.candy_machine.pubkey();
+
+    let nft = candy_manager.mint_nft(context).await.unwrap();
+    candy_manager.mint_nft(context).await.unwrap();
+    let reveal_lines =
+        initialize_reveal_lines(context, &candy_machine, &candy_manager.authority, 2)
+            .await
+            .unwrap();
+
+    let err = add_reveal_lines(
+        context,
+        &candy_machine,
+        &candy_manager.authority,
+        &reveal_lines,
+        0,
+        make_config_lines(1, 2),
+    )
+    .await
+    .unwrap_err()
+    .unwrap();
+    let candy_error_num: u32 = CandyError::RevealHashMismatch.into();
+    assert_eq!(candy_error(err), candy_error_num);
+
+    let err = reveal_nft(
+        context,
+        &candy_machine,
+        &reveal_lines,
+        &nft.metadata_pubkey,
+        &candy_manager.minter,
+    )
+    .await
+    .unwrap_err()
+    .unwrap();
+    let candy_error_num: u32 = CandyError::RevealLinesNotVerified.into();
+    assert_eq!(candy_error(err), candy_error_num);
+}
@@ -1,9 +1,24 @@
 #![cfg(feature = "test-bpf")]
 #![allow(dead_code)]
 
-use crate::utils::{auto_config, candy_machine_program_test, helpers::test_start, CandyManager};
+use crate::{
+    core::helpers::{airdrop, get_account},
+    utils::{
+        add_config_lines, auto_config, candy_machine_program_test,
+        helpers::{make_config_lines, sol, test_start},
+        CandyManager, ITEMS_AVAILABLE,
+    },
+};
+use mpl_candy_machine::{
+    constants::MAX_SETTINGS_SPACE, BotTax, CandyError, ConfigLinesLayout, MintPhase,
+};
+use solana_program::instruction::InstructionError;
 use solana_program_test::*;
-use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::{
+    account::{AccountSharedData, WritableAccount},
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
 
 mod core;
 mod utils;
@@ -43,3 +58,286 @@ async fn update_auth_with_collection_fails() {
         "Authority wasn't updated correctly!"
     );
 }
+
+#[tokio::test]
+async fn update_mint_phases_before_mint() {
+    test_start("Update Mint Phases Before Mint");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(2.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, None, true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+
+    let phases = vec![MintPhase {
+        label: "public".to_string(),
+        start_date: 0,
+        end_date: None,
+        price: sol(0.5),
+        mint_limit: None,
+        gate_mint: None,
+        allowlist_root: None,
+    }];
+    candy_manager
+        .update_mint_phases(context, phases.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        candy_manager.get_settings(context).await.phases,
+        phases,
+        "Mint phases weren't stored correctly!"
+    );
+
+    // the phase replaces the missing go live date and the price
+    candy_manager
+        .mint_and_assert_successful(context, Some(sol(0.5)), true)
+        .await
+        .unwrap();
+
+    let error_num = match candy_manager
+        .update_mint_phases(context, vec![])
+        .await
+        .unwrap_err()
+        .unwrap()
+    {
+        TransactionError::InstructionError(_, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    };
+    let candy_error_num: u32 = CandyError::NoChangingPhasesDuringMint.into();
+    assert_eq!(error_num, candy_error_num);
+}
+
+#[tokio::test]
+async fn update_mint_phases_grows_existing_candy_machine() {
+    test_start("Update Mint Phases Grows Existing Candy Machine");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    let candy_data = auto_config(&candy_manager, None, true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+
+    // candy machines created before the settings have no room after their config lines
+    let candy_key = candy_manager.candy_machine.pubkey();
+    let account = get_account(context, &candy_key).await;
+    let data = account.data[..account.data.len() - MAX_SETTINGS_SPACE].to_vec();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    context.set_account(
+        &candy_key,
+        &AccountSharedData::create(
+            rent.minimum_balance(data.len()),
+            data,
+            mpl_candy_machine::id(),
+            false,
+            0,
+        ),
+    );
+    let start_len = get_account(context, &candy_key).await.data.len();
+
+    let phases = vec![MintPhase {
+        label: "public".to_string(),
+        start_date: 0,
+        end_date: None,
+        price: sol(0.5),
+        mint_limit: None,
+        gate_mint: None,
+        allowlist_root: None,
+    }];
+    candy_manager
+        .update_mint_phases(context, phases.clone())
+        .await
+        .unwrap();
+    assert_eq!(candy_manager.get_settings(context).await.phases, phases);
+    let account = get_account(context, &candy_key).await;
+    assert!(account.data.len() > start_len, "Candy machine didn't grow");
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+}
+
+#[tokio::test]
+async fn update_mint_phases_overlapping_fails() {
+    test_start("Update Mint Phases Overlapping Fails");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+
+    let og = MintPhase {
+        label: "og".to_string(),
+        start_date: 0,
+        end_date: Some(100),
+        price: sol(0.5),
+        mint_limit: Some(1),
+        gate_mint: None,
+        allowlist_root: None,
+    };
+    let public = MintPhase {
+        label: "public".to_string(),
+        start_date: 50,
+        end_date: None,
+        price: sol(1.0),
+        mint_limit: None,
+        gate_mint: None,
+        allowlist_root: None,
+    };
+    let error_num = match candy_manager
+        .update_mint_phases(context, vec![og, public])
+        .await
+        .unwrap_err()
+        .unwrap()
+    {
+        TransactionError::InstructionError(_, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    };
+    let candy_error_num: u32 = CandyError::InvalidMintPhases.into();
+    assert_eq!(error_num, candy_error_num);
+    assert!(candy_manager.get_settings(context).await.phases.is_empty());
+}
+
+#[tokio::test]
+async fn resize_candy_machine() {
+    test_start("Resize Candy Machine");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(3.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    candy_manager
+        .update_bot_tax(context, Some(BotTax::default()))
+        .await
+        .unwrap();
+    candy_manager
+        .mint_and_assert_successful(context, Some(sol(1.0)), true)
+        .await
+        .unwrap();
+
+    let items_available = ITEMS_AVAILABLE + 5;
+    candy_manager
+        .resize(context, items_available)
+        .await
+        .unwrap();
+    add_config_lines(
+        context,
+        &candy_manager.candy_machine.pubkey(),
+        &candy_manager.authority,
+        ITEMS_AVAILABLE as u32,
+        make_config_lines(ITEMS_AVAILABLE as u32, 5),
+    )
+    .await
+    .unwrap();
+
+    // the settings and the minted item moved after the new config lines
+    let candy_machine = candy_manager.get_candy(context).await;
+    assert_eq!(candy_machine.data.items_available, items_available);
+    assert_eq!(candy_machine.items_redeemed, 1);
+    assert_eq!(
+        candy_manager.get_settings(context).await.bot_tax,
+        Some(BotTax::default())
+    );
+    let account = get_account(context, &candy_manager.candy_machine.pubkey()).await;
+    let layout = ConfigLinesLayout::load(&candy_machine.data, &account.data).unwrap();
+    let minted_start = layout.minted_bit_mask_start(items_available as usize);
+    let minted = account.data[minted_start..minted_start + items_available as usize / 8 + 1]
+        .iter()
+        .map(|byte| byte.count_ones())
+        .sum::<u32>();
+    assert_eq!(minted, 1);
+
+    candy_manager
+        .mint_and_assert_successful(context, Some(sol(1.0)), true)
+        .await
+        .unwrap();
+
+    let error_num = match candy_manager
+        .resize(context, ITEMS_AVAILABLE)
+        .await
+        .unwrap_err()
+        .unwrap()
+    {
+        TransactionError::InstructionError(_, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    };
+    let candy_error_num: u32 = CandyError::CannotShrinkCandyMachine.into();
+    assert_eq!(error_num, candy_error_num);
+}
+
+#[tokio::test]
+async fn mint_between_resize_and_add_config_lines() {
+    test_start("Mint Between Resize And Add Config Lines");
+    let mut context = candy_machine_program_test().start_with_context().await;
+    let context = &mut context;
+
+    let mut candy_manager = CandyManager::init(context, None, false, None, None, None).await;
+    airdrop(context, &candy_manager.minter.pubkey(), sol(20.0))
+        .await
+        .unwrap();
+
+    let candy_data = auto_config(&candy_manager, Some(0), true, true, None, None);
+    candy_manager
+        .create(context, candy_data.clone())
+        .await
+        .unwrap();
+    candy_manager.fill_config_lines(context).await.unwrap();
+    for _ in 0..ITEMS_AVAILABLE {
+        candy_manager
+            .mint_and_assert_successful(context, Some(sol(1.0)), true)
+            .await
+            .unwrap();
+    }
+
+    // only the new, still empty lines are left to mint
+    let items_available = ITEMS_AVAILABLE + 5;
+    candy_manager
+        .resize(context, items_available)
+        .await
+        .unwrap();
+    let error_num = match candy_manager.mint_nft(context).await.unwrap_err().unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(err_num)) => err_num,
+        _ => 0,
+    };
+    let candy_error_num: u32 = CandyError::CannotFindUsableConfigLine.into();
+    assert_eq!(error_num, candy_error_num);
+    assert_eq!(
+        candy_manager.get_candy(context).await.items_redeemed,
+        ITEMS_AVAILABLE
+    );
+
+    add_config_lines(
+        context,
+        &candy_manager.candy_machine.pubkey(),
+        &candy_manager.authority,
+        ITEMS_AVAILABLE as u32,
+        make_config_lines(ITEMS_AVAILABLE as u32, 5),
+    )
+    .await
+    .unwrap();
+    candy_manager
+        .mint_and_assert_successful(context, Some(sol(1.0)), true)
+        .await
+        .unwrap();
+}
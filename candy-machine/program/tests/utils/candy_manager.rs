@@ -1,9 +1,11 @@
 use std::{fmt::Debug, str::FromStr};
 
-use anchor_lang::AccountDeserialize;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize};
 use mpl_token_metadata::{pda::find_collection_authority_account, state::Metadata};
 use solana_gateway::state::{get_expire_address_with_seed, get_gateway_token_address_with_seed};
-use solana_program::{clock::Clock, program_option::COption, pubkey::Pubkey};
+use solana_program::{
+    clock::Clock, instruction::AccountMeta, program_option::COption, pubkey::Pubkey,
+};
 use solana_program_test::ProgramTestContext;
 use solana_sdk::{
     signature::{Keypair, Signer},
@@ -13,8 +15,11 @@ use spl_associated_token_account::get_associated_token_address;
 use spl_token::state::AccountState;
 
 use mpl_candy_machine::{
-    constants::{BOT_FEE, FREEZE_FEATURE_INDEX, FREEZE_LOCK_FEATURE_INDEX},
-    is_feature_active, CandyMachine, CandyMachineData, CollectionPDA, FreezePDA, WhitelistMintMode,
+    constants::BOT_FEE,
+    get_space_for_candy, AllowlistEntry, AllowlistProof, BotTax, CandyMachine, CandyMachineData,
+    CandyMachineFeatures, CandyMachineSettings, CollectionPDA, ConfigLineSettings,
+    ConfigLinesLayout, Feature, FreezePDA, MintCommitment, MintCounter, MintLimit, MintPhase,
+    MintReceipt, PaymentOption, ProgrammableSettings, WhitelistMintMode,
     WhitelistMintMode::{BurnEveryTime, NeverBurn},
 };
 
@@ -23,15 +28,22 @@ use crate::{
         helpers::{
             airdrop, assert_account_empty, clone_keypair, create_associated_token_account,
             create_mint, get_account, get_balance, get_token_account, get_token_balance,
-            mint_to_wallets, prepare_nft,
+            mint_to_wallets, prepare_nft, prepare_programmable_nft,
         },
         MasterEditionManager, MetadataManager,
     },
     utils::{
-        add_all_config_lines,
-        helpers::{find_candy_creator, find_collection_pda, sol, CandyTestLogger},
-        initialize_candy_machine, mint_nft, remove_collection, remove_freeze, set_collection,
-        set_freeze, thaw_nft, unlock_funds, update_authority, update_candy_machine, withdraw_funds,
+        add_all_config_lines, commit_mint,
+        helpers::{
+            find_candy_creator, find_collection_pda, find_mint_commitment, find_mint_receipt, sol,
+            CandyTestLogger,
+        },
+        initialize_candy_machine, migrate_features, mint_nft, mint_nfts, refund_nft,
+        remove_collection, remove_freeze, resize_candy_machine, set_collection, set_freeze,
+        submit_allowlist_proof, thaw_nft, unlock_funds, update_authority, update_bot_tax,
+        update_candy_machine, update_commit_reveal, update_freeze_refund, update_mint_limit,
+        update_mint_phases, update_mint_receipts, update_on_chain_reveal, update_payment_options,
+        update_programmable, withdraw_funds,
     },
 };
 
@@ -46,6 +58,12 @@ pub struct CandyManager {
     pub whitelist_info: WhitelistInfo,
     pub gateway_info: GatekeeperInfo,
     pub freeze_info: FreezeInfo,
+    pub mint_limit_info: MintLimitInfo,
+    pub phase_info: PhaseInfo,
+    pub payment_info: PaymentInfo,
+    pub mint_commitment: Option<Pubkey>,
+    pub programmable: Option<ProgrammableSettings>,
+    pub mint_receipts: bool,
 }
 
 impl Clone for CandyManager {
@@ -60,6 +78,12 @@ impl Clone for CandyManager {
             whitelist_info: self.whitelist_info.clone(),
             gateway_info: self.gateway_info.clone(),
             freeze_info: self.freeze_info.clone(),
+            mint_limit_info: self.mint_limit_info.clone(),
+            phase_info: self.phase_info.clone(),
+            payment_info: self.payment_info.clone(),
+            mint_commitment: self.mint_commitment,
+            programmable: self.programmable.clone(),
+            mint_receipts: self.mint_receipts,
         }
     }
 }
@@ -332,6 +356,7 @@ pub struct FreezeInfo {
     pub set: bool,
     pub ata: Pubkey,
     pub pda: Pubkey,
+    pub refund: bool,
 }
 
 impl FreezeInfo {
@@ -344,6 +369,7 @@ impl FreezeInfo {
             pda,
             freeze_time,
             ata: freeze_ata,
+            refund: false,
         }
     }
 
@@ -366,6 +392,65 @@ impl FreezeInfo {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct MintLimitInfo {
+    pub set: bool,
+    pub mint_counter: Pubkey,
+}
+
+impl MintLimitInfo {
+    pub fn new(set: bool, candy_machine: &Pubkey, minter: &Pubkey) -> Self {
+        let seeds: &[&[u8]] = &[
+            MintCounter::PREFIX.as_bytes(),
+            candy_machine.as_ref(),
+            minter.as_ref(),
+        ];
+        let mint_counter = Pubkey::find_program_address(seeds, &mpl_candy_machine::ID).0;
+        MintLimitInfo { set, mint_counter }
+    }
+}
+
+/// Accounts required by the active mint phase.
+#[derive(Debug, Clone, Default)]
+pub struct PhaseInfo {
+    pub gate_token_account: Option<Pubkey>,
+    pub allowlist_proof: Option<Pubkey>,
+    pub mint_counter: Option<Pubkey>,
+}
+
+impl PhaseInfo {
+    pub fn find_allowlist_proof(
+        candy_machine: &Pubkey,
+        minter: &Pubkey,
+        phase_index: u8,
+    ) -> Pubkey {
+        let seeds: &[&[u8]] = &[
+            AllowlistProof::PREFIX.as_bytes(),
+            candy_machine.as_ref(),
+            minter.as_ref(),
+            &[phase_index],
+        ];
+        Pubkey::find_program_address(seeds, &mpl_candy_machine::ID).0
+    }
+
+    pub fn find_mint_counter(candy_machine: &Pubkey, minter: &Pubkey, phase_index: u8) -> Pubkey {
+        let seeds: &[&[u8]] = &[
+            MintCounter::PREFIX.as_bytes(),
+            candy_machine.as_ref(),
+            minter.as_ref(),
+            &[phase_index],
+        ];
+        Pubkey::find_program_address(seeds, &mpl_candy_machine::ID).0
+    }
+}
+
+/// Payment option selected on mint and the accounts its payments require.
+#[derive(Debug, Clone, Default)]
+pub struct PaymentInfo {
+    pub payment_option: Option<u8>,
+    pub accounts: Vec<AccountMeta>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct FreezeConfig {
     pub set: bool,
@@ -492,6 +577,11 @@ impl CandyManager {
         whitelist_info: WhitelistInfo,
         gateway_info: GatekeeperInfo,
         freeze_info: FreezeInfo,
+        mint_limit_info: MintLimitInfo,
+        phase_info: PhaseInfo,
+        payment_info: PaymentInfo,
+        mint_commitment: Option<Pubkey>,
+        programmable: Option<ProgrammableSettings>,
     ) -> Self {
         CandyManager {
             candy_machine,
@@ -503,6 +593,12 @@ impl CandyManager {
             whitelist_info,
             gateway_info,
             freeze_info,
+            mint_limit_info,
+            phase_info,
+            payment_info,
+            mint_commitment,
+            programmable,
+            mint_receipts: false,
         }
     }
 
@@ -611,6 +707,8 @@ impl CandyManager {
             }
         };
 
+        let mint_limit_info = MintLimitInfo::new(false, &candy_machine.pubkey(), &minter.pubkey());
+
         let wallet = match &token_info.set {
             true => token_info.auth_account,
             false => authority.pubkey(),
@@ -626,6 +724,11 @@ impl CandyManager {
             whitelist_info,
             gateway_info,
             freeze_info,
+            mint_limit_info,
+            PhaseInfo::default(),
+            PaymentInfo::default(),
+            None,
+            None,
         )
     }
 
@@ -634,6 +737,20 @@ impl CandyManager {
         CandyMachine::try_deserialize(&mut account.data.as_ref()).unwrap()
     }
 
+    pub async fn get_settings(&self, context: &mut ProgramTestContext) -> CandyMachineSettings {
+        let candy_machine = self.get_candy(context).await;
+        let account = get_account(context, &self.candy_machine.pubkey()).await;
+        let layout = ConfigLinesLayout::load(&candy_machine.data, &account.data).unwrap();
+        let start = get_space_for_candy(&candy_machine.data, &layout).unwrap();
+        CandyMachineSettings::deserialize(&mut &account.data[start..]).unwrap()
+    }
+
+    pub async fn get_features(&self, context: &mut ProgramTestContext) -> CandyMachineFeatures {
+        let candy_machine = self.get_candy(context).await;
+        let account = get_account(context, &self.candy_machine.pubkey()).await;
+        CandyMachineFeatures::from_account_data(&candy_machine.data, &account.data).unwrap()
+    }
+
     pub async fn get_collection_pda(&self, context: &mut ProgramTestContext) -> CollectionPDA {
         let account = get_account(context, &self.collection_info.pda).await;
         CollectionPDA::try_deserialize(&mut account.data.as_ref()).unwrap()
@@ -644,22 +761,34 @@ impl CandyManager {
         FreezePDA::try_deserialize(&mut account.data.as_ref()).unwrap()
     }
 
+    pub async fn get_mint_commitment(&self, context: &mut ProgramTestContext) -> MintCommitment {
+        let account = get_account(context, &self.mint_commitment.unwrap()).await;
+        MintCommitment::try_deserialize(&mut account.data.as_ref()).unwrap()
+    }
+
+    pub async fn get_mint_receipt(
+        &self,
+        context: &mut ProgramTestContext,
+        mint: &Pubkey,
+    ) -> MintReceipt {
+        let account = get_account(
+            context,
+            &find_mint_receipt(&self.candy_machine.pubkey(), mint),
+        )
+        .await;
+        MintReceipt::try_deserialize(&mut account.data.as_ref()).unwrap()
+    }
+
     pub async fn assert_freeze_set(
         &self,
         context: &mut ProgramTestContext,
         expected_freeze_pda: &FreezePDA,
     ) -> FreezePDA {
         let freeze_pda_account = self.get_freeze_pda(context).await;
-        let candy_machine_account = self.get_candy(context).await;
+        let features = self.get_features(context).await;
         assert_eq!(*expected_freeze_pda, freeze_pda_account);
-        assert!(is_feature_active(
-            &candy_machine_account.data.uuid,
-            FREEZE_FEATURE_INDEX
-        ));
-        assert!(is_feature_active(
-            &candy_machine_account.data.uuid,
-            FREEZE_LOCK_FEATURE_INDEX
-        ));
+        assert!(features.is_active(Feature::Freeze));
+        assert!(features.is_active(Feature::FreezeLock));
         freeze_pda_account
     }
 
@@ -737,6 +866,28 @@ impl CandyManager {
             &self.wallet,
             candy_data,
             self.token_info.clone(),
+            None,
+        )
+        .await?;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn create_compressed(
+        &mut self,
+        context: &mut ProgramTestContext,
+        candy_data: CandyMachineData,
+        config_line_settings: ConfigLineSettings,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Initialize Compressed Candy Machine");
+        initialize_candy_machine(
+            context,
+            &self.candy_machine,
+            &self.authority,
+            &self.wallet,
+            candy_data,
+            self.token_info.clone(),
+            Some(config_line_settings),
         )
         .await?;
         logger.end();
@@ -788,6 +939,23 @@ impl CandyManager {
         Ok(())
     }
 
+    pub async fn resize(
+        &mut self,
+        context: &mut ProgramTestContext,
+        items_available: u64,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Resize Candy Machine");
+        resize_candy_machine(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.authority,
+            items_available,
+        )
+        .await?;
+        logger.end();
+        Ok(())
+    }
+
     pub async fn update(
         &mut self,
         context: &mut ProgramTestContext,
@@ -834,6 +1002,221 @@ impl CandyManager {
         Ok(())
     }
 
+    pub async fn update_mint_phases(
+        &mut self,
+        context: &mut ProgramTestContext,
+        phases: Vec<MintPhase>,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Update Mint Phases");
+        update_mint_phases(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.authority,
+            &self.wallet,
+            phases,
+        )
+        .await?;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn submit_allowlist_proof(
+        &mut self,
+        context: &mut ProgramTestContext,
+        phase_index: u8,
+        entry: AllowlistEntry,
+        proof: Vec<[u8; 32]>,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Submit Allowlist Proof");
+        submit_allowlist_proof(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.minter,
+            phase_index,
+            entry,
+            proof,
+        )
+        .await?;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn update_mint_limit(
+        &mut self,
+        context: &mut ProgramTestContext,
+        mint_limit: Option<MintLimit>,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Update Mint Limit");
+        let set = mint_limit.is_some();
+        update_mint_limit(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.authority,
+            &self.wallet,
+            mint_limit,
+        )
+        .await?;
+        self.mint_limit_info.set = set;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn update_payment_options(
+        &mut self,
+        context: &mut ProgramTestContext,
+        payment_options: Vec<PaymentOption>,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Update Payment Options");
+        update_payment_options(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.authority,
+            &self.wallet,
+            payment_options,
+        )
+        .await?;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn update_commit_reveal(
+        &mut self,
+        context: &mut ProgramTestContext,
+        commit_reveal: bool,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Update Commit Reveal");
+        update_commit_reveal(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.authority,
+            &self.wallet,
+            commit_reveal,
+        )
+        .await?;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn update_on_chain_reveal(
+        &mut self,
+        context: &mut ProgramTestContext,
+        on_chain_reveal: bool,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Update On Chain Reveal");
+        update_on_chain_reveal(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.authority,
+            &self.wallet,
+            on_chain_reveal,
+        )
+        .await?;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn update_programmable(
+        &mut self,
+        context: &mut ProgramTestContext,
+        programmable: Option<ProgrammableSettings>,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Update Programmable");
+        update_programmable(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.authority,
+            &self.wallet,
+            programmable.clone(),
+        )
+        .await?;
+        self.programmable = programmable;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn update_bot_tax(
+        &mut self,
+        context: &mut ProgramTestContext,
+        bot_tax: Option<BotTax>,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Update Bot Tax");
+        update_bot_tax(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.authority,
+            &self.wallet,
+            bot_tax,
+        )
+        .await?;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn update_freeze_refund(
+        &mut self,
+        context: &mut ProgramTestContext,
+        freeze_refund: bool,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Update Freeze Refund");
+        update_freeze_refund(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.authority,
+            &self.wallet,
+            freeze_refund,
+        )
+        .await?;
+        self.freeze_info.refund = freeze_refund;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn update_mint_receipts(
+        &mut self,
+        context: &mut ProgramTestContext,
+        mint_receipts: bool,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Update Mint Receipts");
+        update_mint_receipts(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.authority,
+            &self.wallet,
+            mint_receipts,
+        )
+        .await?;
+        self.mint_receipts = mint_receipts;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn migrate_features(
+        &mut self,
+        context: &mut ProgramTestContext,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Migrate Features");
+        migrate_features(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.authority,
+            &self.wallet,
+        )
+        .await?;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn commit_mint(&mut self, context: &mut ProgramTestContext) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Commit Mint");
+        commit_mint(context, &self.candy_machine.pubkey(), &self.minter).await?;
+        self.mint_commitment = Some(find_mint_commitment(
+            &self.candy_machine.pubkey(),
+            &self.minter.pubkey(),
+        ));
+        logger.end();
+        Ok(())
+    }
+
     pub async fn set_freeze(&mut self, context: &mut ProgramTestContext) -> transport::Result<()> {
         let logger = CandyTestLogger::new_start("Set freeze");
         set_freeze(
@@ -879,6 +1262,26 @@ impl CandyManager {
             authority,
             &self.freeze_info,
             nft_info,
+            self.programmable.is_some(),
+        )
+        .await?;
+        logger.end();
+        Ok(())
+    }
+
+    pub async fn refund_nft(
+        &mut self,
+        context: &mut ProgramTestContext,
+        nft_info: &MasterEditionManager,
+    ) -> transport::Result<()> {
+        let logger = CandyTestLogger::new_start("Refund NFT");
+        refund_nft(
+            context,
+            &self.candy_machine.pubkey(),
+            &self.freeze_info,
+            &self.token_info,
+            nft_info,
+            self.mint_receipts,
         )
         .await?;
         logger.end();
@@ -908,7 +1311,11 @@ impl CandyManager {
         context: &mut ProgramTestContext,
     ) -> transport::Result<MasterEditionManager> {
         let logger = CandyTestLogger::new_start("Mint NFT");
-        let nft_info = prepare_nft(context, &self.minter).await;
+        let nft_info = if self.programmable.is_some() {
+            prepare_programmable_nft(context, &self.minter).await
+        } else {
+            prepare_nft(context, &self.minter).await
+        };
         let (candy_machine_creator, creator_bump) =
             find_candy_creator(&self.candy_machine.pubkey());
         mint_nft(
@@ -925,12 +1332,59 @@ impl CandyManager {
             self.collection_info.clone(),
             self.gateway_info.clone(),
             self.freeze_info.clone(),
+            self.mint_limit_info.clone(),
+            self.phase_info.clone(),
+            self.payment_info.clone(),
+            self.mint_commitment,
+            self.programmable.clone(),
+            self.mint_receipts,
         )
         .await?;
         logger.end();
         Ok(nft_info)
     }
 
+    pub async fn mint_nfts(
+        &mut self,
+        context: &mut ProgramTestContext,
+        count: u8,
+    ) -> transport::Result<Vec<MasterEditionManager>> {
+        let logger = CandyTestLogger::new_start("Mint NFTs");
+        let mut nft_infos = Vec::new();
+        for _ in 0..count {
+            nft_infos.push(if self.programmable.is_some() {
+                prepare_programmable_nft(context, &self.minter).await
+            } else {
+                prepare_nft(context, &self.minter).await
+            });
+        }
+        let (candy_machine_creator, creator_bump) =
+            find_candy_creator(&self.candy_machine.pubkey());
+        mint_nfts(
+            context,
+            &self.candy_machine.pubkey(),
+            &candy_machine_creator,
+            creator_bump,
+            &self.wallet,
+            &self.authority.pubkey(),
+            &self.minter,
+            &nft_infos,
+            self.token_info.clone(),
+            self.whitelist_info.clone(),
+            self.collection_info.clone(),
+            self.gateway_info.clone(),
+            self.freeze_info.clone(),
+            self.mint_limit_info.clone(),
+            self.phase_info.clone(),
+            self.payment_info.clone(),
+            self.programmable.clone(),
+            self.mint_receipts,
+        )
+        .await?;
+        logger.end();
+        Ok(nft_infos)
+    }
+
     pub async fn withdraw(
         &mut self,
         context: &mut ProgramTestContext,
@@ -1,5 +1,6 @@
 use anchor_client::solana_sdk::{signature::Signer, system_program, sysvar};
 use anchor_lang::*;
+use mpl_token_metadata::pda::find_token_record_account;
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
@@ -9,8 +10,11 @@ use solana_program_test::*;
 use solana_sdk::{signature::Keypair, transaction::Transaction};
 
 use mpl_candy_machine::{
-    constants::{CONFIG_ARRAY_START, CONFIG_LINE_SIZE},
-    CandyMachine, CandyMachineData, ConfigLine,
+    constants::{
+        CONFIG_ARRAY_START, CONFIG_LINE_SETTINGS_SIZE, CONFIG_LINE_SIZE, MAX_SETTINGS_SPACE,
+    },
+    AllowlistEntry, BotTax, CandyMachine, CandyMachineData, ConfigLine, ConfigLineSettings,
+    MintLimit, MintPhase, PaymentOption, ProgrammableSettings, RevealLines,
     WhitelistMintMode::BurnEveryTime,
 };
 
@@ -18,8 +22,11 @@ use crate::{
     core::{helpers::update_blockhash, MasterEditionManager},
     utils::{
         candy_manager::{CollectionInfo, GatekeeperInfo, TokenInfo, WhitelistInfo},
-        helpers::make_config_lines,
-        FreezeInfo,
+        helpers::{
+            find_candy_creator, find_mint_commitment, find_mint_receipt, find_refund_record,
+            make_config_lines,
+        },
+        FreezeInfo, MintLimitInfo, PaymentInfo, PhaseInfo,
     },
 };
 use std::result::Result;
@@ -38,44 +45,517 @@ pub async fn initialize_candy_machine(
     wallet: &Pubkey,
     candy_data: CandyMachineData,
     token_info: TokenInfo,
+    config_line_settings: Option<ConfigLineSettings>,
 ) -> Result<(), BanksClientError> {
     let items_available = candy_data.items_available;
+    let config_line_size = match &config_line_settings {
+        Some(settings) => 8 + settings.name_length as usize + settings.uri_length as usize,
+        None => CONFIG_LINE_SIZE,
+    };
+    let config_line_settings_size = if config_line_settings.is_some() {
+        CONFIG_LINE_SETTINGS_SIZE
+    } else {
+        0
+    };
+    // leave room after the config lines for the mint settings
     let candy_account_size = if candy_data.hidden_settings.is_some() {
         CONFIG_ARRAY_START
     } else {
         CONFIG_ARRAY_START
+            + config_line_settings_size
             + 4
-            + items_available as usize * CONFIG_LINE_SIZE
+            + items_available as usize * config_line_size
             + 8
             + 2 * (items_available as usize / 8 + 1)
+    } + MAX_SETTINGS_SPACE;
+
+    let rent = context.banks_client.get_rent().await?;
+    let lamports = rent.minimum_balance(candy_account_size);
+    let create_ix = system_instruction::create_account(
+        &payer.pubkey(),
+        &candy_account.pubkey(),
+        lamports,
+        candy_account_size as u64,
+        &mpl_candy_machine::id(),
+    );
+
+    let mut accounts = mpl_candy_machine::accounts::InitializeCandyMachine {
+        candy_machine: candy_account.pubkey(),
+        wallet: *wallet,
+        authority: payer.pubkey(),
+        payer: payer.pubkey(),
+        system_program: system_program::id(),
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    if token_info.set {
+        accounts.push(AccountMeta::new_readonly(token_info.mint, false));
+    }
+
+    let data = match config_line_settings {
+        Some(config_line_settings) => {
+            mpl_candy_machine::instruction::InitializeCompressedCandyMachine {
+                data: candy_data,
+                config_line_settings,
+            }
+            .data()
+        }
+        None => mpl_candy_machine::instruction::InitializeCandyMachine { data: candy_data }.data(),
+    };
+
+    let init_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[create_ix, init_ix],
+        Some(&payer.pubkey()),
+        &[candy_account, payer],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn update_candy_machine(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    data: CandyMachineData,
+    wallet: &Pubkey,
+    token_mint: Option<Pubkey>,
+) -> Result<(), BanksClientError> {
+    let mut accounts = mpl_candy_machine::accounts::UpdateCandyMachine {
+        candy_machine: *candy_machine,
+        authority: authority.pubkey(),
+        wallet: *wallet,
+    }
+    .to_account_metas(None);
+    if let Some(token_mint) = token_mint {
+        accounts.push(AccountMeta::new_readonly(token_mint, false));
+    }
+
+    let data = mpl_candy_machine::instruction::UpdateCandyMachine { data }.data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn update_authority(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+    new_authority: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let accounts = mpl_candy_machine::accounts::UpdateCandyMachine {
+        candy_machine: *candy_machine,
+        authority: authority.pubkey(),
+        wallet: *wallet,
+    }
+    .to_account_metas(None);
+    let data = mpl_candy_machine::instruction::UpdateAuthority {
+        new_authority: Some(*new_authority),
+    }
+    .data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+// settings updates grow candy machines created without room for the settings, paid by the authority
+fn settings_update_accounts(
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+) -> Vec<AccountMeta> {
+    let mut accounts = mpl_candy_machine::accounts::UpdateCandyMachine {
+        candy_machine: *candy_machine,
+        authority: authority.pubkey(),
+        wallet: *wallet,
+    }
+    .to_account_metas(None);
+    accounts[1].is_writable = true;
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts
+}
+
+pub async fn update_mint_phases(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+    phases: Vec<MintPhase>,
+) -> Result<(), BanksClientError> {
+    let accounts = settings_update_accounts(candy_machine, authority, wallet);
+    let data = mpl_candy_machine::instruction::UpdateMintPhases { phases }.data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn update_mint_limit(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+    mint_limit: Option<MintLimit>,
+) -> Result<(), BanksClientError> {
+    let accounts = settings_update_accounts(candy_machine, authority, wallet);
+    let data = mpl_candy_machine::instruction::UpdateMintLimit { mint_limit }.data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn update_payment_options(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+    payment_options: Vec<PaymentOption>,
+) -> Result<(), BanksClientError> {
+    let accounts = settings_update_accounts(candy_machine, authority, wallet);
+    let data = mpl_candy_machine::instruction::UpdatePaymentOptions { payment_options }.data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
     };
 
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn update_commit_reveal(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+    commit_reveal: bool,
+) -> Result<(), BanksClientError> {
+    let accounts = settings_update_accounts(candy_machine, authority, wallet);
+    let data = mpl_candy_machine::instruction::UpdateCommitReveal { commit_reveal }.data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn update_on_chain_reveal(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+    on_chain_reveal: bool,
+) -> Result<(), BanksClientError> {
+    let accounts = settings_update_accounts(candy_machine, authority, wallet);
+    let data = mpl_candy_machine::instruction::UpdateOnChainReveal { on_chain_reveal }.data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn update_programmable(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+    programmable: Option<ProgrammableSettings>,
+) -> Result<(), BanksClientError> {
+    let accounts = settings_update_accounts(candy_machine, authority, wallet);
+    let data = mpl_candy_machine::instruction::UpdateProgrammable { programmable }.data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn update_bot_tax(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+    bot_tax: Option<BotTax>,
+) -> Result<(), BanksClientError> {
+    let accounts = settings_update_accounts(candy_machine, authority, wallet);
+    let data = mpl_candy_machine::instruction::UpdateBotTax { bot_tax }.data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn update_freeze_refund(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+    freeze_refund: bool,
+) -> Result<(), BanksClientError> {
+    let accounts = settings_update_accounts(candy_machine, authority, wallet);
+    let data = mpl_candy_machine::instruction::UpdateFreezeRefund { freeze_refund }.data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn update_mint_receipts(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+    mint_receipts: bool,
+) -> Result<(), BanksClientError> {
+    let accounts = settings_update_accounts(candy_machine, authority, wallet);
+    let data = mpl_candy_machine::instruction::UpdateMintReceipts { mint_receipts }.data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn migrate_features(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    wallet: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let accounts = mpl_candy_machine::accounts::UpdateCandyMachine {
+        candy_machine: *candy_machine,
+        authority: authority.pubkey(),
+        wallet: *wallet,
+    }
+    .to_account_metas(None);
+    let data = mpl_candy_machine::instruction::MigrateFeatures {}.data();
+    let update_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[update_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn commit_mint(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    payer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let accounts = mpl_candy_machine::accounts::CommitMint {
+        candy_machine: *candy_machine,
+        mint_commitment: find_mint_commitment(candy_machine, &payer.pubkey()),
+        payer: payer.pubkey(),
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    let data = mpl_candy_machine::instruction::CommitMint {}.data();
+    let commit_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[commit_ix],
+        Some(&payer.pubkey()),
+        &[payer],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn close_mint_commitment(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    payer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let accounts = mpl_candy_machine::accounts::CloseMintCommitment {
+        candy_machine: *candy_machine,
+        mint_commitment: find_mint_commitment(candy_machine, &payer.pubkey()),
+        payer: payer.pubkey(),
+        recent_slothashes: sysvar::slot_hashes::id(),
+    }
+    .to_account_metas(None);
+    let data = mpl_candy_machine::instruction::CloseMintCommitment {}.data();
+    let close_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix],
+        Some(&payer.pubkey()),
+        &[payer],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn initialize_reveal_lines(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    items_available: u64,
+) -> Result<Pubkey, BanksClientError> {
+    let reveal_lines = Keypair::new();
+    let space = RevealLines::space(items_available).unwrap();
     let rent = context.banks_client.get_rent().await?;
-    let lamports = rent.minimum_balance(candy_account_size);
     let create_ix = system_instruction::create_account(
-        &payer.pubkey(),
-        &candy_account.pubkey(),
-        lamports,
-        candy_account_size as u64,
+        &authority.pubkey(),
+        &reveal_lines.pubkey(),
+        rent.minimum_balance(space),
+        space as u64,
         &mpl_candy_machine::id(),
     );
 
-    let mut accounts = mpl_candy_machine::accounts::InitializeCandyMachine {
-        candy_machine: candy_account.pubkey(),
-        wallet: *wallet,
-        authority: payer.pubkey(),
-        payer: payer.pubkey(),
-        system_program: system_program::id(),
-        rent: sysvar::rent::id(),
+    let accounts = mpl_candy_machine::accounts::InitializeRevealLines {
+        candy_machine: *candy_machine,
+        authority: authority.pubkey(),
+        reveal_lines: reveal_lines.pubkey(),
     }
     .to_account_metas(None);
-
-    if token_info.set {
-        accounts.push(AccountMeta::new_readonly(token_info.mint, false));
-    }
-
-    let data = mpl_candy_machine::instruction::InitializeCandyMachine { data: candy_data }.data();
-
+    let data = mpl_candy_machine::instruction::InitializeRevealLines {}.data();
     let init_ix = Instruction {
         program_id: mpl_candy_machine::id(),
         data,
@@ -85,34 +565,35 @@ pub async fn initialize_candy_machine(
     update_blockhash(context).await?;
     let tx = Transaction::new_signed_with_payer(
         &[create_ix, init_ix],
-        Some(&payer.pubkey()),
-        &[candy_account, payer],
+        Some(&authority.pubkey()),
+        &[authority, &reveal_lines],
         context.last_blockhash,
     );
 
-    context.banks_client.process_transaction(tx).await
+    context.banks_client.process_transaction(tx).await?;
+    Ok(reveal_lines.pubkey())
 }
 
-pub async fn update_candy_machine(
+pub async fn add_reveal_lines(
     context: &mut ProgramTestContext,
     candy_machine: &Pubkey,
     authority: &Keypair,
-    data: CandyMachineData,
-    wallet: &Pubkey,
-    token_mint: Option<Pubkey>,
+    reveal_lines: &Pubkey,
+    index: u64,
+    config_lines: Vec<ConfigLine>,
 ) -> Result<(), BanksClientError> {
-    let mut accounts = mpl_candy_machine::accounts::UpdateCandyMachine {
+    let accounts = mpl_candy_machine::accounts::AddRevealLines {
         candy_machine: *candy_machine,
         authority: authority.pubkey(),
-        wallet: *wallet,
+        reveal_lines: *reveal_lines,
     }
     .to_account_metas(None);
-    if let Some(token_mint) = token_mint {
-        accounts.push(AccountMeta::new_readonly(token_mint, false));
+    let data = mpl_candy_machine::instruction::AddRevealLines {
+        index,
+        config_lines,
     }
-
-    let data = mpl_candy_machine::instruction::UpdateCandyMachine { data }.data();
-    let update_ix = Instruction {
+    .data();
+    let add_ix = Instruction {
         program_id: mpl_candy_machine::id(),
         data,
         accounts,
@@ -120,7 +601,7 @@ pub async fn update_candy_machine(
 
     update_blockhash(context).await?;
     let tx = Transaction::new_signed_with_payer(
-        &[update_ix],
+        &[add_ix],
         Some(&authority.pubkey()),
         &[authority],
         context.last_blockhash,
@@ -129,24 +610,65 @@ pub async fn update_candy_machine(
     context.banks_client.process_transaction(tx).await
 }
 
-pub async fn update_authority(
+pub async fn reveal_nft(
     context: &mut ProgramTestContext,
     candy_machine: &Pubkey,
-    authority: &Keypair,
-    wallet: &Pubkey,
-    new_authority: &Pubkey,
+    reveal_lines: &Pubkey,
+    metadata: &Pubkey,
+    payer: &Keypair,
 ) -> Result<(), BanksClientError> {
-    let accounts = mpl_candy_machine::accounts::UpdateCandyMachine {
+    let accounts = mpl_candy_machine::accounts::RevealNFT {
         candy_machine: *candy_machine,
-        authority: authority.pubkey(),
-        wallet: *wallet,
+        candy_machine_creator: find_candy_creator(candy_machine).0,
+        reveal_lines: *reveal_lines,
+        metadata: *metadata,
+        token_metadata_program: mpl_token_metadata::id(),
     }
     .to_account_metas(None);
-    let data = mpl_candy_machine::instruction::UpdateAuthority {
-        new_authority: Some(*new_authority),
+    let data = mpl_candy_machine::instruction::RevealNft {}.data();
+    let reveal_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[reveal_ix],
+        Some(&payer.pubkey()),
+        &[payer],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn submit_allowlist_proof(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    wallet: &Keypair,
+    phase_index: u8,
+    entry: AllowlistEntry,
+    proof: Vec<[u8; 32]>,
+) -> Result<(), BanksClientError> {
+    let accounts = mpl_candy_machine::accounts::SubmitAllowlistProof {
+        candy_machine: *candy_machine,
+        allowlist_proof: PhaseInfo::find_allowlist_proof(
+            candy_machine,
+            &wallet.pubkey(),
+            phase_index,
+        ),
+        wallet: wallet.pubkey(),
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    let data = mpl_candy_machine::instruction::SubmitAllowlistProof {
+        phase_index,
+        entry,
+        proof,
     }
     .data();
-    let update_ix = Instruction {
+    let submit_ix = Instruction {
         program_id: mpl_candy_machine::id(),
         data,
         accounts,
@@ -154,9 +676,9 @@ pub async fn update_authority(
 
     update_blockhash(context).await?;
     let tx = Transaction::new_signed_with_payer(
-        &[update_ix],
-        Some(&authority.pubkey()),
-        &[authority],
+        &[submit_ix],
+        Some(&wallet.pubkey()),
+        &[wallet],
         context.last_blockhash,
     );
 
@@ -200,6 +722,36 @@ pub async fn add_config_lines(
     context.banks_client.process_transaction(tx).await
 }
 
+pub async fn resize_candy_machine(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    authority: &Keypair,
+    items_available: u64,
+) -> Result<(), BanksClientError> {
+    let accounts = mpl_candy_machine::accounts::ResizeCandyMachine {
+        candy_machine: *candy_machine,
+        authority: authority.pubkey(),
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    let data = mpl_candy_machine::instruction::ResizeCandyMachine { items_available }.data();
+    let resize_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[resize_ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
 pub async fn add_all_config_lines(
     context: &mut ProgramTestContext,
     candy_machine: &Pubkey,
@@ -375,14 +927,70 @@ pub async fn remove_freeze(
     context.banks_client.process_transaction(tx).await
 }
 
+pub async fn refund_nft(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    freeze_info: &FreezeInfo,
+    token_info: &TokenInfo,
+    nft_info: &MasterEditionManager,
+    mint_receipts: bool,
+) -> Result<(), BanksClientError> {
+    let mint = nft_info.mint.pubkey();
+    let mut accounts = mpl_candy_machine::accounts::RefundNFT {
+        freeze_pda: freeze_info.pda,
+        candy_machine: *candy_machine,
+        refund_record: find_refund_record(candy_machine, &mint),
+        token_account: nft_info.token_account,
+        owner: nft_info.owner.pubkey(),
+        mint,
+        metadata: nft_info.metadata_pubkey,
+        edition: nft_info.edition_pubkey,
+        token_program: spl_token::ID,
+        token_metadata_program: mpl_token_metadata::ID,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+
+    if token_info.set {
+        accounts.push(AccountMeta::new(
+            freeze_info.find_freeze_ata(&token_info.mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new(token_info.minter_account, false));
+    }
+    if mint_receipts {
+        accounts.push(AccountMeta::new(
+            find_mint_receipt(candy_machine, &mint),
+            false,
+        ));
+    }
+
+    let data = mpl_candy_machine::instruction::RefundNft {}.data();
+    let refund_ix = Instruction {
+        program_id: mpl_candy_machine::id(),
+        data,
+        accounts,
+    };
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &[refund_ix],
+        Some(&nft_info.owner.pubkey()),
+        &[&nft_info.owner],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
 pub async fn thaw_nft(
     context: &mut ProgramTestContext,
     candy_machine: &Pubkey,
     signer: &Keypair,
     freeze_info: &FreezeInfo,
     nft_info: &MasterEditionManager,
+    programmable: bool,
 ) -> Result<(), BanksClientError> {
-    let accounts = mpl_candy_machine::accounts::ThawNFT {
+    let mut accounts = mpl_candy_machine::accounts::ThawNFT {
         freeze_pda: freeze_info.pda,
         candy_machine: *candy_machine,
         token_account: nft_info.token_account,
@@ -396,6 +1004,15 @@ pub async fn thaw_nft(
     }
     .to_account_metas(None);
 
+    if programmable {
+        accounts.push(AccountMeta::new(nft_info.metadata_pubkey, false));
+        accounts.push(AccountMeta::new(
+            find_token_record_account(&nft_info.mint.pubkey(), &nft_info.token_account).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    }
+
     let data = mpl_candy_machine::instruction::ThawNft {}.data();
     let set_ix = Instruction {
         program_id: mpl_candy_machine::id(),
@@ -501,6 +1118,12 @@ pub fn mint_nft_ix(
     collection_info: CollectionInfo,
     gateway_info: GatekeeperInfo,
     freeze_info: FreezeInfo,
+    mint_limit_info: MintLimitInfo,
+    phase_info: PhaseInfo,
+    payment_info: PaymentInfo,
+    mint_commitment: Option<Pubkey>,
+    programmable: Option<ProgrammableSettings>,
+    mint_receipts: bool,
 ) -> Vec<Instruction> {
     let metadata = new_nft.metadata_pubkey;
     let master_edition = new_nft.edition_pubkey;
@@ -526,6 +1149,24 @@ pub fn mint_nft_ix(
     }
     .to_account_metas(None);
 
+    if let Some(gate_token_account) = phase_info.gate_token_account {
+        accounts.push(AccountMeta::new_readonly(gate_token_account, false));
+    }
+    if let Some(allowlist_proof) = phase_info.allowlist_proof {
+        accounts.push(AccountMeta::new_readonly(allowlist_proof, false));
+    }
+    if let Some(mint_counter) = phase_info.mint_counter {
+        accounts.push(AccountMeta::new(mint_counter, false));
+    }
+
+    if mint_limit_info.set {
+        accounts.push(AccountMeta::new(mint_limit_info.mint_counter, false));
+    }
+
+    if let Some(mint_commitment) = mint_commitment {
+        accounts.push(AccountMeta::new(mint_commitment, false));
+    }
+
     if gateway_info.set {
         accounts.push(AccountMeta::new(gateway_info.gateway_token_info, false));
 
@@ -545,7 +1186,9 @@ pub fn mint_nft_ix(
         }
     }
 
-    if token_info.set {
+    if payment_info.payment_option.is_some() {
+        accounts.extend(payment_info.accounts);
+    } else if token_info.set {
         accounts.push(AccountMeta::new(token_info.minter_account, false));
         accounts.push(AccountMeta::new_readonly(payer.pubkey(), false));
     }
@@ -561,7 +1204,44 @@ pub fn mint_nft_ix(
         }
     }
 
-    let data = mpl_candy_machine::instruction::MintNft { creator_bump }.data();
+    if freeze_info.set && freeze_info.refund {
+        accounts.push(AccountMeta::new(
+            find_refund_record(candy_machine, &mint),
+            false,
+        ));
+    }
+
+    if let Some(programmable) = programmable {
+        assert!(
+            programmable.rule_set.is_none(),
+            "Rule sets are not supported by the test helpers"
+        );
+        accounts.push(AccountMeta::new(new_nft.token_account, false));
+        accounts.push(AccountMeta::new(
+            find_token_record_account(&mint, &new_nft.token_account).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            spl_associated_token_account::id(),
+            false,
+        ));
+    }
+
+    if mint_receipts {
+        accounts.push(AccountMeta::new(
+            find_mint_receipt(candy_machine, &mint),
+            false,
+        ));
+    }
+
+    let data = match payment_info.payment_option {
+        Some(payment_option) => mpl_candy_machine::instruction::MintNftWithPayment {
+            creator_bump,
+            payment_option,
+        }
+        .data(),
+        None => mpl_candy_machine::instruction::MintNft { creator_bump }.data(),
+    };
 
     let mut instructions = Vec::new();
 
@@ -620,6 +1300,12 @@ pub async fn mint_nft(
     collection_info: CollectionInfo,
     gateway_info: GatekeeperInfo,
     freeze_info: FreezeInfo,
+    mint_limit_info: MintLimitInfo,
+    phase_info: PhaseInfo,
+    payment_info: PaymentInfo,
+    mint_commitment: Option<Pubkey>,
+    programmable: Option<ProgrammableSettings>,
+    mint_receipts: bool,
 ) -> Result<(), BanksClientError> {
     let ins = mint_nft_ix(
         candy_machine,
@@ -634,6 +1320,151 @@ pub async fn mint_nft(
         collection_info,
         gateway_info,
         freeze_info,
+        mint_limit_info,
+        phase_info,
+        payment_info,
+        mint_commitment,
+        programmable,
+        mint_receipts,
+    );
+    let signers = vec![payer];
+    update_blockhash(context).await?;
+    let tx = Transaction::new_signed_with_payer(
+        &ins,
+        Some(&payer.pubkey()),
+        &signers,
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn mint_nfts_ix(
+    candy_machine: &Pubkey,
+    candy_creator_pda: &Pubkey,
+    creator_bump: u8,
+    wallet: &Pubkey,
+    authority: &Pubkey,
+    payer: &Keypair,
+    new_nfts: &[MasterEditionManager],
+    token_info: TokenInfo,
+    whitelist_info: WhitelistInfo,
+    collection_info: CollectionInfo,
+    gateway_info: GatekeeperInfo,
+    freeze_info: FreezeInfo,
+    mint_limit_info: MintLimitInfo,
+    phase_info: PhaseInfo,
+    payment_info: PaymentInfo,
+    programmable: Option<ProgrammableSettings>,
+    mint_receipts: bool,
+) -> Vec<Instruction> {
+    let payment_option = payment_info.payment_option.unwrap_or(0);
+    let mut ins = mint_nft_ix(
+        candy_machine,
+        candy_creator_pda,
+        creator_bump,
+        wallet,
+        authority,
+        payer,
+        &new_nfts[0],
+        token_info,
+        whitelist_info,
+        collection_info,
+        gateway_info,
+        freeze_info.clone(),
+        mint_limit_info,
+        phase_info,
+        payment_info,
+        None,
+        programmable.clone(),
+        mint_receipts,
+    );
+    let mint_ix = &mut ins[0];
+    for new_nft in &new_nfts[1..] {
+        let mint = new_nft.mint.pubkey();
+        mint_ix
+            .accounts
+            .push(AccountMeta::new(new_nft.metadata_pubkey, false));
+        mint_ix.accounts.push(AccountMeta::new(mint, false));
+        mint_ix
+            .accounts
+            .push(AccountMeta::new(new_nft.edition_pubkey, false));
+        if freeze_info.set {
+            mint_ix
+                .accounts
+                .push(AccountMeta::new(new_nft.token_account, false));
+            if freeze_info.refund {
+                mint_ix.accounts.push(AccountMeta::new(
+                    find_refund_record(candy_machine, &mint),
+                    false,
+                ));
+            }
+        }
+        if programmable.is_some() {
+            mint_ix
+                .accounts
+                .push(AccountMeta::new(new_nft.token_account, false));
+            mint_ix.accounts.push(AccountMeta::new(
+                find_token_record_account(&mint, &new_nft.token_account).0,
+                false,
+            ));
+        }
+        if mint_receipts {
+            mint_ix.accounts.push(AccountMeta::new(
+                find_mint_receipt(candy_machine, &mint),
+                false,
+            ));
+        }
+    }
+    mint_ix.data = mpl_candy_machine::instruction::MintNfts {
+        creator_bump,
+        payment_option,
+        count: new_nfts.len() as u8,
+    }
+    .data();
+    ins
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn mint_nfts(
+    context: &mut ProgramTestContext,
+    candy_machine: &Pubkey,
+    candy_creator_pda: &Pubkey,
+    creator_bump: u8,
+    wallet: &Pubkey,
+    authority: &Pubkey,
+    payer: &Keypair,
+    new_nfts: &[MasterEditionManager],
+    token_info: TokenInfo,
+    whitelist_info: WhitelistInfo,
+    collection_info: CollectionInfo,
+    gateway_info: GatekeeperInfo,
+    freeze_info: FreezeInfo,
+    mint_limit_info: MintLimitInfo,
+    phase_info: PhaseInfo,
+    payment_info: PaymentInfo,
+    programmable: Option<ProgrammableSettings>,
+    mint_receipts: bool,
+) -> Result<(), BanksClientError> {
+    let ins = mint_nfts_ix(
+        candy_machine,
+        candy_creator_pda,
+        creator_bump,
+        wallet,
+        authority,
+        payer,
+        new_nfts,
+        token_info,
+        whitelist_info,
+        collection_info,
+        gateway_info,
+        freeze_info,
+        mint_limit_info,
+        phase_info,
+        payment_info,
+        programmable,
+        mint_receipts,
     );
     let signers = vec![payer];
     update_blockhash(context).await?;